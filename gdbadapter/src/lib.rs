@@ -1,18 +1,24 @@
-/// GDB Adapter module for Katori
-/// 
-/// This module handles communication with GDB using GDB/MI (Machine Interface)
-/// and provides a high-level API for debugging operations.
+//! GDB Adapter module for Katori
+//! 
+//! This module handles communication with GDB using GDB/MI (Machine Interface)
+//! and provides a high-level API for debugging operations.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, ChildStdin, ChildStdout, Command};
-use tokio::sync::{mpsc, oneshot};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
 use thiserror::Error;
 
+pub mod mock;
+mod milog;
 pub mod parser;
+#[cfg(unix)]
+mod tty;
 pub mod types;
 
 pub use types::*;
@@ -24,6 +30,15 @@ pub enum GdbError {
     ProcessStartError(#[from] std::io::Error),
     #[error("GDB command failed: {0}")]
     CommandError(String),
+    /// No executable or symbol file is loaded, so symbols can't be looked up
+    #[error("No symbols loaded: {0}")]
+    NoSymbols(String),
+    /// The command needs a live process (registers, stack, stepping)
+    #[error("The program is not running: {0}")]
+    NotRunning(String),
+    /// GDB doesn't know the command (`code="undefined-command"`)
+    #[error("Unknown GDB command: {0}")]
+    UnknownCommand(String),
     #[error("Failed to parse GDB output: {0}")]
     ParseError(String),
     #[error("GDB process terminated unexpectedly")]
@@ -32,22 +47,174 @@ pub enum GdbError {
     CommunicationError(String),
 }
 
+impl GdbError {
+    /// Classify an `^error` record by its `code` field, or by the message for
+    /// conditions GDB reports without one
+    pub fn from_error_result(result: &GdbResult) -> Self {
+        let msg = result.results.get("msg")
+            .and_then(|v| v.as_string())
+            .unwrap_or("Unknown error")
+            .to_string();
+        let code = result.results.get("code").and_then(|v| v.as_string());
+        
+        const NO_SYMBOLS: &[&str] = &["No symbol table is loaded", "No symbol file"];
+        const NOT_RUNNING: &[&str] = &["The program is not being run", "No registers", "No stack", "No frame selected", "Target is not running"];
+        if code == Some("undefined-command") {
+            GdbError::UnknownCommand(msg)
+        } else if NO_SYMBOLS.iter().any(|pattern| msg.contains(pattern)) {
+            GdbError::NoSymbols(msg)
+        } else if NOT_RUNNING.iter().any(|pattern| msg.contains(pattern)) {
+            GdbError::NotRunning(msg)
+        } else {
+            GdbError::CommandError(msg)
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, GdbError>;
 
+/// Sent at the start of every session so GDB never waits for input MI can't give it:
+/// confirmation prompts (`kill`, `delete`) and pagination would otherwise stall
+pub const DEFAULT_STARTUP_COMMANDS: &[&str] = &[
+    "gdb-set confirm off",
+    "gdb-set pagination off",
+    "gdb-set height 0",
+    // Lets variable objects use pretty-printers, e.g. to list a std::vector's elements
+    "enable-pretty-printing",
+];
+
+/// Events buffered per subscriber before the slowest one starts lagging
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Longest MI record the reader keeps. Big memory reads and register dumps fit
+/// easily; anything longer is dropped rather than growing without bound.
+pub const MAX_MI_LINE_LENGTH: usize = 16 * 1024 * 1024;
+
+/// How long `stop_target` waits for the target to stop after interrupting it
+pub const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Bytes the start of a disassembly window before `$pc` may be moved forward to
+/// land on an instruction boundary; x86 instructions are at most 15 bytes long
+const MAX_ALIGN_SHIFT: u64 = 15;
+
+/// Fails the commands waiting for a reply, without needing the adapter itself.
+///
+/// A command such as `exec-continue` can hold the adapter (and whatever lock
+/// guards it) for a long time; cancelling its reply lets session teardown go
+/// ahead instead of queueing behind it.
+#[derive(Clone)]
+pub struct CommandCanceller {
+    pending_commands: Arc<Mutex<HashMap<u32, oneshot::Sender<GdbResult>>>>,
+}
+
+impl CommandCanceller {
+    /// Drop every pending reply sender, so their `send_command` calls return an
+    /// error. Returns how many commands were waiting.
+    pub fn cancel_pending(&self) -> usize {
+        let mut pending = self.pending_commands.lock().unwrap();
+        let count = pending.len();
+        pending.clear();
+        count
+    }
+}
+
+/// Round-trips kept for the rolling average
+pub const LATENCY_SAMPLES: usize = 16;
+
+/// Round-trip times of recent commands, from writing the command to its reply.
+///
+/// Shares the adapter's samples, so it can be read while a long-running
+/// command holds the adapter.
+#[derive(Clone, Default)]
+pub struct LatencyMonitor {
+    samples: Arc<Mutex<VecDeque<Duration>>>,
+}
+
+impl LatencyMonitor {
+    fn record(&self, latency: Duration) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == LATENCY_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+    
+    /// Round-trip of the most recent reply
+    pub fn last(&self) -> Option<Duration> {
+        self.samples.lock().unwrap().back().copied()
+    }
+    
+    /// Average over the last `LATENCY_SAMPLES` replies
+    pub fn average(&self) -> Option<Duration> {
+        let samples = self.samples.lock().unwrap();
+        let count = u32::try_from(samples.len()).ok().filter(|&count| count > 0)?;
+        Some(samples.iter().sum::<Duration>() / count)
+    }
+}
+
 /// Main GDB adapter that manages the GDB process and communication
 pub struct GdbAdapter {
     process: Option<Child>,
-    stdin: Option<ChildStdin>,
-    event_sender: mpsc::UnboundedSender<GdbEvent>,
+    stdin: Option<Box<dyn AsyncWrite + Send + Sync + Unpin>>,
+    /// Events not claimed by a pending command, fanned out to every subscriber
+    event_sender: broadcast::Sender<GdbEvent>,
     token_counter: AtomicU32,
     pending_commands: Arc<Mutex<HashMap<u32, oneshot::Sender<GdbResult>>>>,
     is_running: Arc<Mutex<bool>>,
+    /// Threads the target is executing (`all` for a whole-target resume), tracked
+    /// from `*running`/`*stopped` records
+    target_running: watch::Sender<HashSet<String>>,
+    /// Default timeout applied by `send_command`, `None` waits indefinitely
+    command_timeout: Option<Duration>,
+    /// Format requested by `get_registers`
+    register_format: RegisterFormat,
+    /// Set listed by `get_registers`, and the register numbers making up the core set
+    register_set: RegisterSet,
+    core_registers: Option<Vec<u32>>,
+    /// Registers `get_registers` narrows the set down to, e.g. the ones a UI shows
+    register_subset: Option<Vec<u32>>,
+    /// Bytes per instruction assumed when sizing disassembly ranges; `None` picks
+    /// 2 for Thumb code and 4 otherwise
+    instruction_width: Option<u32>,
+    /// Whether the target is executing Thumb code, as last reported by `set_thumb`
+    thumb: bool,
+    /// MI async mode is on, so GDB keeps reading commands while the target runs
+    mi_async: bool,
+    /// Threads stop and resume individually; applied with MI async mode at session start
+    non_stop: bool,
+    /// Architecture forced with `set_architecture`, applied again at session start
+    architecture: Option<String>,
+    /// x86 syntax chosen with `set_disassembly_flavor`, applied again at session start
+    disassembly_flavor: Option<String>,
+    /// MI commands sent by `start_session` before anything else
+    startup_commands: Vec<String>,
+    /// Round-trip times of recent commands
+    latency: LatencyMonitor,
+    /// Function addresses found by `resolve_symbol`, until loading, running or attaching moves them
+    symbol_cache: HashMap<String, String>,
+    /// Architecture found by `target_architecture`, until another target is loaded
+    target_arch: Option<String>,
+    /// Where `disassemble_around_pc` found instructions lining up, keyed by PC and
+    /// window start; dropped with `symbol_cache` since the code may move too
+    aligned_starts: HashMap<(u64, u64), u64>,
+    /// Terminal the debugged program reads and writes, once `create_inferior_tty` made one
+    #[cfg(unix)]
+    inferior_tty: Option<tty::InferiorTty>,
+    /// Optional copy of every parsed record, including results routed to pending commands
+    event_tap: Arc<Mutex<Option<mpsc::UnboundedSender<GdbEvent>>>>,
+    /// Transcript of every MI line sent and received, once `set_mi_log` started one
+    mi_log: Arc<Mutex<Option<milog::MiLog>>>,
+    /// Console output of an internal CLI command, kept out of the event stream while collecting
+    console_capture: Arc<Mutex<Option<String>>>,
 }
 
 impl GdbAdapter {
     /// Create a new GDB adapter instance - returns (adapter, event_receiver)
-    pub fn new() -> (Self, mpsc::UnboundedReceiver<GdbEvent>) {
-        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+    ///
+    /// The receiver is an ordinary subscription; more can be made with `subscribe`.
+    pub fn new() -> (Self, broadcast::Receiver<GdbEvent>) {
+        let (event_sender, event_receiver) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (target_running, _) = watch::channel(HashSet::new());
         
         // Install custom Ctrl+C handler on Windows to prevent self-termination
         #[cfg(windows)]
@@ -60,6 +227,28 @@ impl GdbAdapter {
             token_counter: AtomicU32::new(1),
             pending_commands: Arc::new(Mutex::new(HashMap::new())),
             is_running: Arc::new(Mutex::new(false)),
+            target_running,
+            command_timeout: None,
+            register_format: RegisterFormat::default(),
+            register_set: RegisterSet::default(),
+            core_registers: None,
+            register_subset: None,
+            instruction_width: None,
+            thumb: false,
+            mi_async: false,
+            non_stop: false,
+            architecture: None,
+            disassembly_flavor: None,
+            startup_commands: DEFAULT_STARTUP_COMMANDS.iter().map(|c| c.to_string()).collect(),
+            latency: LatencyMonitor::default(),
+            symbol_cache: HashMap::new(),
+            aligned_starts: HashMap::new(),
+            target_arch: None,
+            #[cfg(unix)]
+            inferior_tty: None,
+            event_tap: Arc::new(Mutex::new(None)),
+            mi_log: Arc::new(Mutex::new(None)),
+            console_capture: Arc::new(Mutex::new(None)),
         };
         
         (adapter, event_receiver)
@@ -128,49 +317,140 @@ impl GdbAdapter {
             GdbError::CommunicationError("Failed to get stderr handle".into())
         })?;
         
-        self.stdin = Some(stdin);
         self.process = Some(process);
-        
-        // Start the output reader task for stdout
-        self.start_output_reader(stdout).await;
+        self.connect(stdout, stdin).await;
         
         // Start the stderr reader task
         self.start_stderr_reader(stderr).await;
         
-        *self.is_running.lock().unwrap() = true;
+        self.apply_startup_commands().await;
         
         Ok(())
     }
     
+    /// Run a session over any MI streams instead of a GDB process the adapter
+    /// starts, e.g. a `mock::MockGdb` in tests. `output` is what GDB prints and
+    /// `input` is where commands are written; the startup commands are sent as usual.
+    pub async fn start_session_with_io<R, W>(&mut self, output: R, input: W) -> Result<()>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Send + Sync + Unpin + 'static,
+    {
+        if self.is_running() {
+            return Err(GdbError::CommandError("GDB session already running".into()));
+        }
+        
+        self.connect(output, input).await;
+        self.apply_startup_commands().await;
+        
+        Ok(())
+    }
+    
+    /// Take GDB's input and start reading its output, marking the session running
+    async fn connect<R, W>(&mut self, stdout: R, stdin: W)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Send + Sync + Unpin + 'static,
+    {
+        self.stdin = Some(Box::new(stdin));
+        
+        // Mark the session running before the readers start, they loop while it is set
+        *self.is_running.lock().unwrap() = true;
+        
+        // Start the output reader task for stdout
+        self.start_output_reader(stdout).await;
+    }
+    
+    /// Send the startup commands, logging (but not failing on) any GDB rejects
+    async fn apply_startup_commands(&mut self) {
+        let commands = self.startup_commands.clone();
+        let results = self.send_commands_pipelined(&commands).await;
+        for (command, result) in commands.iter().zip(results) {
+            if let Err(e) = result {
+                log::warn!("start_session: Startup command '{}' failed: {}", command, e);
+            }
+        }
+        if self.non_stop {
+            if let Err(e) = self.apply_non_stop().await {
+                log::warn!("start_session: Enabling non-stop mode failed: {}", e);
+            }
+        }
+        if let Some(arch) = self.architecture.clone() {
+            if let Err(e) = self.send_command(&format!("gdb-set architecture {}", arch)).await {
+                log::warn!("start_session: Setting architecture {} failed: {}", arch, e);
+            }
+        }
+        if let Some(flavor) = self.disassembly_flavor.clone() {
+            if let Err(e) = self.send_command(&format!("gdb-set disassembly-flavor {}", flavor)).await {
+                log::warn!("start_session: Setting disassembly flavor {} failed: {}", flavor, e);
+            }
+        }
+    }
+    
     /// Start the output reader task that processes GDB output
-    async fn start_output_reader(&self, stdout: ChildStdout) {
+    async fn start_output_reader<R>(&self, stdout: R)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
         log::trace!("start_output_reader: Starting output reader task");
         let event_sender = self.event_sender.clone();
         let pending_commands = self.pending_commands.clone();
         let is_running = self.is_running.clone();
+        let target_running = self.target_running.clone();
+        let event_tap = self.event_tap.clone();
+        let mi_log = self.mi_log.clone();
+        let console_capture = self.console_capture.clone();
         
         tokio::spawn(async move {
             log::trace!("start_output_reader: Output reader task started");
             let mut reader = BufReader::new(stdout);
-            let mut line = String::new();
+            let mut line = Vec::new();
             
             while *is_running.lock().unwrap() {
-                line.clear();
                 log::trace!("start_output_reader: Waiting for next line from GDB...");
-                match reader.read_line(&mut line).await {
-                    Ok(0) => {
+                match read_mi_line(&mut reader, &mut line, MAX_MI_LINE_LENGTH).await {
+                    Ok(MiLine::Eof) => {
                         log::trace!("start_output_reader: EOF reached, breaking");
                         break; // EOF
                     }
-                    Ok(bytes_read) => {
-                        let trimmed = line.trim();
-                        log::trace!("start_output_reader: Read {} bytes: '{}'", bytes_read, trimmed);
+                    Ok(MiLine::TooLong) => {
+                        log::warn!("start_output_reader: Dropped an MI record longer than {} bytes", MAX_MI_LINE_LENGTH);
+                        // Fail the command it answered instead of leaving it waiting
+                        if let Some(token) = result_token(&line) {
+                            if let Some(sender) = pending_commands.lock().unwrap().remove(&token) {
+                                let _ = sender.send(GdbResult {
+                                    token: Some(token),
+                                    class: ResultClass::Error,
+                                    results: HashMap::from([(
+                                        "msg".to_string(),
+                                        Value::String(format!("Reply longer than {} bytes", MAX_MI_LINE_LENGTH)),
+                                    )]),
+                                });
+                            }
+                        }
+                    }
+                    Ok(MiLine::Line) => {
+                        // Invalid UTF-8 (e.g. in a program's strings) shouldn't end the session
+                        let text = String::from_utf8_lossy(&line);
+                        let trimmed = text.trim();
+                        log::trace!("start_output_reader: Read {} bytes: '{}'", line.len(), trimmed);
                         
                         if !trimmed.is_empty() {
+                            if let Some(log) = mi_log.lock().unwrap().as_ref() {
+                                log.received(trimmed);
+                            }
                             log::trace!("start_output_reader: Parsing GDB output: '{}'", trimmed);
                             match parse_gdb_output(trimmed) {
                                 Ok(output) => {
                                     log::trace!("start_output_reader: Successfully parsed output: {:?}", output);
+                                    if let Some(tap) = event_tap.lock().unwrap().as_ref() {
+                                        let _ = tap.send(match &output {
+                                            GdbOutput::Result(result) => GdbEvent::Result(result.clone()),
+                                            GdbOutput::Async(record) => GdbEvent::Async(record.clone()),
+                                            GdbOutput::Stream(stream) => GdbEvent::Stream(stream.clone()),
+                                            GdbOutput::Prompt => GdbEvent::Prompt,
+                                        });
+                                    }
                                     match output {
                                         GdbOutput::Result(result) => {
                                             log::trace!("start_output_reader: Processing result with token: {:?}", result.token);
@@ -188,11 +468,26 @@ impl GdbAdapter {
                                         }
                                         GdbOutput::Async(async_record) => {
                                             log::trace!("start_output_reader: Processing async record: {:?}", async_record);
+                                            match async_record.class {
+                                                AsyncClass::Running | AsyncClass::Stopped => {
+                                                    target_running.send_modify(|threads| update_running_threads(threads, &async_record));
+                                                }
+                                                _ => {}
+                                            }
                                             let _ = event_sender.send(GdbEvent::Async(async_record));
                                         }
                                         GdbOutput::Stream(stream) => {
                                             log::trace!("start_output_reader: Processing stream: {:?}", stream);
-                                            let _ = event_sender.send(GdbEvent::Stream(stream));
+                                            match console_capture.lock().unwrap().as_mut() {
+                                                Some(text) if stream.stream_type == StreamType::Console => text.push_str(&stream.content),
+                                                _ => {
+                                                    let _ = event_sender.send(GdbEvent::Stream(stream));
+                                                }
+                                            }
+                                        }
+                                        GdbOutput::Prompt => {
+                                            log::trace!("start_output_reader: GDB is ready for input");
+                                            let _ = event_sender.send(GdbEvent::Prompt);
                                         }
                                     }
                                 }
@@ -210,6 +505,19 @@ impl GdbAdapter {
                     }
                 }
             }
+            
+            // If the session is still marked running, nobody asked us to stop: GDB went away
+            let lost = std::mem::replace(&mut *is_running.lock().unwrap(), false);
+            if lost {
+                log::warn!("start_output_reader: GDB output closed unexpectedly, marking session as lost");
+                // Nobody will answer the outstanding commands; dropping their senders fails them
+                pending_commands.lock().unwrap().clear();
+                target_running.send_replace(HashSet::new());
+                let _ = event_sender.send(GdbEvent::ConnectionLost);
+                if let Some(tap) = event_tap.lock().unwrap().as_ref() {
+                    let _ = tap.send(GdbEvent::ConnectionLost);
+                }
+            }
             log::trace!("start_output_reader: Output reader task finished");
         });
     }
@@ -249,6 +557,159 @@ impl GdbAdapter {
     
     /// Send a command to GDB and wait for the result
     pub async fn send_command(&mut self, command: &str) -> Result<GdbResult> {
+        self.send_command_inner(command, self.command_timeout).await
+    }
+    
+    /// Send a command, giving up with `CommandError("timeout")` if GDB doesn't reply in time
+    pub async fn send_command_timeout(&mut self, command: &str, timeout: Duration) -> Result<GdbResult> {
+        self.send_command_inner(command, Some(timeout)).await
+    }
+    
+    /// Subscribe to GDB events (async/stream records and connection loss).
+    ///
+    /// Each receiver gets every event sent after it subscribed. A receiver that
+    /// falls more than `EVENT_CHANNEL_CAPACITY` events behind gets `Lagged`.
+    pub fn subscribe(&self) -> broadcast::Receiver<GdbEvent> {
+        self.event_sender.subscribe()
+    }
+    
+    /// Handle that cancels pending commands from outside the adapter
+    pub fn canceller(&self) -> CommandCanceller {
+        CommandCanceller { pending_commands: self.pending_commands.clone() }
+    }
+    
+    /// Round-trip of the most recent command, from write to reply
+    pub fn last_command_latency(&self) -> Option<Duration> {
+        self.latency.last()
+    }
+    
+    /// Handle on command round-trip times (last and rolling average) usable from outside the adapter
+    pub fn latency_monitor(&self) -> LatencyMonitor {
+        self.latency.clone()
+    }
+    
+    /// Receive a copy of every record GDB emits, for diagnostics (`None` removes the tap)
+    pub fn set_event_tap(&mut self, tap: Option<mpsc::UnboundedSender<GdbEvent>>) {
+        *self.event_tap.lock().unwrap() = tap;
+    }
+    
+    /// Append every raw MI line sent and received to the file at `path`, with its
+    /// direction and a timestamp. Writing happens in the background, so a slow
+    /// disk never delays commands. Replaces any log already being written.
+    pub fn set_mi_log(&mut self, path: &Path) -> Result<()> {
+        let log = milog::MiLog::open(path)
+            .map_err(|e| GdbError::CommandError(format!("Failed to open MI log {}: {}", path.display(), e)))?;
+        *self.mi_log.lock().unwrap() = Some(log);
+        Ok(())
+    }
+    
+    /// Stop logging MI traffic; what was recorded is still written out
+    pub fn close_mi_log(&mut self) {
+        *self.mi_log.lock().unwrap() = None;
+    }
+    
+    pub fn is_logging_mi(&self) -> bool {
+        self.mi_log.lock().unwrap().is_some()
+    }
+    
+    fn log_sent(&self, line: &[u8]) {
+        if let Some(log) = self.mi_log.lock().unwrap().as_ref() {
+            log.sent(&String::from_utf8_lossy(line));
+        }
+    }
+    
+    /// Set the default timeout used by `send_command` (`None` to wait indefinitely)
+    pub fn set_command_timeout(&mut self, timeout: Option<Duration>) {
+        self.command_timeout = timeout;
+    }
+    
+    /// Replace the commands sent when a session starts (defaults to `DEFAULT_STARTUP_COMMANDS`)
+    pub fn set_startup_commands(&mut self, commands: Vec<String>) {
+        self.startup_commands = commands;
+    }
+    
+    /// Set the format `get_registers` asks GDB for
+    pub fn set_register_format(&mut self, format: RegisterFormat) {
+        self.register_format = format;
+    }
+    
+    pub fn register_format(&self) -> RegisterFormat {
+        self.register_format
+    }
+    
+    /// Set which registers `get_registers` lists. Clears the register subset,
+    /// which was picked from the old set.
+    pub fn set_register_set(&mut self, set: RegisterSet) {
+        self.register_set = set;
+        self.register_subset = None;
+    }
+    
+    pub fn register_set(&self) -> RegisterSet {
+        self.register_set
+    }
+    
+    /// Set the register numbers of the core set; GDB/MI has no notion of one,
+    /// so it's up to the caller (e.g. from the architecture). `None` means all.
+    pub fn set_core_registers(&mut self, numbers: Option<Vec<u32>>) {
+        self.core_registers = numbers;
+    }
+    
+    /// Have `get_registers` read only these register numbers, e.g. the ones on
+    /// screen, instead of the whole set. `None` or an empty list reads the set.
+    pub fn set_register_subset(&mut self, numbers: Option<Vec<u32>>) {
+        self.register_subset = numbers.filter(|numbers| !numbers.is_empty());
+    }
+    
+    pub fn register_subset(&self) -> Option<&[u32]> {
+        self.register_subset.as_deref()
+    }
+    
+    /// Override the instruction width used to size disassembly ranges, `None` to detect it
+    pub fn set_instruction_width(&mut self, width: Option<u32>) {
+        self.instruction_width = width;
+    }
+    
+    /// Record whether the target is in Thumb state (e.g. from the CPSR T bit).
+    ///
+    /// `-data-disassemble` only takes address ranges, so `lines` is turned into
+    /// bytes: Thumb instructions are mostly 2 bytes, and assuming 4 would fetch
+    /// twice the lines asked for.
+    pub fn set_thumb(&mut self, thumb: bool) {
+        self.thumb = thumb;
+    }
+    
+    /// Bytes per instruction used when disassembling a number of lines
+    pub fn instruction_width(&self) -> u32 {
+        self.instruction_width.unwrap_or(if self.thumb { 2 } else { 4 })
+    }
+    
+    async fn send_command_inner(&mut self, command: &str, timeout: Option<Duration>) -> Result<GdbResult> {
+        let (token, sent, receiver) = self.dispatch_command(command).await?;
+        self.await_reply(token, sent, receiver, command, timeout).await
+    }
+    
+    /// Send several commands without waiting between them, then collect the replies.
+    ///
+    /// Saves a round-trip per command on slow links. Each command gets its own
+    /// result, so one failing command doesn't affect the others.
+    pub async fn send_commands_pipelined(&mut self, commands: &[String]) -> Vec<Result<GdbResult>> {
+        let mut dispatched = Vec::with_capacity(commands.len());
+        for command in commands {
+            dispatched.push(self.dispatch_command(command).await);
+        }
+        
+        let mut results = Vec::with_capacity(commands.len());
+        for (command, sent) in commands.iter().zip(dispatched) {
+            results.push(match sent {
+                Ok((token, sent, receiver)) => self.await_reply(token, sent, receiver, command, self.command_timeout).await,
+                Err(e) => Err(e),
+            });
+        }
+        results
+    }
+    
+    /// Register a token for `command` and write it to GDB, noting when it was sent
+    async fn dispatch_command(&mut self, command: &str) -> Result<(u32, Instant, oneshot::Receiver<GdbResult>)> {
         log::trace!("send_command: Entering with command: '{}'", command);
         
         if !self.is_running() {
@@ -267,39 +728,61 @@ impl GdbAdapter {
         let command_line = format!("{}-{}\n", token, command);
         log::trace!("send_command: Formatted command line: '{}'", command_line.trim());
         
-        if let Some(ref mut stdin) = self.stdin {
-            log::trace!("send_command: Writing command to stdin...");
-            stdin.write_all(command_line.as_bytes()).await.map_err(|e| {
-                log::trace!("send_command: Failed to write command to stdin: {}", e);
-                GdbError::CommunicationError(format!("Failed to write command: {}", e))
-            })?;
-            
-            log::trace!("send_command: Flushing stdin...");
-            stdin.flush().await.map_err(|e| {
-                log::trace!("send_command: Failed to flush stdin: {}", e);
-                GdbError::CommunicationError(format!("Failed to flush command: {}", e))
-            })?;
-            
-            log::trace!("send_command: Command sent successfully, waiting for response...");
-        } else {
-            log::trace!("send_command: stdin is None, returning ProcessTerminated error");
-            return Err(GdbError::ProcessTerminated);
+        let written = match self.stdin {
+            Some(ref mut stdin) => {
+                log::trace!("send_command: Writing command to stdin...");
+                Self::write_command(stdin, command_line.as_bytes()).await
+            }
+            None => {
+                log::trace!("send_command: stdin is None, returning ProcessTerminated error");
+                Err(GdbError::ProcessTerminated)
+            }
+        };
+        if let Err(e) = written {
+            // No reply can come for a command GDB never received
+            self.pending_commands.lock().unwrap().remove(&token);
+            return Err(e);
         }
+        self.log_sent(command_line.as_bytes());
+        log::trace!("send_command: Command sent successfully, waiting for response...");
         
-        let result = receiver.await.map_err(|_| {
+        Ok((token, Instant::now(), receiver))
+    }
+    
+    /// Wait for the reply to a dispatched command, turning `^error` into a `GdbError`
+    async fn await_reply(
+        &self,
+        token: u32,
+        sent: Instant,
+        receiver: oneshot::Receiver<GdbResult>,
+        command: &str,
+        timeout: Option<Duration>,
+    ) -> Result<GdbResult> {
+        let received = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, receiver).await {
+                Ok(received) => received,
+                Err(_) => {
+                    // Forget the token so a late reply is dropped instead of leaking the sender
+                    self.pending_commands.lock().unwrap().remove(&token);
+                    log::warn!("send_command: Timed out waiting for token {} ({})", token, command);
+                    return Err(GdbError::CommandError("timeout".into()));
+                }
+            },
+            None => receiver.await,
+        };
+        let result = received.map_err(|_| {
             log::trace!("send_command: Command response channel closed for token {}", token);
             GdbError::CommunicationError("Command response channel closed".into())
         });
         
         log::trace!("send_command: Received result for token {}: {:?}", token, result);
         if let Ok(r) = result {
+            // Error replies still measure the link
+            self.latency.record(sent.elapsed());
             if r.class == ResultClass::Error {
-                let error_msg = r.results.get("msg")
-                    .and_then(|v| v.as_string())
-                    .unwrap_or("Unknown error")
-                    .to_string();
-                log::error!("send_command: GDB returned error: {}", error_msg);
-                return Err(GdbError::CommandError(error_msg));
+                let error = GdbError::from_error_result(&r);
+                log::error!("send_command: GDB returned error: {}", error);
+                return Err(error);
             } else {
                 return Ok(r);
             }
@@ -307,6 +790,36 @@ impl GdbAdapter {
         result
     }
     
+    /// Write and flush a command line, retrying once after a transient error.
+    /// Partial writes resume where they stopped so GDB never sees a duplicated prefix.
+    async fn write_command(stdin: &mut (dyn AsyncWrite + Send + Sync + Unpin), bytes: &[u8]) -> Result<()> {
+        let mut written = 0;
+        let mut retried = false;
+        
+        while written < bytes.len() {
+            match stdin.write(&bytes[written..]).await {
+                Ok(0) => {
+                    return Err(GdbError::CommunicationError("Failed to write command: pipe closed".into()));
+                }
+                Ok(n) => written += n,
+                Err(e) if !retried && is_transient(&e) => {
+                    log::warn!("send_command: Transient write error, retrying: {}", e);
+                    retried = true;
+                }
+                Err(e) => {
+                    log::trace!("send_command: Failed to write command to stdin: {}", e);
+                    return Err(GdbError::CommunicationError(format!("Failed to write command: {}", e)));
+                }
+            }
+        }
+        
+        log::trace!("send_command: Flushing stdin...");
+        stdin.flush().await.map_err(|e| {
+            log::trace!("send_command: Failed to flush stdin: {}", e);
+            GdbError::CommunicationError(format!("Failed to flush command: {}", e))
+        })
+    }
+    
     /// Stop the current GDB session
     pub async fn stop_session(&mut self) -> Result<()> {
         if !self.is_running() {
@@ -315,9 +828,25 @@ impl GdbAdapter {
         
         // Send quit command
         let _ = self.send_command("gdb-exit").await;
+        // The transcript of a session is most useful right after it ends
+        let mi_log = self.mi_log.lock().unwrap().clone();
+        if let Some(log) = mi_log {
+            log.flush().await;
+        }
         
         // Clean up
         *self.is_running.lock().unwrap() = false;
+        // Nothing will answer commands still waiting, e.g. a continue that never stopped
+        self.canceller().cancel_pending();
+        self.symbol_cache.clear();
+        self.aligned_starts.clear();
+        self.target_arch = None;
+        // The next target may number its registers differently
+        self.register_subset = None;
+        #[cfg(unix)]
+        {
+            self.inferior_tty = None;
+        }
         
         if let Some(mut process) = self.process.take() {
             let _ = process.kill().await;
@@ -330,75 +859,196 @@ impl GdbAdapter {
     
     /// Load an executable file
     pub async fn load_executable(&mut self, path: &str) -> Result<GdbResult> {
-        self.send_command(&format!("file-exec-and-symbols \"{}\"", path)).await
+        self.symbol_cache.clear();
+        self.aligned_starts.clear();
+        self.target_arch = None;
+        self.send_command(&format!("file-exec-and-symbols {}", quote_mi_string(path))).await
+    }
+
+    /// Load an executable's symbols and a core file it dumped, for post-mortem debugging
+    pub async fn load_core(&mut self, executable: &str, core: &str) -> Result<GdbResult> {
+        self.load_executable(executable).await?;
+        self.send_command(&format!("target-select core {}", quote_mi_string(core))).await
     }
 
     /// Attach to a running process by PID
     pub async fn attach_to_process(&mut self, pid: u32) -> Result<GdbResult> {
+        self.symbol_cache.clear();
+        self.aligned_starts.clear();
+        self.target_arch = None;
         self.send_command(&format!("target-attach {}", pid)).await
     }
 
     /// Attach to a remote GDB server
     pub async fn attach_to_gdbserver(&mut self, host_port: &str) -> Result<GdbResult> {
+        self.symbol_cache.clear();
+        self.aligned_starts.clear();
+        self.target_arch = None;
         self.send_command(&format!("target-select remote {}", host_port)).await
     }
 
+    /// Attach to a target and make sure it is stopped before returning.
+    ///
+    /// Depending on the stub, a gdbserver may leave the target running after
+    /// connecting; in that case it is interrupted and we wait for `*stopped` so
+    /// callers can immediately inspect registers, stack and memory.
+    pub async fn attach_and_stop(&mut self, target: &AttachTarget) -> Result<GdbResult> {
+        let result = match target {
+            AttachTarget::Process(pid) => self.attach_to_process(*pid).await?,
+            AttachTarget::GdbServer(host_port) => self.attach_to_gdbserver(host_port).await?,
+        };
+        
+        // Async records precede the result record, so the state is current by now
+        self.stop_target().await?;
+        
+        Ok(result)
+    }
+
+    /// Interrupt the target if it is running and wait for `*stopped`
+    pub async fn stop_target(&mut self) -> Result<()> {
+        if self.is_target_running() {
+            log::debug!("stop_target: Target is running, interrupting");
+            let mut target_running = self.target_running.subscribe();
+            self.interrupt().await?;
+            
+            tokio::time::timeout(STOP_TIMEOUT, target_running.wait_for(HashSet::is_empty))
+                .await
+                .map_err(|_| GdbError::CommandError("Target did not stop after interrupt".into()))?
+                .map_err(|_| GdbError::ProcessTerminated)?;
+        }
+        
+        Ok(())
+    }
+
     /// Detach from current target
     pub async fn detach(&mut self) -> Result<GdbResult> {
         self.send_command("target-detach").await
     }
 
-    /// Interrupt execution (break) - sends CTRL_C_EVENT to GDB process
+    /// Kill the inferior (MI has no equivalent of the console `kill` command)
+    pub async fn kill(&mut self) -> Result<GdbResult> {
+        self.send_command("interpreter-exec console \"kill\"").await
+    }
+
+    /// Turn MI async mode on or off; must be set before the target runs.
+    ///
+    /// With it on, `interrupt` writes `-exec-interrupt` instead of raising a
+    /// console Ctrl+C event.
+    pub async fn set_mi_async(&mut self, enabled: bool) -> Result<()> {
+        self.send_command(&format!("gdb-set mi-async {}", if enabled { "on" } else { "off" })).await?;
+        self.mi_async = enabled;
+        Ok(())
+    }
+
+    /// Run in non-stop mode, where threads stop and resume individually.
+    ///
+    /// GDB only accepts the change while no program runs, so set it before
+    /// attaching: it's applied when the session starts, or straight away if GDB
+    /// is already up. MI async mode is switched along with it.
+    pub async fn set_non_stop(&mut self, enabled: bool) -> Result<()> {
+        self.non_stop = enabled;
+        if self.is_running() {
+            self.apply_non_stop().await?;
+        }
+        Ok(())
+    }
+    
+    pub fn is_non_stop(&self) -> bool {
+        self.non_stop
+    }
+    
+    async fn apply_non_stop(&mut self) -> Result<()> {
+        self.set_mi_async(self.non_stop).await?;
+        self.send_command(&format!("gdb-set non-stop {}", if self.non_stop { "on" } else { "off" })).await?;
+        Ok(())
+    }
+
+    /// Interrupt execution (break)
+    ///
+    /// In MI async mode this writes `-exec-interrupt` to GDB's stdin. Otherwise
+    /// GDB doesn't read stdin while the target runs, so GDB is signalled the way
+    /// a terminal Ctrl+C would: SIGINT on Unix, a CTRL_C_EVENT to its process
+    /// group on Windows. That event reaches every process on the console and
+    /// fails when Katori has none. A command written instead would only be read
+    /// once the target stops on its own, so a failed signal is returned as an
+    /// error rather than falling back to stdin.
+    ///
+    /// In non-stop mode every thread is interrupted (`--all`); use
+    /// `interrupt_thread` to stop just one.
     pub async fn interrupt(&mut self) -> Result<GdbResult> {
-        log::debug!("INTERRUPT: Sending CTRL_C_EVENT to GDB process");
-        
         if !self.is_running() {
             log::debug!("INTERRUPT: GDB not running");
             return Err(GdbError::ProcessTerminated);
         }
         
-        if let Some(ref mut process) = self.process {
-            if let Some(pid) = process.id() {
-                log::debug!("INTERRUPT: Sending CTRL_C_EVENT to PID {}", pid);
-                
-                #[cfg(windows)]
-                {
-                    unsafe {
-                        use winapi::um::wincon::{GenerateConsoleCtrlEvent, CTRL_C_EVENT};
-                        
-                        let result = GenerateConsoleCtrlEvent(CTRL_C_EVENT, pid);
-                        if result == 0 {
-                            log::error!("INTERRUPT: GenerateConsoleCtrlEvent failed");
-                            return Err(GdbError::CommunicationError("Failed to send Ctrl+C event".into()));
-                        } else {
-                            log::debug!("INTERRUPT: Successfully sent CTRL_C_EVENT");
-                        }
-                    }
-                }
-                
-                #[cfg(not(windows))]
-                {
-                    // On non-Windows systems, we could use SIGINT here
-                    log::warn!("INTERRUPT: GenerateConsoleCtrlEvent not available on this platform");
-                    return Err(GdbError::CommunicationError("Interrupt not supported on this platform".into()));
-                }
-                
-                // Return a synthetic success result since interrupt doesn't return a MI response
-                use crate::types::{GdbResult, ResultClass};
-                use std::collections::HashMap;
-                Ok(GdbResult {
-                    token: None,
-                    class: ResultClass::Done,
-                    results: HashMap::new(),
-                })
-            } else {
-                log::error!("INTERRUPT: Could not get process ID");
-                return Err(GdbError::CommunicationError("Could not get process ID".into()));
+        if !self.mi_async {
+            let pid = self.process.as_ref().and_then(|process| process.id()).ok_or_else(|| {
+                GdbError::CommunicationError("No GDB process to signal; interrupting over MI needs MI async mode".into())
+            })?;
+            log::debug!("INTERRUPT: Signalling GDB PID {}", pid);
+            Self::send_ctrl_c(pid)?;
+            return Ok(Self::interrupt_sent());
+        }
+        
+        log::debug!("INTERRUPT: Writing -exec-interrupt to GDB");
+        let command: &[u8] = if self.non_stop { b"-exec-interrupt --all\n" } else { b"-exec-interrupt\n" };
+        match self.stdin {
+            // Untokened, so the ^done GDB answers with arrives as an event rather than a reply
+            Some(ref mut stdin) => Self::write_command(stdin, command).await?,
+            None => return Err(GdbError::ProcessTerminated),
+        }
+        self.log_sent(command);
+        Ok(Self::interrupt_sent())
+    }
+    
+    /// Interrupt a single thread, leaving the others running (non-stop mode)
+    pub async fn interrupt_thread(&mut self, thread: u32) -> Result<GdbResult> {
+        self.send_command(&format!("exec-interrupt --thread {}", thread)).await
+    }
+    
+    /// Synthetic success result, since an interrupt gets no MI reply of its own
+    fn interrupt_sent() -> GdbResult {
+        GdbResult {
+            token: None,
+            class: ResultClass::Done,
+            results: HashMap::new(),
+        }
+    }
+
+    #[cfg(windows)]
+    /// Deliver CTRL_C_EVENT to the GDB process group
+    fn send_ctrl_c(pid: u32) -> Result<()> {
+        unsafe {
+            use winapi::um::wincon::{GenerateConsoleCtrlEvent, CTRL_C_EVENT};
+            
+            let result = GenerateConsoleCtrlEvent(CTRL_C_EVENT, pid);
+            if result == 0 {
+                log::error!("INTERRUPT: GenerateConsoleCtrlEvent failed");
+                return Err(GdbError::CommunicationError("Failed to send Ctrl+C event".into()));
             }
-        } else {
-            log::error!("INTERRUPT: No process handle available");
-            Err(GdbError::ProcessTerminated)
         }
+        log::debug!("INTERRUPT: Successfully sent CTRL_C_EVENT");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    /// Send SIGINT to GDB, which passes the interrupt on to the target
+    fn send_ctrl_c(pid: u32) -> Result<()> {
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGINT) } != 0 {
+            let e = std::io::Error::last_os_error();
+            log::error!("INTERRUPT: Sending SIGINT failed: {}", e);
+            return Err(GdbError::CommunicationError(format!("Failed to send SIGINT to GDB: {}", e)));
+        }
+        log::debug!("INTERRUPT: Successfully sent SIGINT");
+        Ok(())
+    }
+
+    #[cfg(not(any(windows, unix)))]
+    /// Deliver CTRL_C_EVENT to the GDB process group
+    fn send_ctrl_c(_pid: u32) -> Result<()> {
+        // On non-Windows systems, we could use SIGINT here
+        log::warn!("INTERRUPT: GenerateConsoleCtrlEvent not available on this platform");
+        Err(GdbError::CommunicationError("Interrupt not supported on this platform".into()))
     }
 
     /// Set a breakpoint at the specified location
@@ -406,29 +1056,161 @@ impl GdbAdapter {
         self.send_command(&format!("break-insert {}", location)).await
     }
 
+    /// Set a breakpoint with a condition, ignore count, or as temporary/hardware
+    pub async fn set_breakpoint_spec(&mut self, spec: &BreakpointSpec) -> Result<GdbResult> {
+        self.send_command(&spec.mi_command()).await
+    }
+
     /// Set a breakpoint at a specific address
     pub async fn set_breakpoint_at_address(&mut self, address: &str) -> Result<GdbResult> {
         self.send_command(&format!("break-insert *{}", address)).await
     }
 
+    /// Set a temporary breakpoint at `address`, for running to it with `continue_execution`.
+    ///
+    /// GDB deletes the breakpoint when it is hit; if the target stops elsewhere
+    /// the caller should remove the returned breakpoint itself.
+    pub async fn set_temporary_breakpoint_at_address(&mut self, address: &str) -> Result<Breakpoint> {
+        let result = self.send_command(&format!("break-insert -t *{}", address)).await?;
+        result.results.get("bkpt")
+            .and_then(|v| v.as_tuple())
+            .and_then(Breakpoint::from_tuple)
+            .ok_or_else(|| GdbError::ParseError("break-insert returned no breakpoint".into()))
+    }
+
     /// Remove a breakpoint by number
     pub async fn remove_breakpoint(&mut self, number: u32) -> Result<GdbResult> {
         self.send_command(&format!("break-delete {}", number)).await
     }
 
+    /// Enable a breakpoint by number
+    pub async fn enable_breakpoint(&mut self, number: u32) -> Result<GdbResult> {
+        self.send_command(&format!("break-enable {}", number)).await
+    }
+
+    /// Disable a breakpoint by number, keeping it in the list
+    pub async fn disable_breakpoint(&mut self, number: u32) -> Result<GdbResult> {
+        self.send_command(&format!("break-disable {}", number)).await
+    }
+
+    /// Enable one location of a multi-location breakpoint, e.g. `2.1`
+    pub async fn enable_breakpoint_location(&mut self, location: &str) -> Result<GdbResult> {
+        self.send_command(&format!("break-enable {}", location)).await
+    }
+
+    /// Disable one location of a multi-location breakpoint, e.g. `2.1`
+    pub async fn disable_breakpoint_location(&mut self, location: &str) -> Result<GdbResult> {
+        self.send_command(&format!("break-disable {}", location)).await
+    }
+
+    /// Make a breakpoint ignore its next `count` hits; 0 clears a previous ignore count
+    pub async fn set_ignore_count(&mut self, number: u32, count: u32) -> Result<GdbResult> {
+        self.send_command(&format!("break-after {} {}", number, count)).await
+    }
+
+    /// Set a watchpoint on an expression
+    pub async fn set_watchpoint(&mut self, expr: &str, kind: WatchKind) -> Result<GdbResult> {
+        match kind.mi_flag() {
+            Some(flag) => self.send_command(&format!("break-watch {} {}", flag, quote_mi_string(expr))).await,
+            None => self.send_command(&format!("break-watch {}", quote_mi_string(expr))).await,
+        }
+    }
+
     /// List all breakpoints
     pub async fn list_breakpoints(&mut self) -> Result<GdbResult> {
         self.send_command("break-list").await
     }
 
+    /// Set the arguments the program is started with by `run_program`
+    pub async fn set_args(&mut self, args: &[String]) -> Result<GdbResult> {
+        self.send_command(format!("exec-arguments {}", quote_arguments(args)).trim_end()).await
+    }
+
+    /// Change the working directory of GDB, which programs it starts inherit
+    pub async fn set_cwd(&mut self, dir: &str) -> Result<GdbResult> {
+        self.send_command(&format!("environment-cd {}", quote_mi_string(dir))).await
+    }
+
+    /// Set an environment variable for programs GDB starts from now on
+    pub async fn set_environment(&mut self, name: &str, value: &str) -> Result<GdbResult> {
+        let command = format!("set environment {}={}", name, value);
+        self.send_command(&format!("interpreter-exec console {}", quote_mi_string(&command))).await
+    }
+
+    /// Remove an environment variable from programs GDB starts from now on
+    pub async fn unset_environment(&mut self, name: &str) -> Result<GdbResult> {
+        let command = format!("unset environment {}", name);
+        self.send_command(&format!("interpreter-exec console {}", quote_mi_string(&command))).await
+    }
+
+    /// Give programs GDB starts from now on a terminal of their own.
+    ///
+    /// What they print arrives as `StreamType::Target` stream events instead of
+    /// mixing into the MI output, and `write_inferior_input` types into it. Returns
+    /// the terminal's device path. Only available on Unix.
+    pub async fn create_inferior_tty(&mut self) -> Result<String> {
+        #[cfg(unix)]
+        {
+            let tty = tty::InferiorTty::open(self.event_sender.clone())
+                .map_err(|e| GdbError::CommandError(format!("Failed to open a program terminal: {}", e)))?;
+            let path = tty.path().to_string();
+            self.send_command(&format!("inferior-tty-set {}", quote_mi_string(&path))).await?;
+            self.inferior_tty = Some(tty);
+            Ok(path)
+        }
+        #[cfg(not(unix))]
+        {
+            Err(GdbError::CommandError("A separate program terminal needs a Unix host".into()))
+        }
+    }
+
+    /// Send `text` to the program's standard input through its terminal
+    pub fn write_inferior_input(&mut self, text: &str) -> Result<()> {
+        #[cfg(unix)]
+        {
+            match self.inferior_tty.as_mut() {
+                Some(tty) => tty.write_input(text)
+                    .map_err(|e| GdbError::CommandError(format!("Failed to write to the program: {}", e))),
+                None => Err(GdbError::CommandError("The program has no terminal of its own".into())),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = text;
+            Err(GdbError::CommandError("A separate program terminal needs a Unix host".into()))
+        }
+    }
+
+    /// Whether `create_inferior_tty` has set up a terminal for this session
+    pub fn has_inferior_tty(&self) -> bool {
+        #[cfg(unix)]
+        {
+            self.inferior_tty.is_some()
+        }
+        #[cfg(not(unix))]
+        {
+            false
+        }
+    }
+
     /// Execute the target program
     pub async fn run_program(&mut self) -> Result<GdbResult> {
+        // Position-independent executables are relocated when they start
+        self.symbol_cache.clear();
+        self.aligned_starts.clear();
         self.send_command("exec-run").await
     }
 
     /// Continue execution
+    ///
+    /// In non-stop mode every thread is resumed (`--all`), undoing `interrupt`;
+    /// steps still move only the selected thread.
     pub async fn continue_execution(&mut self) -> Result<GdbResult> {
-        self.send_command("exec-continue").await
+        if self.non_stop {
+            self.send_command("exec-continue --all").await
+        } else {
+            self.send_command("exec-continue").await
+        }
     }
 
     /// Step one instruction
@@ -451,14 +1233,144 @@ impl GdbAdapter {
         self.send_command("exec-next-instruction").await
     }
 
-    /// Step out of current function
+    /// Step `count` assembly instructions, stopping once at the end
+    pub async fn step_instructions(&mut self, count: u32) -> Result<GdbResult> {
+        self.send_command(&format!("interpreter-exec console \"stepi {}\"", count)).await
+    }
+
+
+    /// Step out of current function
     pub async fn step_out(&mut self) -> Result<GdbResult> {
         self.send_command("exec-finish").await
     }
 
-    /// Get register values
+    /// Run backwards to the previous breakpoint or the start of the recording
+    pub async fn reverse_continue(&mut self) -> Result<GdbResult> {
+        self.send_command("exec-continue --reverse").await
+    }
+
+    /// Step back one line, entering calls
+    pub async fn reverse_step(&mut self) -> Result<GdbResult> {
+        self.send_command("exec-step --reverse").await
+    }
+
+    /// Step back one line, over calls
+    pub async fn reverse_next(&mut self) -> Result<GdbResult> {
+        self.send_command("exec-next --reverse").await
+    }
+
+    /// Step back one assembly instruction
+    pub async fn reverse_step_instruction(&mut self) -> Result<GdbResult> {
+        self.send_command("exec-step-instruction --reverse").await
+    }
+
+    /// Step back over one assembly instruction
+    pub async fn reverse_next_instruction(&mut self) -> Result<GdbResult> {
+        self.send_command("exec-next-instruction --reverse").await
+    }
+
+    /// Start recording execution so it can be replayed backwards; GDB reports `=record-started`
+    pub async fn start_recording(&mut self) -> Result<GdbResult> {
+        self.send_command("interpreter-exec console \"record\"").await
+    }
+
+    /// Stop recording and discard the execution log; GDB reports `=record-stopped`
+    pub async fn stop_recording(&mut self) -> Result<GdbResult> {
+        self.send_command("interpreter-exec console \"record stop\"").await
+    }
+
+    /// Set the target architecture (`auto` restores detection); needed for stubs
+    /// such as OpenOCD or QEMU that don't report one.
+    ///
+    /// Applied straight away if GDB is up, and again whenever a session starts,
+    /// so it can be set before attaching.
+    pub async fn set_architecture(&mut self, arch: &str) -> Result<()> {
+        self.target_arch = None;
+        self.aligned_starts.clear();
+        self.architecture = (arch != "auto").then(|| arch.to_string());
+        if self.is_running() {
+            self.send_command(&format!("gdb-set architecture {}", arch)).await?;
+        }
+        Ok(())
+    }
+    
+    /// Architecture of the target, e.g. `i386:x86-64` or `armv7e-m`.
+    ///
+    /// Taken from the innermost frame while there is a program, otherwise from
+    /// `show architecture`. Remembered until another target is loaded or attached
+    /// or the architecture is set.
+    pub async fn target_architecture(&mut self) -> Option<String> {
+        if self.target_arch.is_none() {
+            self.target_arch = match self.frame_architecture().await {
+                Some(arch) => Some(arch),
+                None => self.console_output("show architecture").await.ok().and_then(|text| parse_show_architecture(&text)),
+            };
+            log::debug!("target_architecture: Detected {:?}", self.target_arch);
+        }
+        self.target_arch.clone()
+    }
+    
+    async fn frame_architecture(&mut self) -> Option<String> {
+        let result = self.send_command("stack-list-frames 0 0").await.ok()?;
+        StackFrame::list_from_result(&result).ok()?.into_iter().find_map(|frame| frame.arch)
+    }
+    
+    /// Run a CLI command and collect what it prints to the console stream.
+    ///
+    /// The output is captured rather than published, so it doesn't show up
+    /// as if the user had typed the command.
+    async fn console_output(&mut self, command: &str) -> Result<String> {
+        // The reader collects a command's console output before routing its reply
+        *self.console_capture.lock().unwrap() = Some(String::new());
+        let result = self.send_command(&format!("interpreter-exec console {}", quote_mi_string(command))).await;
+        let text = self.console_capture.lock().unwrap().take().unwrap_or_default();
+        result.map(|_| text)
+    }
+
+    /// Choose `intel` or `att` syntax for x86 disassembly; other targets ignore it.
+    /// Like `set_architecture`, it's kept for later sessions.
+    pub async fn set_disassembly_flavor(&mut self, flavor: &str) -> Result<()> {
+        self.disassembly_flavor = Some(flavor.to_string());
+        if self.is_running() {
+            self.send_command(&format!("gdb-set disassembly-flavor {}", flavor)).await?;
+        }
+        Ok(())
+    }
+
+    /// Get register values for the register subset if one is set, otherwise for
+    /// the selected register set
     pub async fn get_registers(&mut self) -> Result<GdbResult> {
-        self.send_command("data-list-register-values x").await
+        let numbers = match (&self.register_subset, self.register_set, &self.core_registers) {
+            (Some(subset), _, _) => subset.clone(),
+            (None, RegisterSet::Core, Some(core)) => core.clone(),
+            _ => Vec::new(),
+        };
+        self.get_registers_subset(&numbers, self.register_format).await
+    }
+    
+    /// Get values of just the registers numbered `numbers`; an empty list reads all of them
+    pub async fn get_registers_subset(&mut self, numbers: &[u32], format: RegisterFormat) -> Result<GdbResult> {
+        if numbers.is_empty() {
+            return self.get_registers_formatted(format).await;
+        }
+        let numbers: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+        self.send_command(&format!("data-list-register-values {} {}", format.mi_letter(), numbers.join(" "))).await
+    }
+
+    /// Get values of every register, regardless of the register set
+    pub async fn get_all_registers(&mut self) -> Result<GdbResult> {
+        self.get_registers_formatted(self.register_format).await
+    }
+
+    /// Get register values in a specific format
+    pub async fn get_registers_formatted(&mut self, format: RegisterFormat) -> Result<GdbResult> {
+        self.send_command(&format!("data-list-register-values {}", format.mi_letter())).await
+    }
+
+    /// Numbers of the registers whose value changed since the previous call
+    pub async fn get_changed_registers(&mut self) -> Result<Vec<u32>> {
+        let result = self.send_command("data-list-changed-registers").await?;
+        Ok(Register::changed_numbers(&result))
     }
 
     /// Get register names
@@ -466,14 +1378,64 @@ impl GdbAdapter {
         self.send_command("data-list-register-names").await
     }
 
-    /// Disassemble at current location
-    pub async fn disassemble_current(&mut self, lines: u32) -> Result<GdbResult> {
-        self.send_command(&format!("data-disassemble -s $pc -e $pc+{} -- 0", lines * 4)).await
+    /// Disassemble around the current location: about `before` instructions leading
+    /// up to `$pc` and `after` instructions from it
+    pub async fn disassemble_current(&mut self, before: u32, after: u32) -> Result<GdbResult> {
+        self.disassemble_around_pc(before, after, 0).await
+    }
+
+    /// Like `disassemble_current`, interleaved with source lines (`-data-disassemble` mode 5)
+    pub async fn disassemble_current_with_source(&mut self, before: u32, after: u32) -> Result<GdbResult> {
+        self.disassemble_around_pc(before, after, 5).await
+    }
+
+    /// Disassemble a window around `$pc`, sized with `instruction_width`.
+    ///
+    /// With variable-length instructions the window may start mid-instruction, which
+    /// decodes garbage and can swallow `$pc` itself, so the start moves forward a byte
+    /// at a time until an instruction begins at `$pc`. If the memory before `$pc`
+    /// can't be read, or no start lines up, only the instructions from `$pc` on are shown.
+    /// A start that lined up is remembered, so stopping at the same PC again costs one read.
+    async fn disassemble_around_pc(&mut self, before: u32, after: u32, mode: u32) -> Result<GdbResult> {
+        let width = u64::from(self.instruction_width());
+        let forward = format!("data-disassemble -s $pc -e $pc+{} -- {}", u64::from(after) * width, mode);
+        if before == 0 {
+            return self.send_command(&forward).await;
+        }
+        
+        let pc = self.evaluate_expression("$pc").await?
+            .results.get("value")
+            .and_then(|v| v.as_string())
+            .and_then(first_hex_word)
+            .and_then(|word| u64::from_str_radix(&word[2..], 16).ok())
+            .ok_or_else(|| GdbError::ParseError("$pc has no address".into()))?;
+        let start = pc.saturating_sub(u64::from(before) * width);
+        let end = pc.saturating_add(u64::from(after) * width);
+        let cached = self.aligned_starts.get(&(pc, start)).copied();
+        for aligned in cached.into_iter().chain((start..pc).take(MAX_ALIGN_SHIFT as usize + 1)) {
+            let result = match self.send_command(&format!("data-disassemble -s 0x{:x} -e 0x{:x} -- {}", aligned, end, mode)).await {
+                Ok(result) => result,
+                Err(e) => {
+                    log::debug!("disassemble_around_pc: Nothing readable from 0x{:x}: {}", aligned, e);
+                    break;
+                }
+            };
+            if instruction_addresses(&result).contains(&pc) {
+                self.aligned_starts.insert((pc, start), aligned);
+                return Ok(result);
+            }
+        }
+        self.send_command(&forward).await
     }
 
     /// Disassemble at specific address
     pub async fn disassemble_at_address(&mut self, address: &str, lines: u32) -> Result<GdbResult> {
-        self.send_command(&format!("data-disassemble -s {} -e {}+{} -- 0", address, address, lines * 4)).await
+        self.send_command(&format!("data-disassemble -s {} -e {}+{} -- 0", address, address, lines * self.instruction_width())).await
+    }
+
+    /// Disassemble the whole function containing `function`, which may be a symbol or address
+    pub async fn disassemble_function(&mut self, function: &str) -> Result<GdbResult> {
+        self.send_command(&format!("data-disassemble -a {} -- 0", quote_mi_string(function))).await
     }
 
     /// Get stack frames
@@ -481,22 +1443,297 @@ impl GdbAdapter {
         self.send_command("stack-list-frames").await
     }
 
+    /// Arguments of every frame; without `all_values` only scalar values are
+    /// included, so large structs and arrays don't slow the listing down
+    pub async fn list_stack_arguments(&mut self, all_values: bool) -> Result<GdbResult> {
+        let values = if all_values { "--all-values" } else { "--simple-values" };
+        self.send_command(&format!("stack-list-arguments {}", values)).await
+    }
+
+    /// Arguments and locals of the selected frame (`Argument::list_from_value`
+    /// on `variables` parses them); `all_values` as for `list_stack_arguments`
+    pub async fn list_stack_variables(&mut self, all_values: bool) -> Result<GdbResult> {
+        let values = if all_values { "--all-values" } else { "--simple-values" };
+        self.send_command(&format!("stack-list-variables {}", values)).await
+    }
+
     /// Read memory at address
     pub async fn read_memory(&mut self, address: &str, size: u32) -> Result<GdbResult> {
         self.send_command(&format!("data-read-memory-bytes {} {}", address, size)).await
     }
     
+    /// Read `size` bytes starting `offset` bytes past `address`, which may be an expression
+    pub async fn read_memory_at(&mut self, address: &str, offset: u32, size: u32) -> Result<GdbResult> {
+        self.send_command(&format!("data-read-memory-bytes -o {} {} {}", offset, address, size)).await
+    }
+
+    /// List all threads and the current thread id (`-thread-info`)
+    pub async fn list_threads(&mut self) -> Result<GdbResult> {
+        self.send_command("thread-info").await
+    }
+
+    /// Make `id` the current thread for subsequent commands
+    pub async fn select_thread(&mut self, id: u32) -> Result<GdbResult> {
+        self.send_command(&format!("thread-select {}", id)).await
+    }
+
+    /// Make frame `level` of the current thread the one registers, disassembly and expressions use
+    pub async fn select_frame(&mut self, level: u32) -> Result<GdbResult> {
+        self.send_command(&format!("stack-select-frame {}", level)).await
+    }
+
+    /// Write bytes to memory at address
+    pub async fn write_memory(&mut self, address: &str, bytes: &[u8]) -> Result<GdbResult> {
+        let contents: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        self.send_command(&format!("data-write-memory-bytes {} {}", address, contents)).await
+    }
+
+    /// Evaluate an expression in the current frame
+    pub async fn evaluate_expression(&mut self, expression: &str) -> Result<GdbResult> {
+        self.send_command(&format!("data-evaluate-expression {}", quote_mi_string(expression))).await
+    }
+
+    /// Address of a function or variable, from evaluating `&name`.
+    ///
+    /// Functions are cached until the next load, run or attach. Variables are
+    /// looked up again every time: locals move with the frame, and statics in
+    /// different functions can share a name. Ambiguous or overloaded names fail
+    /// with GDB's own message, which lists what to qualify.
+    pub async fn resolve_symbol(&mut self, name: &str) -> Result<String> {
+        let name = name.trim();
+        if let Some(address) = self.symbol_cache.get(name) {
+            return Ok(address.clone());
+        }
+        
+        let result = self.evaluate_expression(&format!("&{}", name)).await?;
+        let value = result.results.get("value").and_then(|v| v.as_string()).unwrap_or_default();
+        let address = first_hex_word(value)
+            .ok_or_else(|| GdbError::CommandError(format!("{} has no address: {}", name, value)))?
+            .to_string();
+        
+        if is_function_pointer(value) {
+            self.symbol_cache.insert(name.to_string(), address.clone());
+        }
+        Ok(address)
+    }
+    
+    /// Address of a function already found by `resolve_symbol`
+    pub fn cached_symbol(&self, name: &str) -> Option<&str> {
+        self.symbol_cache.get(name.trim()).map(String::as_str)
+    }
+    
+    /// Evaluate several expressions in one pipelined batch; each gets its own result
+    pub async fn evaluate_expressions(&mut self, expressions: &[String]) -> Vec<Result<GdbResult>> {
+        let commands: Vec<String> = expressions.iter()
+            .map(|expression| format!("data-evaluate-expression {}", quote_mi_string(expression)))
+            .collect();
+        self.send_commands_pipelined(&commands).await
+    }
+    
+    /// Create a variable object for `expression`, for showing its members as a tree.
+    ///
+    /// The object floats: each `update_varobjs` evaluates it again in whichever
+    /// frame is selected, like a watch expression.
+    pub async fn create_varobj(&mut self, expression: &str) -> Result<VarObj> {
+        let result = self.send_command(&format!("var-create - @ {}", quote_mi_string(expression))).await?;
+        let mut varobj = VarObj::from_tuple(&result.results).map_err(GdbError::ParseError)?;
+        varobj.expression = expression.to_string();
+        Ok(varobj)
+    }
+
+    /// Children of a variable object with their values; `range` limits them to
+    /// `from..to`, which keeps large arrays and pretty-printed containers cheap
+    pub async fn list_children(&mut self, name: &str, range: Option<(u32, u32)>) -> Result<VarObjChildren> {
+        let mut command = format!("var-list-children --all-values {}", name);
+        if let Some((from, to)) = range {
+            command.push_str(&format!(" {} {}", from, to));
+        }
+        let result = self.send_command(&command).await?;
+        VarObjChildren::from_result(&result).map_err(GdbError::ParseError)
+    }
+
+    /// Evaluate every variable object again and report the ones that changed
+    pub async fn update_varobjs(&mut self) -> Result<Vec<VarObjChange>> {
+        let result = self.send_command("var-update --all-values *").await?;
+        Ok(VarObjChange::list_from_result(&result))
+    }
+
+    /// Delete a variable object and its children
+    pub async fn delete_varobj(&mut self, name: &str) -> Result<GdbResult> {
+        self.send_command(&format!("var-delete {}", name)).await
+    }
+
     /// Check if GDB is running
     pub fn is_running(&self) -> bool {
         *self.is_running.lock().unwrap()
     }
     
+    /// Check if the target is executing (as last reported by `*running`/`*stopped`).
+    /// In non-stop mode that's while any thread still runs.
+    pub fn is_target_running(&self) -> bool {
+        !self.target_running.borrow().is_empty()
+    }
+    
+}
+
+/// What `read_mi_line` found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MiLine {
+    /// A whole line, or what was left before the end of the output
+    Line,
+    /// A line longer than the limit; only its start was kept
+    TooLong,
+    Eof,
+}
+
+/// Read up to and including the next newline into `line`, however many reads it
+/// takes to arrive. Lines longer than `max` are consumed in full but only their
+/// first `max` bytes are kept.
+async fn read_mi_line<R>(reader: &mut R, line: &mut Vec<u8>, max: usize) -> std::io::Result<MiLine>
+where
+    R: AsyncBufRead + Unpin,
+{
+    line.clear();
+    let mut too_long = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(match (too_long, line.is_empty()) {
+                (true, _) => MiLine::TooLong,
+                (false, true) => MiLine::Eof,
+                (false, false) => MiLine::Line,
+            });
+        }
+        let (chunk, complete) = match available.iter().position(|&b| b == b'\n') {
+            Some(end) => (&available[..=end], true),
+            None => (available, false),
+        };
+        let consumed = chunk.len();
+        if !too_long {
+            let room = max - line.len();
+            too_long = chunk.len() > room;
+            line.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        }
+        reader.consume(consumed);
+        if complete {
+            return Ok(if too_long { MiLine::TooLong } else { MiLine::Line });
+        }
+    }
+}
+
+/// Token of a result record from the start of its line, e.g. `12` in `12^done,...`
+fn result_token(line: &[u8]) -> Option<u32> {
+    let digits = line.iter().take_while(|b| b.is_ascii_digit()).count();
+    if line.get(digits) != Some(&b'^') {
+        return None;
+    }
+    std::str::from_utf8(&line[..digits]).ok()?.parse().ok()
+}
+
+/// Write errors worth one retry; anything else means the pipe is gone
+fn is_transient(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::Interrupted | std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
+
+/// First `0x` number in a value GDB printed, e.g. `(int (*)(int, char **)) 0x401136 <main>`
+fn first_hex_word(value: &str) -> Option<&str> {
+    value.split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .find(|word| word.strip_prefix("0x").is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit())))
+}
+
+/// Apply a `*running` or `*stopped` record to the set of running threads.
+///
+/// A `thread-id="all"` resume only happens in all-stop mode, where the next
+/// stop (`stopped-threads="all"`) ends it. In non-stop mode threads resume and
+/// stop one by one, each listed in `stopped-threads`.
+fn update_running_threads(threads: &mut HashSet<String>, record: &AsyncRecord) {
+    match record.class {
+        AsyncClass::Running => {
+            let thread = record.results.get("thread-id").and_then(Value::as_string).unwrap_or("all");
+            threads.insert(thread.to_string());
+        }
+        AsyncClass::Stopped => match record.results.get("stopped-threads").and_then(Value::as_list) {
+            Some(stopped) => {
+                threads.remove("all");
+                for thread in stopped.iter().filter_map(Value::as_string) {
+                    threads.remove(thread);
+                }
+            }
+            None => threads.clear(),
+        },
+        _ => {}
+    }
+}
+
+/// Whether an `&name` value points to a function, e.g. `(int (*)(int, char **)) 0x401136 <main>`
+fn is_function_pointer(value: &str) -> bool {
+    value.ends_with('>') && value.split(" 0x").next().is_some_and(|ty| ty.contains("*)("))
+}
+
+/// Addresses of the instructions in a `-data-disassemble` result, with or without source lines
+fn instruction_addresses(result: &GdbResult) -> Vec<u64> {
+    let Some(Value::List(items)) = result.results.get("asm_insns") else {
+        return Vec::new();
+    };
+    items.iter()
+        .filter_map(|item| item.as_tuple())
+        .flat_map(|tuple| match tuple.get("src_and_asm_line").and_then(|v| v.as_tuple()) {
+            Some(block) => block.get("line_asm_insn").and_then(|v| v.as_list()).map(Vec::as_slice).unwrap_or_default().iter()
+                .filter_map(|insn| insn.as_tuple()?.get("address")?.as_address())
+                .collect::<Vec<_>>(),
+            None => tuple.get("address").and_then(|v| v.as_address()).into_iter().collect(),
+        })
+        .collect()
+}
+
+/// Architecture from `show architecture`, which prints either
+/// `The target architecture is set to "auto" (currently "i386:x86-64").` or
+/// `The target architecture is set to "armv7".`
+fn parse_show_architecture(text: &str) -> Option<String> {
+    let quoted = |text: &str| text.split('"').nth(1).map(str::to_string);
+    match text.split_once("(currently ") {
+        Some((_, current)) => quoted(current),
+        None => quoted(text).filter(|arch| arch != "auto"),
+    }
+}
+
+/// Quote a string as an MI C-string argument, escaping backslashes and quotes
+pub fn quote_mi_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Join program arguments into a `set args` style command line.
+///
+/// GDB hands `-exec-arguments` to the shell (or Windows command line parser)
+/// as-is, so arguments with whitespace or quotes are double-quoted.
+pub fn quote_arguments(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+                arg.clone()
+            } else {
+                format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 impl Drop for GdbAdapter {
     fn drop(&mut self) {
+        // Let the reader tasks wind down instead of reporting a lost connection
+        *self.is_running.lock().unwrap() = false;
+        // The reader task shares the pending map, so fail outstanding commands explicitly
+        self.canceller().cancel_pending();
+        
+        // start_kill sends the kill signal synchronously, so it works without an async context
         if let Some(mut process) = self.process.take() {
-            let _ = process.kill();
+            if let Err(e) = process.start_kill() {
+                log::warn!("Failed to kill GDB process on drop: {}", e);
+            }
         }
     }
 }
@@ -605,6 +1842,649 @@ mod tests {
         }
     }
     
+    #[tokio::test]
+    async fn test_reader_eof_marks_session_lost() {
+        let (adapter, mut events) = GdbAdapter::new();
+        *adapter.is_running.lock().unwrap() = true;
+        
+        // An empty reader behaves like a GDB stdout that was closed immediately
+        adapter.start_output_reader(tokio::io::empty()).await;
+        
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("reader should report the lost connection");
+        assert!(matches!(event, Ok(GdbEvent::ConnectionLost)));
+        assert!(!adapter.is_running());
+    }
+    
+    /// Writer that fails its first `failures` writes with `kind`, then records everything
+    struct FlakyWriter {
+        failures: usize,
+        kind: std::io::ErrorKind,
+        written: Arc<Mutex<Vec<u8>>>,
+    }
+    
+    impl AsyncWrite for FlakyWriter {
+        fn poll_write(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return std::task::Poll::Ready(Err(self.kind.into()));
+            }
+            self.written.lock().unwrap().extend_from_slice(buf);
+            std::task::Poll::Ready(Ok(buf.len()))
+        }
+        
+        fn poll_flush(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+        
+        fn poll_shutdown(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+    
+    fn flaky_adapter(failures: usize, kind: std::io::ErrorKind) -> (GdbAdapter, Arc<Mutex<Vec<u8>>>) {
+        let (mut adapter, _events) = GdbAdapter::new();
+        *adapter.is_running.lock().unwrap() = true;
+        let written = Arc::new(Mutex::new(Vec::new()));
+        adapter.stdin = Some(Box::new(FlakyWriter { failures, kind, written: written.clone() }));
+        (adapter, written)
+    }
+    
+    #[tokio::test]
+    async fn test_session_startup_disables_prompts_and_pagination() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            gdb_stdout.write_all(b"1^done\n2^done\n3^error,msg=\"unsupported\"\n4^done\n").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        adapter.apply_startup_commands().await;
+        
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            "1-gdb-set confirm off\n2-gdb-set pagination off\n3-gdb-set height 0\n4-enable-pretty-printing\n"
+        );
+        
+        // Overriding replaces the defaults entirely
+        written.lock().unwrap().clear();
+        adapter.set_startup_commands(Vec::new());
+        adapter.apply_startup_commands().await;
+        assert!(written.lock().unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_environment_commands_are_quoted() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            gdb_stdout.write_all(b"1^done\n2^done\n").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        let _ = adapter.set_environment("GREETING", "say \"hi\"").await;
+        let _ = adapter.unset_environment("DISPLAY").await;
+        
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            "1-interpreter-exec console \"set environment GREETING=say \\\"hi\\\"\"\n2-interpreter-exec console \"unset environment DISPLAY\"\n"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_load_core_loads_symbols_first() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            gdb_stdout.write_all(b"1^done\n").await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            gdb_stdout.write_all(b"2^done\n").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        adapter.load_core("/tmp/my app", "/tmp/core.1234").await.unwrap();
+        
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            "1-file-exec-and-symbols \"/tmp/my app\"\n2-target-select core \"/tmp/core.1234\"\n"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_interrupt_writes_exec_interrupt_in_mi_async_mode() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            gdb_stdout.write_all(b"1^done\n").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        // No GDB process to signal, and GDB wouldn't read a command while the target runs
+        assert!(adapter.interrupt().await.is_err());
+        adapter.set_mi_async(true).await.unwrap();
+        adapter.interrupt().await.unwrap();
+        
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            "1-gdb-set mi-async on\n-exec-interrupt\n"
+        );
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_interrupt_signals_gdb_without_mi_async() {
+        use std::os::unix::process::ExitStatusExt;
+        
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        adapter.process = Some(Command::new("sleep").arg("30").spawn().unwrap());
+        
+        adapter.interrupt().await.unwrap();
+        
+        let status = adapter.process.take().unwrap().wait().await.unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGINT));
+        assert!(written.lock().unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_non_stop_mode_interrupts_per_thread() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            for token in 1..=4 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                gdb_stdout.write_all(format!("{}^done\n", token).as_bytes()).await.unwrap();
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        adapter.set_non_stop(true).await.unwrap();
+        adapter.interrupt().await.unwrap();
+        adapter.interrupt_thread(2).await.unwrap();
+        adapter.continue_execution().await.unwrap();
+        
+        assert!(adapter.is_non_stop());
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            "1-gdb-set mi-async on\n2-gdb-set non-stop on\n-exec-interrupt --all\n3-exec-interrupt --thread 2\n4-exec-continue --all\n"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_disassembly_range_follows_instruction_width() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            for token in 1..=3 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                gdb_stdout.write_all(format!("{}^done\n", token).as_bytes()).await.unwrap();
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        adapter.disassemble_current(0, 10).await.unwrap();
+        adapter.set_thumb(true);
+        adapter.disassemble_current(0, 10).await.unwrap();
+        adapter.set_instruction_width(Some(4));
+        adapter.disassemble_at_address("0x8000", 10).await.unwrap();
+        
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            "1-data-disassemble -s $pc -e $pc+40 -- 0\n2-data-disassemble -s $pc -e $pc+20 -- 0\n3-data-disassemble -s 0x8000 -e 0x8000+40 -- 0\n"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_disassembly_window_lines_up_with_pc() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            for reply in [
+                r#"1^done,value="(void (*)()) 0x1010 <main+16>""#,
+                // Started mid-instruction: decoding runs straight past $pc
+                r#"2^done,asm_insns=[{address="0x1000",inst="add"},{address="0x100e",inst="jmp"},{address="0x1013",inst="ret"}]"#,
+                r#"3^done,asm_insns=[{address="0x1001",inst="mov"},{address="0x1010",inst="push"},{address="0x1013",inst="ret"}]"#,
+                r#"4^done,value="(void (*)()) 0x1010 <main+16>""#,
+                r#"5^done,asm_insns=[{address="0x1001",inst="mov"},{address="0x1010",inst="push"},{address="0x1013",inst="ret"}]"#,
+                r#"6^done,value="(void (*)()) 0x10 <_start>""#,
+                r#"7^error,msg="Cannot access memory at address 0x0""#,
+                r#"8^done,asm_insns=[src_and_asm_line={line="3",file="start.s",line_asm_insn=[{address="0x10",inst="nop"}]}]"#,
+            ] {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                gdb_stdout.write_all(format!("{}\n", reply).as_bytes()).await.unwrap();
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        let result = adapter.disassemble_current(4, 2).await.unwrap();
+        assert_eq!(instruction_addresses(&result), vec![0x1001, 0x1010, 0x1013]);
+        // The same PC starts where it lined up last time
+        let result = adapter.disassemble_current(4, 2).await.unwrap();
+        assert_eq!(instruction_addresses(&result), vec![0x1001, 0x1010, 0x1013]);
+        // Nothing mapped before $pc: fall back to the instructions from $pc on
+        let result = adapter.disassemble_current_with_source(4, 2).await.unwrap();
+        assert_eq!(instruction_addresses(&result), vec![0x10]);
+        
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            "1-data-evaluate-expression \"$pc\"\n2-data-disassemble -s 0x1000 -e 0x1018 -- 0\n3-data-disassemble -s 0x1001 -e 0x1018 -- 0\n\
+             4-data-evaluate-expression \"$pc\"\n5-data-disassemble -s 0x1001 -e 0x1018 -- 0\n\
+             6-data-evaluate-expression \"$pc\"\n7-data-disassemble -s 0x0 -e 0x18 -- 5\n8-data-disassemble -s $pc -e $pc+8 -- 5\n"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_resolved_symbols_are_cached_until_reload() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            for reply in [
+                r#"1^done,value="(int (*)(int, char **)) 0x401136 <main>""#,
+                "2^done",
+                r#"3^done,value="(int *) 0x404028 <main>""#,
+                r#"4^done,value="(int *) 0x404040 <main>""#,
+                r#"5^done,value="(int *) 0x7fffffffe43c""#,
+                r#"6^done,value="(int *) 0x7fffffffe41c""#,
+                r#"7^done,value="1""#,
+            ] {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                gdb_stdout.write_all(format!("{}\n", reply).as_bytes()).await.unwrap();
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        assert_eq!(adapter.resolve_symbol("main").await.unwrap(), "0x401136");
+        assert_eq!(adapter.resolve_symbol(" main ").await.unwrap(), "0x401136");
+        assert_eq!(adapter.cached_symbol("main"), Some("0x401136"));
+        adapter.load_executable("other").await.unwrap();
+        assert_eq!(adapter.cached_symbol("main"), None);
+        // Statics in different functions can share a name, so only functions are cached
+        assert_eq!(adapter.resolve_symbol("main").await.unwrap(), "0x404028");
+        assert_eq!(adapter.resolve_symbol("main").await.unwrap(), "0x404040");
+        // Locals move with the frame
+        assert_eq!(adapter.resolve_symbol("count").await.unwrap(), "0x7fffffffe43c");
+        assert_eq!(adapter.resolve_symbol("count").await.unwrap(), "0x7fffffffe41c");
+        assert!(adapter.resolve_symbol("1").await.is_err());
+        
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            "1-data-evaluate-expression \"&main\"\n2-file-exec-and-symbols \"other\"\n3-data-evaluate-expression \"&main\"\n4-data-evaluate-expression \"&main\"\n5-data-evaluate-expression \"&count\"\n6-data-evaluate-expression \"&count\"\n7-data-evaluate-expression \"&1\"\n"
+        );
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_inferior_tty_is_handed_to_gdb() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        let mut events = adapter.subscribe();
+        assert!(adapter.write_inferior_input("x\n").is_err());
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            gdb_stdout.write_all(b"1^done\n").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        let path = adapter.create_inferior_tty().await.unwrap();
+        assert!(adapter.has_inferior_tty());
+        assert_eq!(
+            String::from_utf8(written.lock().unwrap().clone()).unwrap(),
+            format!("1-inferior-tty-set \"{}\"\n", path)
+        );
+        
+        // What the program prints comes back as target output
+        let mut program = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut program, b"ready\n").unwrap();
+        let stream = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(GdbEvent::Stream(stream)) = events.recv().await {
+                    if stream.stream_type == StreamType::Target {
+                        return stream;
+                    }
+                }
+            }
+        }).await.unwrap();
+        assert_eq!(stream.content, "ready\n");
+        assert!(adapter.write_inferior_input("42\n").is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_send_command_retries_transient_write_error() {
+        let (mut adapter, written) = flaky_adapter(1, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            gdb_stdout.write_all(b"1^done\n").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        let result = adapter.send_command_timeout("gdb-version", Duration::from_secs(1)).await;
+        
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(written.lock().unwrap().as_slice(), b"1-gdb-version\n");
+        assert!(adapter.pending_commands.lock().unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_send_command_write_failure_forgets_token() {
+        let (mut adapter, written) = flaky_adapter(usize::MAX, std::io::ErrorKind::BrokenPipe);
+        
+        let result = adapter.send_command("gdb-version").await;
+        
+        assert!(matches!(result, Err(GdbError::CommunicationError(_))));
+        assert!(written.lock().unwrap().is_empty());
+        assert!(adapter.pending_commands.lock().unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_evaluate_expressions_pipelines_and_isolates_errors() {
+        let (mut adapter, written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        let sent = written.clone();
+        tokio::spawn(async move {
+            // Only reply once all three commands were written, out of order
+            while sent.lock().unwrap().iter().filter(|b| **b == b'\n').count() < 3 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            gdb_stdout.write_all(b"3^done,value=\"3\"\n2^error,msg=\"No symbol \\\"nope\\\" in current context.\"\n1^done,value=\"1\"\n").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        let expressions = ["a".to_string(), "nope".to_string(), "c".to_string()];
+        let results = adapter.evaluate_expressions(&expressions).await;
+        
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().results.get("value").and_then(|v| v.as_string()), Some("1"));
+        assert!(matches!(&results[1], Err(GdbError::CommandError(msg)) if msg.contains("nope")));
+        assert_eq!(results[2].as_ref().unwrap().results.get("value").and_then(|v| v.as_string()), Some("3"));
+        assert!(adapter.pending_commands.lock().unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_send_command_timeout_forgets_token() {
+        let (mut adapter, _events) = GdbAdapter::new();
+        *adapter.is_running.lock().unwrap() = true;
+        // Commands go nowhere, so the reply never arrives
+        adapter.stdin = Some(Box::new(tokio::io::sink()));
+        
+        let result = adapter.send_command_timeout("gdb-version", Duration::from_millis(50)).await;
+        
+        assert!(matches!(result, Err(GdbError::CommandError(ref msg)) if msg == "timeout"));
+        assert!(adapter.pending_commands.lock().unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_send_command_default_timeout_allows_replies() {
+        let (mut adapter, _events) = GdbAdapter::new();
+        *adapter.is_running.lock().unwrap() = true;
+        adapter.stdin = Some(Box::new(tokio::io::sink()));
+        adapter.set_command_timeout(Some(Duration::from_secs(1)));
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            gdb_stdout.write_all(b"1^done,value=\"42\"\n").await.unwrap();
+            // Keep the pipe open until the reply has been read
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        let result = adapter.send_command("data-evaluate-expression 42").await.unwrap();
+        
+        assert_eq!(result.results.get("value").and_then(|v| v.as_string()), Some("42"));
+    }
+    
+    #[tokio::test]
+    async fn test_drop_fails_pending_commands() {
+        let (adapter, _events) = GdbAdapter::new();
+        *adapter.is_running.lock().unwrap() = true;
+        let (_gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        let (reply, receiver) = oneshot::channel();
+        adapter.pending_commands.lock().unwrap().insert(1, reply);
+        
+        drop(adapter);
+        
+        let result = tokio::time::timeout(Duration::from_secs(1), receiver).await
+            .expect("pending command was left waiting");
+        assert!(result.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_latency_measured_per_reply() {
+        let (mut adapter, _written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        let monitor = adapter.latency_monitor();
+        assert_eq!(adapter.last_command_latency(), None);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            gdb_stdout.write_all(b"1^error,msg=\"No symbol table is loaded.\"\n").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        
+        assert!(adapter.send_command("symbol-info-functions").await.is_err());
+        
+        let latency = adapter.last_command_latency().unwrap();
+        assert!(latency >= Duration::from_millis(50), "{:?}", latency);
+        assert_eq!(monitor.average(), Some(latency));
+    }
+    
+    #[test]
+    fn test_latency_average_keeps_recent_samples() {
+        let monitor = LatencyMonitor::default();
+        monitor.record(Duration::from_secs(100));
+        for _ in 0..LATENCY_SAMPLES {
+            monitor.record(Duration::from_millis(10));
+        }
+        
+        assert_eq!(monitor.last(), Some(Duration::from_millis(10)));
+        assert_eq!(monitor.average(), Some(Duration::from_millis(10)));
+    }
+    
+    #[tokio::test]
+    async fn test_canceller_unblocks_waiting_command() {
+        let (mut adapter, _written) = flaky_adapter(0, std::io::ErrorKind::Interrupted);
+        let canceller = adapter.canceller();
+        
+        let cancel = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel_pending()
+        });
+        adapter.set_command_timeout(None);
+        let result = adapter.send_command("exec-continue").await;
+        
+        assert_eq!(cancel.await.unwrap(), 1);
+        assert!(matches!(result, Err(GdbError::CommunicationError(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_event_tap_sees_command_results() {
+        let (mut adapter, _events) = GdbAdapter::new();
+        *adapter.is_running.lock().unwrap() = true;
+        let (tap, mut tapped) = mpsc::unbounded_channel();
+        adapter.set_event_tap(Some(tap));
+        let (reply, _receiver) = oneshot::channel();
+        adapter.pending_commands.lock().unwrap().insert(7, reply);
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        gdb_stdout.write_all(b"7^done\n=thread-created,id=\"1\",group-id=\"i1\"\n").await.unwrap();
+        
+        match tapped.recv().await {
+            Some(GdbEvent::Result(result)) => assert_eq!(result.token, Some(7)),
+            other => panic!("Expected tapped result, got {:?}", other),
+        }
+        assert!(matches!(tapped.recv().await, Some(GdbEvent::Async(ref record)) if record.class == AsyncClass::ThreadCreated));
+    }
+    
+    #[test]
+    fn test_quote_mi_string() {
+        assert_eq!(quote_mi_string("&buffer"), "\"&buffer\"");
+        assert_eq!(quote_mi_string("strcmp(s, \"a\\n\")"), "\"strcmp(s, \\\"a\\\\n\\\")\"");
+    }
+    
+    #[test]
+    fn test_quote_arguments() {
+        let args = ["-v", "two words", "", "say \"hi\"", "it's"].map(String::from);
+        assert_eq!(quote_arguments(&args), r#"-v "two words" "" "say \"hi\"" "it's""#);
+        assert_eq!(quote_arguments(&[]), "");
+    }
+    
+    #[tokio::test]
+    async fn test_every_subscriber_receives_events() {
+        let (adapter, mut first) = GdbAdapter::new();
+        let mut second = adapter.subscribe();
+        *adapter.is_running.lock().unwrap() = true;
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        gdb_stdout.write_all(b"~\"hello\\n\"\n").await.unwrap();
+        
+        for events in [&mut first, &mut second] {
+            match events.recv().await.unwrap() {
+                GdbEvent::Stream(stream) => assert_eq!(stream.content, "hello\n"),
+                other => panic!("Expected stream record, got {:?}", other),
+            }
+        }
+    }
+    
+    #[test]
+    fn test_parse_show_architecture() {
+        assert_eq!(
+            parse_show_architecture("The target architecture is set to \"auto\" (currently \"i386:x86-64\").\n").as_deref(),
+            Some("i386:x86-64")
+        );
+        assert_eq!(parse_show_architecture("The target architecture is set to \"armv7\".\n").as_deref(), Some("armv7"));
+        assert_eq!(parse_show_architecture("The target architecture is set to \"auto\".\n"), None);
+        assert_eq!(parse_show_architecture(""), None);
+    }
+    
+    #[tokio::test]
+    async fn test_read_mi_line_waits_for_newline() {
+        let (mut gdb_stdout, stdout) = tokio::io::duplex(1024);
+        let mut reader = BufReader::new(stdout);
+        let mut line = Vec::new();
+        
+        gdb_stdout.write_all(b"1^done,value=").await.unwrap();
+        let read = tokio::spawn(async move {
+            let result = read_mi_line(&mut reader, &mut line, 64).await.unwrap();
+            (result, line)
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        gdb_stdout.write_all(b"\"42\"\n*running").await.unwrap();
+        drop(gdb_stdout);
+        let (result, line) = read.await.unwrap();
+        assert_eq!(result, MiLine::Line);
+        assert_eq!(line, b"1^done,value=\"42\"\n");
+    }
+    
+    #[tokio::test]
+    async fn test_read_mi_line_caps_length() {
+        let mut reader: &[u8] = b"12^done,contents=\"00112233445566778899\"\n~\"ok\"\nlast";
+        let mut line = Vec::new();
+        
+        // The rest of an overlong line is skipped, not taken for the next one
+        assert_eq!(read_mi_line(&mut reader, &mut line, 16).await.unwrap(), MiLine::TooLong);
+        assert_eq!(line, b"12^done,contents");
+        assert_eq!(result_token(&line), Some(12));
+        assert_eq!(read_mi_line(&mut reader, &mut line, 16).await.unwrap(), MiLine::Line);
+        assert_eq!(line, b"~\"ok\"\n");
+        assert_eq!(result_token(&line), None);
+        // Output that ends without a newline still counts
+        assert_eq!(read_mi_line(&mut reader, &mut line, 16).await.unwrap(), MiLine::Line);
+        assert_eq!(line, b"last");
+        assert_eq!(read_mi_line(&mut reader, &mut line, 16).await.unwrap(), MiLine::Eof);
+    }
+    
+    #[tokio::test]
+    async fn test_reader_tracks_target_running_state() {
+        let (adapter, mut events) = GdbAdapter::new();
+        *adapter.is_running.lock().unwrap() = true;
+        
+        let (mut gdb_stdout, reader) = tokio::io::duplex(1024);
+        adapter.start_output_reader(reader).await;
+        assert!(!adapter.is_target_running());
+        
+        gdb_stdout.write_all(b"*running,thread-id=\"all\"\n").await.unwrap();
+        events.recv().await.unwrap();
+        assert!(adapter.is_target_running());
+        
+        gdb_stdout.write_all(b"*stopped,reason=\"signal-received\",signal-name=\"SIGINT\"\n").await.unwrap();
+        events.recv().await.unwrap();
+        assert!(!adapter.is_target_running());
+        
+        // Non-stop mode: the target runs until its last running thread stops
+        for record in [
+            "*running,thread-id=\"1\"",
+            "*running,thread-id=\"2\"",
+            "*stopped,reason=\"breakpoint-hit\",thread-id=\"1\",stopped-threads=[\"1\"]",
+        ] {
+            gdb_stdout.write_all(format!("{}\n", record).as_bytes()).await.unwrap();
+            events.recv().await.unwrap();
+        }
+        assert!(adapter.is_target_running());
+        
+        gdb_stdout.write_all(b"*stopped,reason=\"signal-received\",thread-id=\"2\",stopped-threads=[\"2\"]\n").await.unwrap();
+        events.recv().await.unwrap();
+        assert!(!adapter.is_target_running());
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_drop_kills_running_process() {
+        let (mut adapter, _events) = GdbAdapter::new();
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id().unwrap();
+        adapter.process = Some(child);
+        
+        drop(adapter);
+        
+        // The process is gone once /proc no longer lists it or it is a zombie awaiting reaping
+        let stat_path = format!("/proc/{}/stat", pid);
+        let mut terminated = false;
+        for _ in 0..100 {
+            match std::fs::read_to_string(&stat_path) {
+                Err(_) => terminated = true,
+                Ok(stat) => terminated = stat.contains(") Z") || stat.contains(") X"),
+            }
+            if terminated {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(terminated, "GDB process {} still alive after drop", pid);
+    }
+    
     #[test]
     fn test_parse_stream_log() {
         let input = "&\"debug message\"";
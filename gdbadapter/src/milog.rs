@@ -0,0 +1,100 @@
+//! Transcript of the raw MI traffic, for debugging the adapter and bug reports
+//!
+//! Lines are handed to a background task over a channel, so a slow disk never
+//! holds up a command or the output reader. Each entry is one line:
+//! seconds since the Unix epoch, `>` for sent or `<` for received, and the line
+//! exactly as it went over the pipe.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+
+enum Entry {
+    Line(String),
+    Flush(oneshot::Sender<()>),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MiLog {
+    sender: mpsc::UnboundedSender<Entry>,
+}
+
+impl MiLog {
+    /// Append to the file at `path`, creating it if needed.
+    /// Must be called inside a Tokio runtime.
+    pub(crate) fn open(path: &Path) -> std::io::Result<Self> {
+        // Opened here so a bad path is reported to the caller rather than the task
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let mut file = tokio::io::BufWriter::new(tokio::fs::File::from_std(file));
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            while let Some(entry) = receiver.recv().await {
+                match entry {
+                    Entry::Line(line) => {
+                        if let Err(e) = file.write_all(line.as_bytes()).await {
+                            log::warn!("MiLog: Writing the MI log failed: {}", e);
+                            return;
+                        }
+                    }
+                    Entry::Flush(done) => {
+                        let _ = file.flush().await;
+                        let _ = done.send(());
+                    }
+                }
+            }
+            // Every sender is gone, e.g. logging was turned off
+            let _ = file.flush().await;
+        });
+
+        Ok(MiLog { sender })
+    }
+
+    /// Record a line written to GDB
+    pub(crate) fn sent(&self, line: &str) {
+        self.record('>', line);
+    }
+
+    /// Record a line GDB printed
+    pub(crate) fn received(&self, line: &str) {
+        self.record('<', line);
+    }
+
+    fn record(&self, direction: char, line: &str) {
+        let _ = self.sender.send(Entry::Line(format_entry(SystemTime::now(), direction, line)));
+    }
+
+    /// Wait until everything recorded so far is on disk
+    pub(crate) async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.sender.send(Entry::Flush(done)).is_ok() {
+            let _ = flushed.await;
+        }
+    }
+}
+
+fn format_entry(time: SystemTime, direction: char, line: &str) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    format!(
+        "{}.{:06} {} {}\n",
+        since_epoch.as_secs(),
+        since_epoch.subsec_micros(),
+        direction,
+        line.trim_end_matches(['\r', '\n'])
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_entry() {
+        let time = UNIX_EPOCH + Duration::from_micros(1_700_000_000_000_042);
+        assert_eq!(format_entry(time, '>', "1-exec-run\n"), "1700000000.000042 > 1-exec-run\n");
+        assert_eq!(format_entry(time, '<', "*stopped,reason=\"end-stepping-range\""), "1700000000.000042 < *stopped,reason=\"end-stepping-range\"\n");
+    }
+}
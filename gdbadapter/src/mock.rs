@@ -0,0 +1,142 @@
+//! Scripted stand-in for a GDB process
+//!
+//! `MockGdb` answers the adapter's MI commands from a script instead of running
+//! GDB, so the command/reply and event plumbing can be tested end to end. Each
+//! step names a pattern and the output lines GDB would print for it; hand the
+//! streams from `serve` to `GdbAdapter::start_session_with_io`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream};
+
+/// Buffer between the adapter and the mock, in each direction
+const MOCK_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+struct Step {
+    pattern: String,
+    replies: Vec<String>,
+    /// Time to wait before replying
+    delay: Duration,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    script: VecDeque<Step>,
+    received: Vec<String>,
+    unscripted: Vec<String>,
+}
+
+/// A fake GDB that replays canned MI output.
+///
+/// Commands are matched in script order: one containing the next step's pattern
+/// gets that step's replies, with `{token}` replaced by the command's token.
+/// Anything else (startup settings, `gdb-exit`) gets a bare `^done` and is
+/// recorded in `unscripted`.
+#[derive(Debug, Clone, Default)]
+pub struct MockGdb {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockGdb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step: answer the next command containing `pattern` with `replies`,
+    /// one MI line each, e.g. `{token}^running` followed by `*running,thread-id="all"`
+    pub fn expect(self, pattern: &str, replies: &[&str]) -> Self {
+        self.expect_delayed(pattern, Duration::ZERO, replies)
+    }
+
+    /// Like `expect`, but wait `delay` before replying, e.g. for a target that
+    /// takes a while to stop. Later commands wait behind it, as they would in GDB.
+    pub fn expect_delayed(self, pattern: &str, delay: Duration, replies: &[&str]) -> Self {
+        self.state.lock().unwrap().script.push_back(Step {
+            pattern: pattern.to_string(),
+            replies: replies.iter().map(|reply| reply.to_string()).collect(),
+            delay,
+        });
+        self
+    }
+
+    /// Start answering commands; returns GDB's output and input streams, in the
+    /// order `GdbAdapter::start_session_with_io` takes them.
+    /// Must be called inside a Tokio runtime.
+    pub fn serve(&self) -> (DuplexStream, DuplexStream) {
+        let (adapter_output, gdb_output) = tokio::io::duplex(MOCK_BUFFER_SIZE);
+        let (adapter_input, gdb_input) = tokio::io::duplex(MOCK_BUFFER_SIZE);
+        let state = self.state.clone();
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(gdb_input).lines();
+            let mut output = gdb_output;
+            // Ends when the adapter drops its input, e.g. in `stop_session`
+            while let Ok(Some(line)) = lines.next_line().await {
+                let (token, command) = split_token(&line);
+                let (replies, delay) = {
+                    let mut state = state.lock().unwrap();
+                    state.received.push(command.to_string());
+                    match state.script.front() {
+                        Some(step) if command.contains(&step.pattern) => {
+                            let step = state.script.pop_front().unwrap();
+                            (step.replies, step.delay)
+                        }
+                        _ => {
+                            state.unscripted.push(command.to_string());
+                            (vec!["{token}^done".to_string()], Duration::ZERO)
+                        }
+                    }
+                };
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                for reply in replies {
+                    let reply = format!("{}\n", reply.replace("{token}", token));
+                    if output.write_all(reply.as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+                let _ = output.write_all(b"(gdb)\n").await;
+            }
+        });
+
+        (adapter_output, adapter_input)
+    }
+
+    /// Every command received so far, without its token or leading `-`
+    pub fn received(&self) -> Vec<String> {
+        self.state.lock().unwrap().received.clone()
+    }
+
+    /// Commands that didn't match the script's next step
+    pub fn unscripted(&self) -> Vec<String> {
+        self.state.lock().unwrap().unscripted.clone()
+    }
+
+    /// Patterns of the steps no command has reached yet
+    pub fn remaining(&self) -> Vec<String> {
+        self.state.lock().unwrap().script.iter().map(|step| step.pattern.clone()).collect()
+    }
+}
+
+/// Split `12-break-insert main` into `12` and `break-insert main`
+fn split_token(line: &str) -> (&str, &str) {
+    let line = line.trim();
+    let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(line.len());
+    let (token, command) = line.split_at(digits);
+    (token, command.strip_prefix('-').unwrap_or(command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_token() {
+        assert_eq!(split_token("12-break-insert main\n"), ("12", "break-insert main"));
+        assert_eq!(split_token("-gdb-exit"), ("", "gdb-exit"));
+        assert_eq!(split_token("info registers"), ("", "info registers"));
+    }
+}
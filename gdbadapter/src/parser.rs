@@ -1,6 +1,6 @@
-/// GDB/MI output parser
-/// 
-/// This module handles parsing GDB/MI protocol output into structured data.
+//! GDB/MI output parser
+//! 
+//! This module handles parsing GDB/MI protocol output into structured data.
 
 use crate::types::*;
 use regex::Regex;
@@ -11,9 +11,13 @@ pub fn parse_gdb_output(line: &str) -> Result<GdbOutput, String> {
     let line = line.trim();
     log::trace!("parse_gdb_output: Parsing line: '{}'", line);
     
-    if line.is_empty() || line == "(gdb)" {
-        log::trace!("parse_gdb_output: Empty or prompt line, skipping");
-        return Err("Empty or prompt line".into());
+    if line.is_empty() {
+        log::trace!("parse_gdb_output: Empty line, skipping");
+        return Err("Empty line".into());
+    }
+    
+    if line == "(gdb)" {
+        return Ok(GdbOutput::Prompt);
     }
     
     // Check for stream records first (single character prefix)
@@ -100,10 +104,10 @@ fn parse_result_record(line: &str) -> Result<GdbResult, String> {
 
 /// Parse an async record
 fn parse_async_record(line: &str) -> Result<AsyncRecord, String> {
-    let (_prefix, rest) = if line.starts_with('*') {
-        ('*', &line[1..])
-    } else if line.starts_with('=') {
-        ('=', &line[1..])
+    let (_prefix, rest) = if let Some(rest) = line.strip_prefix('*') {
+        ('*', rest)
+    } else if let Some(rest) = line.strip_prefix('=') {
+        ('=', rest)
     } else {
         return Err("Invalid async record prefix".into());
     };
@@ -211,6 +215,16 @@ fn parse_identifier(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<
     Ok(identifier)
 }
 
+/// Whether the input starts with `identifier=` followed by a quoted string,
+/// tuple or list, i.e. a result rather than a bare value like `1` or `a==b`
+fn starts_with_result(chars: &std::iter::Peekable<std::str::Chars>) -> bool {
+    let mut lookahead = chars.clone();
+    if parse_identifier(&mut lookahead).is_err() || lookahead.next() != Some('=') {
+        return false;
+    }
+    matches!(lookahead.peek(), Some('"') | Some('{') | Some('['))
+}
+
 /// Parse a value (string, list, or tuple)
 fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Value, String> {
     match chars.peek() {
@@ -219,7 +233,7 @@ fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Value
             let mut string_val = String::new();
             let mut escaped = false;
             
-            while let Some(ch) = chars.next() {
+            for ch in chars.by_ref() {
                 if escaped {
                     match ch {
                         'n' => string_val.push('\n'),
@@ -249,22 +263,8 @@ fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Value
             let mut list = Vec::new();
             
             while chars.peek() != Some(&']') && chars.peek().is_some() {
-                // Check if this looks like a key=value pair
-                let mut temp_chars = chars.clone();
-                let mut has_equals = false;
-                
-                // Look ahead to see if there's an equals sign before a value delimiter
-                while let Some(&ch) = temp_chars.peek() {
-                    if ch == '=' {
-                        has_equals = true;
-                        break;
-                    } else if ch == ',' || ch == ']' || ch == '{' || ch == '[' || ch == '"' {
-                        break;
-                    }
-                    temp_chars.next();
-                }
-                
-                if has_equals {
+                // Lists hold either `key=value` results or bare values
+                if starts_with_result(chars) {
                     // Parse as key=value pair and store as a tuple
                     let key = parse_identifier(chars)?;
                     
@@ -319,22 +319,25 @@ fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Value
             Ok(Value::Tuple(tuple))
         }
         _ => {
-            // Try to parse as unquoted string until comma, space, or end
+            // Unquoted values aren't valid MI, but keep them whole up to the next
+            // separator: stopping at a space would leave the rest of the value to
+            // be misread as the next key
             let mut string_val = String::new();
             
             while let Some(&ch) = chars.peek() {
-                if ch == ',' || ch == ']' || ch == '}' || ch == ' ' {
+                if ch == ',' || ch == ']' || ch == '}' {
                     break;
                 }
                 string_val.push(ch);
                 chars.next();
             }
             
+            let string_val = string_val.trim_end();
             if string_val.is_empty() {
                 return Err("Empty value".into());
             }
             
-            Ok(Value::String(string_val))
+            Ok(Value::String(string_val.to_string()))
         }
     }
 }
@@ -403,6 +406,22 @@ mod tests {
         assert_eq!(results.get("thread-id").unwrap().as_string(), Some("1"));
     }
     
+    #[test]
+    fn test_parse_values_with_spaces() {
+        let input = "inst=\"mov r0, r1\",opcodes=\"01 00 a0 e1\"";
+        let results = parse_results(input).unwrap();
+        assert_eq!(results.get("inst").unwrap().as_string(), Some("mov r0, r1"));
+        assert_eq!(results.get("opcodes").unwrap().as_string(), Some("01 00 a0 e1"));
+
+        // Unquoted values run to the next separator rather than the first space
+        let input = "value=0x1234 <main+4>,frame={level=0 ,addr=\"0x1234\"}";
+        let results = parse_results(input).unwrap();
+        assert_eq!(results.get("value").unwrap().as_string(), Some("0x1234 <main+4>"));
+        let frame = results.get("frame").unwrap().as_tuple().unwrap();
+        assert_eq!(frame.get("level").unwrap().as_string(), Some("0"));
+        assert_eq!(frame.get("addr").unwrap().as_string(), Some("0x1234"));
+    }
+    
     #[test]
     fn test_parse_tuple_value() {
         let input = "bkpt={number=\"1\",type=\"breakpoint\"}";
@@ -425,6 +444,41 @@ mod tests {
         assert_eq!(groups[0].as_string(), Some("i1"));
     }
 
+    #[test]
+    fn test_parse_bare_value_list() {
+        let results = parse_results("x=[1,2,3]").unwrap();
+        
+        let values: Vec<_> = results.get("x").unwrap().as_list().unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap())
+            .collect();
+        assert_eq!(values, ["1", "2", "3"]);
+    }
+    
+    #[test]
+    fn test_parse_bare_tuple_list() {
+        let results = parse_results("x=[{a=\"b\"},{a=\"c\"}]").unwrap();
+        
+        let list = results.get("x").unwrap().as_list().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].as_tuple().unwrap().get("a").unwrap().as_string(), Some("b"));
+        assert_eq!(list[1].as_tuple().unwrap().get("a").unwrap().as_string(), Some("c"));
+    }
+    
+    #[test]
+    fn test_parse_bare_list_edge_cases() {
+        let results = parse_results("empty=[],nested=[[1,2],[]],ops=[a==b],tuples=[{},{a=[x]}]").unwrap();
+        
+        assert!(results.get("empty").unwrap().as_list().unwrap().is_empty());
+        let nested = results.get("nested").unwrap().as_list().unwrap();
+        assert_eq!(nested[0].as_list().unwrap().len(), 2);
+        assert!(nested[1].as_list().unwrap().is_empty());
+        assert_eq!(results.get("ops").unwrap().as_list().unwrap()[0].as_string(), Some("a==b"));
+        let tuples = results.get("tuples").unwrap().as_list().unwrap();
+        assert!(tuples[0].as_tuple().unwrap().is_empty());
+        assert_eq!(tuples[1].as_tuple().unwrap().get("a").unwrap().as_list().unwrap()[0].as_string(), Some("x"));
+    }
+
     #[test]
     fn test_parse_stack_frame() {
         // This is the exact output from the GDB logs
@@ -0,0 +1,111 @@
+//! Pseudo-terminal for the debugged program's own I/O
+//!
+//! GDB starts programs on its own terminal by default, which for the adapter is
+//! the MI pipe; `-inferior-tty-set` moves them onto the slave side of a pty
+//! opened here, and the master side is read into `@` target stream events.
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::fd::FromRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+
+use tokio::sync::broadcast;
+
+use crate::types::{GdbEvent, StreamRecord, StreamType};
+
+pub(crate) struct InferiorTty {
+    master: File,
+    /// Held open so reading the master doesn't fail between runs of the program;
+    /// closing it on drop ends the reader once nothing else uses the terminal
+    _slave: File,
+    path: String,
+}
+
+impl InferiorTty {
+    /// Open a new pty and forward what the program writes to it as target streams
+    pub(crate) fn open(event_sender: broadcast::Sender<GdbEvent>) -> std::io::Result<Self> {
+        let (master, path) = unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let master = File::from_raw_fd(fd);
+            if libc::grantpt(fd) != 0 || libc::unlockpt(fd) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let name = libc::ptsname(fd);
+            if name.is_null() {
+                return Err(std::io::Error::last_os_error());
+            }
+            (master, CStr::from_ptr(name).to_string_lossy().into_owned())
+        };
+        let slave = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(&path)?;
+
+        let mut reader = master.try_clone()?;
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 4096];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        // The terminal turns every newline into CRLF
+                        let content = String::from_utf8_lossy(&buffer[..n]).replace("\r\n", "\n");
+                        let _ = event_sender.send(GdbEvent::Stream(StreamRecord {
+                            stream_type: StreamType::Target,
+                            content,
+                        }));
+                    }
+                }
+            }
+        });
+
+        Ok(InferiorTty { master, _slave: slave, path })
+    }
+
+    /// Device path to hand to GDB, e.g. `/dev/pts/3`
+    pub(crate) fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Type `text` into the program's terminal
+    pub(crate) fn write_input(&mut self, text: &str) -> std::io::Result<()> {
+        self.master.write_all(text.as_bytes())?;
+        self.master.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slave_output_becomes_target_stream() {
+        let (sender, mut receiver) = broadcast::channel(16);
+        let tty = InferiorTty::open(sender).unwrap();
+        assert!(tty.path().starts_with("/dev/"));
+
+        let mut program = std::fs::OpenOptions::new().write(true).open(tty.path()).unwrap();
+        program.write_all(b"hello\n").unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            match receiver.try_recv() {
+                Ok(GdbEvent::Stream(stream)) => {
+                    assert_eq!(stream.stream_type, StreamType::Target);
+                    assert_eq!(stream.content, "hello\n");
+                    break;
+                }
+                Ok(_) => {}
+                Err(_) => {
+                    assert!(std::time::Instant::now() < deadline, "no output from the terminal");
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+            }
+        }
+    }
+}
@@ -1,28 +1,41 @@
-/// GDB/MI types and data structures
-/// 
-/// This module defines the data structures used to represent GDB/MI protocol messages.
+//! GDB/MI types and data structures
+//! 
+//! This module defines the data structures used to represent GDB/MI protocol messages.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Represents the different types of GDB output
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Serialized with a `type` field naming the variant, e.g. `{"type":"prompt"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
 pub enum GdbOutput {
     Result(GdbResult),
     Async(AsyncRecord),
     Stream(StreamRecord),
+    /// The `(gdb)` prompt, which ends each batch of output
+    Prompt,
 }
 
 /// Represents a GDB/MI result record
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GdbResult {
     pub token: Option<u32>,
     pub class: ResultClass,
     pub results: HashMap<String, Value>,
 }
 
-/// GDB/MI result classes
-#[derive(Debug, Clone, PartialEq)]
+impl GdbResult {
+    /// Walk nested tuples from the result's fields, e.g. `["BreakpointTable", "body"]`
+    pub fn get_path(&self, path: &[&str]) -> Option<&Value> {
+        get_path(&self.results, path)
+    }
+}
+
+/// GDB/MI result classes, serialized as GDB spells them (`done`, `error`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ResultClass {
     Done,
     Running,
@@ -32,15 +45,29 @@ pub enum ResultClass {
 }
 
 /// Represents a GDB/MI async record
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AsyncRecord {
     pub token: Option<u32>,
     pub class: AsyncClass,
     pub results: HashMap<String, Value>,
 }
 
-/// GDB/MI async classes
-#[derive(Debug, Clone, PartialEq)]
+impl AsyncRecord {
+    /// Walk nested tuples from the record's fields, e.g. `["frame", "addr"]`
+    pub fn get_path(&self, path: &[&str]) -> Option<&Value> {
+        get_path(&self.results, path)
+    }
+}
+
+/// Look up `path[0]` in `results`, then each further key in the tuple found so far
+fn get_path<'a>(results: &'a HashMap<String, Value>, path: &[&str]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    results.get(*first)?.get_path(rest)
+}
+
+/// GDB/MI async classes, serialized as GDB spells them (`stopped`, `breakpoint-modified`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum AsyncClass {
     // Exec async records
     Running,
@@ -70,14 +97,15 @@ pub enum AsyncClass {
 }
 
 /// Represents a GDB/MI stream record
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StreamRecord {
     pub stream_type: StreamType,
     pub content: String,
 }
 
 /// Types of GDB/MI streams
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum StreamType {
     Console,  // ~ prefix
     Target,   // @ prefix
@@ -117,6 +145,71 @@ impl Value {
             _ => None,
         }
     }
+    
+    /// Walk tuple members by key; an empty path is the value itself, and `None`
+    /// means a key is missing or a step is not a tuple
+    pub fn get_path(&self, path: &[&str]) -> Option<&Value> {
+        path.iter().try_fold(self, |value, key| value.as_tuple()?.get(*key))
+    }
+    
+    /// Parse a decimal string value such as a line number or count
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_string()?.trim().parse().ok()
+    }
+    
+    /// Parse a decimal string value that fits in 32 bits (lines, hit counts, ids)
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_u64()?.try_into().ok()
+    }
+    
+    /// Parse a signed decimal string value
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_string()?.trim().parse().ok()
+    }
+    
+    /// Parse a `0x`-prefixed hex address; a bare number is rejected since it could be decimal
+    pub fn as_address(&self) -> Option<u64> {
+        parse_address(self.as_string()?)
+    }
+}
+
+/// Parse a `0x`-prefixed hex address as GDB prints them, e.g. in `frame.addr`
+/// or an instruction's `address`; a bare number is rejected since GDB would read it as decimal
+pub fn parse_address(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X"))?;
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// Renders in GDB's value syntax (`{a = 1, b = {2, 3}}`), tuple members sorted by name
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{}", s),
+            Value::List(list) => {
+                write!(f, "{{")?;
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "}}")
+            }
+            Value::Tuple(tuple) => {
+                let mut members: Vec<_> = tuple.iter().collect();
+                members.sort_by_key(|(name, _)| name.as_str());
+                write!(f, "{{")?;
+                for (i, (name, value)) in members.into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} = {}", name, value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
 }
 
 /// Events that can be received from GDB
@@ -125,6 +218,19 @@ pub enum GdbEvent {
     Result(GdbResult),
     Async(AsyncRecord),
     Stream(StreamRecord),
+    /// GDB printed its prompt: the output it had ready has all been delivered
+    Prompt,
+    /// GDB's output stream closed while the session was running (GDB exited or crashed)
+    ConnectionLost,
+}
+
+/// Target to attach a debugging session to
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttachTarget {
+    /// A local process by PID
+    Process(u32),
+    /// A remote gdbserver stub at `host:port`
+    GdbServer(String),
 }
 
 /// Breakpoint information
@@ -139,8 +245,479 @@ pub struct Breakpoint {
     pub file: Option<String>,
     pub fullname: Option<String>,
     pub line: Option<u32>,
+    pub original_location: Option<String>,
     pub thread_groups: Vec<String>,
     pub times: u32,
+    /// Condition the breakpoint only stops on (`cond`)
+    #[serde(default)]
+    pub condition: Option<String>,
+    /// Remaining hits to skip before stopping (`ignore`)
+    #[serde(default)]
+    pub ignore_count: u32,
+    /// Resolved locations when GDB reports `addr="<MULTIPLE>"` (numbered `N.1`, `N.2`, ...)
+    #[serde(default)]
+    pub locations: Vec<BreakpointLocation>,
+    /// Location GDB is still waiting to resolve (`addr="<PENDING>"`)
+    #[serde(default)]
+    pub pending: Option<String>,
+}
+
+/// Options for inserting a breakpoint with `-break-insert`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BreakpointSpec {
+    /// Function, `file:line` or `*address`
+    pub location: String,
+    /// Only stop when this expression is true
+    pub condition: Option<String>,
+    /// Skip this many hits before stopping
+    pub ignore_count: u32,
+    /// Delete the breakpoint after its first stop
+    pub temporary: bool,
+    /// Use a hardware breakpoint (needed for code in flash on most targets)
+    pub hardware: bool,
+    /// Keep the breakpoint pending if the location isn't known yet (`-f`),
+    /// e.g. in a shared library that hasn't been loaded
+    #[serde(default)]
+    pub pending: bool,
+}
+
+impl BreakpointSpec {
+    /// A plain breakpoint at `location`
+    pub fn new(location: &str) -> Self {
+        BreakpointSpec { location: location.to_string(), ..Default::default() }
+    }
+    
+    /// A plain breakpoint at a checked location
+    pub fn at(location: &LocationSpec) -> Self {
+        Self::new(&location.mi_argument())
+    }
+    
+    /// The `-break-insert` command (without the leading dash) for this spec
+    pub fn mi_command(&self) -> String {
+        let mut command = String::from("break-insert");
+        if self.temporary {
+            command.push_str(" -t");
+        }
+        if self.hardware {
+            command.push_str(" -h");
+        }
+        if self.pending {
+            command.push_str(" -f");
+        }
+        if let Some(condition) = self.condition.as_deref().filter(|c| !c.trim().is_empty()) {
+            command.push_str(&format!(" -c {}", crate::quote_mi_string(condition.trim())));
+        }
+        if self.ignore_count > 0 {
+            command.push_str(&format!(" -i {}", self.ignore_count));
+        }
+        command.push_str(&format!(" {}", self.location));
+        command
+    }
+}
+
+/// Where a breakpoint goes: a function, a source line or an address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LocationSpec {
+    Function(String),
+    FileLine(String, u32),
+    /// Address or address expression, without the leading `*`
+    Address(String),
+}
+
+impl LocationSpec {
+    /// Parse a location as typed for `break`: `*address`, `file:line` or a function.
+    /// `file:function` stays a function location
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let location = if let Some(address) = input.strip_prefix('*') {
+            LocationSpec::Address(address.trim().to_string())
+        } else {
+            match input.rsplit_once(':') {
+                Some((file, line)) if !line.is_empty() && line.bytes().all(|b| b.is_ascii_digit()) => {
+                    let line = line.parse().map_err(|_| format!("Line number out of range: {}", line))?;
+                    LocationSpec::FileLine(file.trim().to_string(), line)
+                }
+                _ => LocationSpec::Function(input.to_string()),
+            }
+        };
+        location.validate()?;
+        Ok(location)
+    }
+    
+    /// Check the parts GDB would otherwise reject with a less specific error
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            LocationSpec::Function(name) if name.trim().is_empty() => Err("No function given".to_string()),
+            LocationSpec::FileLine(file, _) if file.trim().is_empty() => Err("No source file given".to_string()),
+            LocationSpec::FileLine(_, 0) => Err("Line numbers start at 1".to_string()),
+            LocationSpec::Address(address) if address.trim().is_empty() => Err("No address given".to_string()),
+            _ => Ok(()),
+        }
+    }
+    
+    /// The location as a `-break-insert` argument, quoted when it contains spaces or quotes
+    pub fn mi_argument(&self) -> String {
+        let location = self.to_string();
+        if location.contains(|c: char| c.is_whitespace() || c == '"') {
+            crate::quote_mi_string(&location)
+        } else {
+            location
+        }
+    }
+}
+
+impl std::fmt::Display for LocationSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocationSpec::Function(name) => write!(f, "{}", name.trim()),
+            LocationSpec::FileLine(file, line) => write!(f, "{}:{}", file.trim(), line),
+            LocationSpec::Address(address) => write!(f, "*{}", address.trim()),
+        }
+    }
+}
+
+/// A single resolved location of a multi-location breakpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakpointLocation {
+    pub number: String,
+    pub enabled: String,
+    pub address: Option<String>,
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub fullname: Option<String>,
+    pub line: Option<u32>,
+}
+
+impl Breakpoint {
+    /// Build a breakpoint from a `bkpt` tuple as returned by `-break-insert` or `-break-list`
+    pub fn from_tuple(tuple: &HashMap<String, Value>) -> Option<Self> {
+        let field = |key: &str| tuple.get(key).and_then(|v| v.as_string()).map(|s| s.to_string());
+        
+        let thread_groups = tuple.get("thread-groups")
+            .and_then(|v| v.as_list())
+            .map(|groups| groups.iter().filter_map(|g| g.as_string()).map(|s| s.to_string()).collect())
+            .unwrap_or_default();
+        
+        let locations = tuple.get("locations")
+            .and_then(|v| v.as_list())
+            .map(|list| list.iter().filter_map(|v| v.as_tuple()).filter_map(BreakpointLocation::from_tuple).collect())
+            .unwrap_or_default();
+        
+        Some(Breakpoint {
+            number: field("number")?,
+            breakpoint_type: field("type").unwrap_or_default(),
+            disposition: field("disp").unwrap_or_default(),
+            enabled: field("enabled").unwrap_or_default(),
+            address: field("addr"),
+            function: field("func"),
+            file: field("file"),
+            fullname: field("fullname"),
+            line: tuple.get("line").and_then(Value::as_u32),
+            original_location: field("original-location"),
+            thread_groups,
+            times: tuple.get("times").and_then(Value::as_u32).unwrap_or(0),
+            condition: field("cond"),
+            ignore_count: tuple.get("ignore").and_then(Value::as_u32).unwrap_or(0),
+            locations,
+            pending: field("pending"),
+        })
+    }
+    
+    /// Whether GDB placed the breakpoint pending, without an address yet
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some() || self.address.as_deref() == Some("<PENDING>")
+    }
+    
+    /// Whether GDB resolved this breakpoint to more than one address
+    pub fn has_multiple_locations(&self) -> bool {
+        !self.locations.is_empty()
+    }
+    
+    /// Whether GDB deletes the breakpoint when it is hit (`-break-insert -t`)
+    pub fn is_temporary(&self) -> bool {
+        self.disposition == "del"
+    }
+    
+    /// Whether this is a watchpoint (`watchpoint`, `hw watchpoint`, `read watchpoint`, ...)
+    pub fn is_watchpoint(&self) -> bool {
+        self.breakpoint_type.contains("watchpoint")
+    }
+    
+    /// Extract all entries from a `-break-list` result (`BreakpointTable.body`)
+    pub fn list_from_result(result: &GdbResult) -> Vec<Self> {
+        result.get_path(&["BreakpointTable", "body"])
+            .and_then(|v| v.as_list())
+            .map(|body| body.iter()
+                .filter_map(|row| row.as_tuple())
+                .filter_map(|row| row.get("bkpt").and_then(|v| v.as_tuple()))
+                .filter_map(Breakpoint::from_tuple)
+                .collect())
+            .unwrap_or_default()
+    }
+}
+
+impl BreakpointLocation {
+    /// Build a location from one entry of a breakpoint's `locations` list
+    pub fn from_tuple(tuple: &HashMap<String, Value>) -> Option<Self> {
+        let field = |key: &str| tuple.get(key).and_then(|v| v.as_string()).map(|s| s.to_string());
+        
+        Some(BreakpointLocation {
+            number: field("number")?,
+            enabled: field("enabled").unwrap_or_default(),
+            address: field("addr"),
+            function: field("func"),
+            file: field("file"),
+            fullname: field("fullname"),
+            line: tuple.get("line").and_then(Value::as_u32),
+        })
+    }
+}
+
+/// Kind of access a watchpoint triggers on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WatchKind {
+    Write,
+    Read,
+    Access,
+}
+
+impl WatchKind {
+    /// Flag passed to `-break-watch` for this kind
+    pub fn mi_flag(&self) -> Option<&'static str> {
+        match self {
+            WatchKind::Write => None,
+            WatchKind::Read => Some("-r"),
+            WatchKind::Access => Some("-a"),
+        }
+    }
+    
+    /// Result key GDB uses for the created watchpoint
+    pub fn result_key(&self) -> &'static str {
+        match self {
+            WatchKind::Write => "wpt",
+            WatchKind::Read => "hw-rwpt",
+            WatchKind::Access => "hw-awpt",
+        }
+    }
+}
+
+impl std::fmt::Display for WatchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            WatchKind::Write => "write",
+            WatchKind::Read => "read",
+            WatchKind::Access => "access",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Output format for `-data-list-register-values`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RegisterFormat {
+    #[default]
+    Hex,
+    Decimal,
+    Binary,
+    Octal,
+    /// GDB's natural format for the register type (e.g. floats for FP registers)
+    Natural,
+}
+
+impl RegisterFormat {
+    pub const ALL: [RegisterFormat; 5] = [
+        RegisterFormat::Hex,
+        RegisterFormat::Decimal,
+        RegisterFormat::Binary,
+        RegisterFormat::Octal,
+        RegisterFormat::Natural,
+    ];
+    
+    /// Format letter passed to `-data-list-register-values`
+    pub fn mi_letter(&self) -> char {
+        match self {
+            RegisterFormat::Hex => 'x',
+            RegisterFormat::Decimal => 'd',
+            RegisterFormat::Binary => 't',
+            RegisterFormat::Octal => 'o',
+            RegisterFormat::Natural => 'N',
+        }
+    }
+}
+
+impl std::fmt::Display for RegisterFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RegisterFormat::Hex => "hex",
+            RegisterFormat::Decimal => "decimal",
+            RegisterFormat::Binary => "binary",
+            RegisterFormat::Octal => "octal",
+            RegisterFormat::Natural => "natural",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Which registers `get_registers` lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RegisterSet {
+    /// The registers given to `set_core_registers`, or all of them if none were
+    #[default]
+    Core,
+    /// Every register GDB knows, including system and debug registers
+    All,
+}
+
+/// Watchpoint information as returned by `-break-watch`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Watchpoint {
+    pub number: String,
+    pub expression: String,
+    pub kind: WatchKind,
+}
+
+impl Watchpoint {
+    /// Extract the watchpoint from a `-break-watch` result (`wpt`, `hw-rwpt` or `hw-awpt`)
+    pub fn from_result(result: &GdbResult) -> Option<Self> {
+        [WatchKind::Write, WatchKind::Read, WatchKind::Access]
+            .into_iter()
+            .find_map(|kind| {
+                let tuple = result.results.get(kind.result_key())?.as_tuple()?;
+                Some(Watchpoint {
+                    number: tuple.get("number")?.as_string()?.to_string(),
+                    expression: tuple.get("exp")?.as_string()?.to_string(),
+                    kind,
+                })
+            })
+    }
+}
+
+/// A GDB variable object, from `-var-create` or one child of `-var-list-children`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarObj {
+    /// GDB's handle for the object, e.g. `var1` or `var1.pos.x` for a child
+    pub name: String,
+    /// What to show for it: the created expression, or a child's field name or index
+    pub expression: String,
+    /// Children GDB knows of; a dynamic varobj may have more than it reports
+    pub num_children: u32,
+    pub value: Option<String>,
+    pub type_name: Option<String>,
+    pub thread_id: Option<String>,
+    /// Children come from a pretty-printer, so `num_children` is only a lower bound
+    pub dynamic: bool,
+    /// A dynamic varobj has children beyond the ones listed so far
+    pub has_more: bool,
+    /// Pretty-printer hint, e.g. `array`, `map` or `string`
+    pub display_hint: Option<String>,
+}
+
+impl VarObj {
+    /// Build from a `-var-create` result or a `child={...}` tuple
+    pub fn from_tuple(tuple: &HashMap<String, Value>) -> Result<Self, String> {
+        let field = |key: &str| tuple.get(key).and_then(|v| v.as_string()).map(|s| s.to_string());
+        let flag = |key: &str| field(key).is_some_and(|s| s == "1");
+        let name = field("name").ok_or("Variable object missing 'name' field")?;
+        
+        Ok(VarObj {
+            // `-var-create` has no `exp`; the caller knows the expression it created
+            expression: field("exp").unwrap_or_else(|| name.clone()),
+            name,
+            num_children: tuple.get("numchild").and_then(Value::as_u32).unwrap_or(0),
+            value: field("value"),
+            type_name: field("type"),
+            thread_id: field("thread-id"),
+            dynamic: flag("dynamic"),
+            has_more: flag("has_more"),
+            display_hint: field("displayhint"),
+        })
+    }
+    
+    /// Whether the object can be expanded
+    pub fn has_children(&self) -> bool {
+        self.num_children > 0 || self.has_more
+    }
+}
+
+/// A range of children from `-var-list-children`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VarObjChildren {
+    pub children: Vec<VarObj>,
+    /// A dynamic parent has children past this range
+    pub has_more: bool,
+}
+
+impl VarObjChildren {
+    pub fn from_result(result: &GdbResult) -> Result<Self, String> {
+        let children = match result.results.get("children") {
+            Some(children) => children.as_list()
+                .ok_or("Children is not a list")?
+                .iter()
+                .enumerate()
+                .map(|(i, child)| child.get_path(&["child"]).unwrap_or(child)
+                    .as_tuple()
+                    .ok_or_else(|| format!("Child {}: not a tuple", i))
+                    .and_then(|child| VarObj::from_tuple(child).map_err(|e| format!("Child {}: {}", i, e))))
+                .collect::<Result<_, _>>()?,
+            // GDB leaves the list out when there are no children
+            None => Vec::new(),
+        };
+        let has_more = result.results.get("has_more").and_then(Value::as_string) == Some("1");
+        
+        Ok(VarObjChildren { children, has_more })
+    }
+}
+
+/// One entry of a `-var-update` changelist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarObjChange {
+    pub name: String,
+    pub value: Option<String>,
+    /// `false` once the expression can't be evaluated where the program is now,
+    /// `invalid` when it can never be again (e.g. the program was rebuilt)
+    pub in_scope: String,
+    /// The type, and with it the children, changed; they have to be listed again
+    pub type_changed: bool,
+    pub new_type: Option<String>,
+    pub new_num_children: Option<u32>,
+    pub has_more: bool,
+    /// Children a dynamic varobj gained since it was last listed
+    pub new_children: Vec<VarObj>,
+}
+
+impl VarObjChange {
+    /// Entries of a `-var-update` result's `changelist`
+    pub fn list_from_result(result: &GdbResult) -> Vec<Self> {
+        result.results.get("changelist")
+            .and_then(|v| v.as_list())
+            .map(|changes| changes.iter()
+                .filter_map(|change| change.as_tuple())
+                .filter_map(|change| {
+                    let field = |key: &str| change.get(key).and_then(|v| v.as_string()).map(|s| s.to_string());
+                    Some(VarObjChange {
+                        name: field("name")?,
+                        value: field("value"),
+                        in_scope: field("in_scope").unwrap_or_else(|| "true".to_string()),
+                        type_changed: field("type_changed").as_deref() == Some("true"),
+                        new_type: field("new_type"),
+                        new_num_children: change.get("new_num_children").and_then(Value::as_u32),
+                        has_more: field("has_more").as_deref() == Some("1"),
+                        new_children: change.get("new_children")
+                            .and_then(|v| v.as_list())
+                            .map(|children| children.iter()
+                                .filter_map(|child| child.get_path(&["child"]).unwrap_or(child).as_tuple())
+                                .filter_map(|child| VarObj::from_tuple(child).ok())
+                                .collect())
+                            .unwrap_or_default(),
+                    })
+                })
+                .collect())
+            .unwrap_or_default()
+    }
+    
+    pub fn is_in_scope(&self) -> bool {
+        self.in_scope == "true"
+    }
 }
 
 /// Frame information
@@ -155,6 +732,36 @@ pub struct Frame {
     pub arch: Option<String>,
 }
 
+impl Frame {
+    /// Build a frame from an MI `frame={...}` tuple
+    pub fn from_tuple(frame: &HashMap<String, Value>) -> Option<Self> {
+        Self::parse_tuple(frame).ok()
+    }
+    
+    /// Build a frame from an MI frame value, either the tuple itself or a
+    /// `frame={...}` entry of a list; the error names what is missing
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        let frame = value.get_path(&["frame"]).unwrap_or(value)
+            .as_tuple()
+            .ok_or("Frame is not a tuple")?;
+        Self::parse_tuple(frame)
+    }
+    
+    fn parse_tuple(frame: &HashMap<String, Value>) -> Result<Self, String> {
+        let field = |key: &str| frame.get(key).and_then(|v| v.as_string()).map(|s| s.to_string());
+        
+        Ok(Frame {
+            address: field("addr").ok_or("Frame missing 'addr' field")?,
+            function: field("func"),
+            args: frame.get("args").map(Argument::list_from_value).unwrap_or_default(),
+            file: field("file"),
+            fullname: field("fullname"),
+            line: frame.get("line").and_then(Value::as_u32),
+            arch: field("arch"),
+        })
+    }
+}
+
 /// Function argument
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Argument {
@@ -162,6 +769,22 @@ pub struct Argument {
     pub value: String,
 }
 
+impl Argument {
+    /// Arguments from an MI `args=[{name="argc",value="1"}]` list; the value is
+    /// empty when GDB was asked for names only
+    pub fn list_from_value(args: &Value) -> Vec<Argument> {
+        args.as_list()
+            .map(|args| args.iter()
+                .filter_map(|arg| arg.as_tuple())
+                .filter_map(|arg| Some(Argument {
+                    name: arg.get("name")?.as_string()?.to_string(),
+                    value: arg.get("value").and_then(|v| v.as_string()).unwrap_or_default().to_string(),
+                }))
+                .collect())
+            .unwrap_or_default()
+    }
+}
+
 /// Stop reason for stopped events
 #[derive(Debug, Clone, PartialEq)]
 pub enum StopReason {
@@ -188,6 +811,7 @@ pub enum StopReason {
 
 impl StopReason {
     /// Parse a stop reason from string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "breakpoint-hit" => Some(StopReason::BreakpointHit),
@@ -241,6 +865,92 @@ impl std::fmt::Display for StopReason {
     }
 }
 
+/// Structured information carried by a `*stopped` async record
+#[derive(Debug, Clone, PartialEq)]
+pub struct StopInfo {
+    pub reason: Option<StopReason>,
+    pub thread_id: Option<String>,
+    pub breakpoint_number: Option<String>,
+    pub frame: Option<Frame>,
+    /// Exit status for `exited` stops (GDB reports it in octal)
+    pub exit_code: Option<i32>,
+    /// Signal for `signal-received` and `exited-signalled` stops, e.g. `SIGKILL`
+    pub signal_name: Option<String>,
+    /// GDB's description of the signal, e.g. `Segmentation fault`
+    pub signal_meaning: Option<String>,
+}
+
+impl StopInfo {
+    /// Extract stop information from an async record, `None` unless it is `*stopped`
+    pub fn from_record(record: &AsyncRecord) -> Option<Self> {
+        if record.class != AsyncClass::Stopped {
+            return None;
+        }
+        
+        let field = |key: &str| record.results.get(key).and_then(|v| v.as_string()).map(|s| s.to_string());
+        
+        let frame = record.results.get("frame").and_then(|v| Frame::from_value(v).ok());
+        
+        Some(StopInfo {
+            reason: field("reason").and_then(|s| StopReason::from_str(&s)),
+            thread_id: field("thread-id"),
+            breakpoint_number: field("bkptno"),
+            frame,
+            exit_code: field("exit-code").and_then(|s| i32::from_str_radix(&s, 8).ok()),
+            signal_name: field("signal-name"),
+            signal_meaning: field("signal-meaning"),
+        })
+    }
+}
+
+/// A thread as reported by `-thread-info`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreadInfo {
+    pub id: u32,
+    pub target_id: String,
+    pub name: Option<String>,
+    /// `stopped` or `running`
+    pub state: String,
+    pub frame: Option<Frame>,
+}
+
+impl ThreadInfo {
+    /// Build a thread from one entry of the `threads` list
+    pub fn from_tuple(thread: &HashMap<String, Value>) -> Option<Self> {
+        let field = |key: &str| thread.get(key).and_then(|v| v.as_string()).map(|s| s.to_string());
+        
+        Some(ThreadInfo {
+            id: thread.get("id")?.as_u32()?,
+            target_id: field("target-id").unwrap_or_default(),
+            name: field("name"),
+            state: field("state").unwrap_or_default(),
+            frame: thread.get("frame").and_then(|v| Frame::from_value(v).ok()),
+        })
+    }
+}
+
+/// Result of `-thread-info`: all threads plus the currently selected one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThreadList {
+    pub threads: Vec<ThreadInfo>,
+    pub current_thread_id: Option<u32>,
+}
+
+impl ThreadList {
+    pub fn from_result(result: &GdbResult) -> Self {
+        let threads = result.results.get("threads")
+            .and_then(|v| v.as_list())
+            .map(|threads| threads.iter()
+                .filter_map(|thread| thread.as_tuple())
+                .filter_map(ThreadInfo::from_tuple)
+                .collect())
+            .unwrap_or_default();
+        let current_thread_id = result.results.get("current-thread-id").and_then(Value::as_u32);
+        
+        ThreadList { threads, current_thread_id }
+    }
+}
+
 /// Represents a CPU register
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Register {
@@ -249,6 +959,129 @@ pub struct Register {
     pub value: String,
 }
 
+impl Register {
+    /// Register numbers listed by `-data-list-changed-registers`
+    pub fn changed_numbers(result: &GdbResult) -> Vec<u32> {
+        result.results.get("changed-registers")
+            .and_then(|v| v.as_list())
+            .map(|numbers| numbers.iter().filter_map(Value::as_u32).collect())
+            .unwrap_or_default()
+    }
+    
+    /// Lane views of a vector register (`xmm0`, `v0`, `q0`, ...), `None` for scalar registers
+    pub fn vector_views(&self) -> Option<Vec<VectorView>> {
+        VectorView::parse(&self.value)
+    }
+}
+
+/// One interpretation of a vector register, e.g. `v4_int32` or `d.u` for NEON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorView {
+    /// Union member path, nested members joined with `.`
+    pub name: String,
+    pub lanes: Vec<String>,
+}
+
+impl VectorView {
+    /// Parse GDB's union syntax, e.g. `{v4_int32 = {0x1, 0x0 <repeats 3 times>}, ...}`
+    pub fn parse(value: &str) -> Option<Vec<Self>> {
+        let chars: Vec<char> = value.trim().chars().collect();
+        let mut pos = 0;
+        let parsed = parse_vector_value(&chars, &mut pos)?;
+        
+        let mut views = Vec::new();
+        flatten_vector_value(String::new(), parsed, &mut views);
+        if views.is_empty() || views.iter().any(|view| view.name.is_empty()) {
+            return None;
+        }
+        Some(views)
+    }
+}
+
+/// Intermediate tree for the union syntax GDB prints for vector registers
+enum VectorValue {
+    Scalar(String),
+    Members(Vec<(String, VectorValue)>),
+    Lanes(Vec<String>),
+}
+
+fn parse_vector_value(chars: &[char], pos: &mut usize) -> Option<VectorValue> {
+    skip_vector_whitespace(chars, pos);
+    if chars.get(*pos) != Some(&'{') {
+        let start = *pos;
+        while *pos < chars.len() && chars[*pos] != ',' && chars[*pos] != '}' {
+            *pos += 1;
+        }
+        return Some(VectorValue::Scalar(chars[start..*pos].iter().collect::<String>().trim().to_string()));
+    }
+    
+    *pos += 1;
+    let mut members = Vec::new();
+    let mut lanes = Vec::new();
+    loop {
+        skip_vector_whitespace(chars, pos);
+        match chars.get(*pos)? {
+            '}' => {
+                *pos += 1;
+                break;
+            }
+            ',' => {
+                *pos += 1;
+                continue;
+            }
+            _ => {}
+        }
+        
+        // `name = value` member, or a bare lane value
+        let rest: String = chars[*pos..].iter().take_while(|c| **c != ',' && **c != '}' && **c != '{').collect();
+        match rest.split_once(" = ") {
+            Some((name, _)) if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') => {
+                *pos += name.len() + 3;
+                members.push((name.to_string(), parse_vector_value(chars, pos)?));
+            }
+            _ => match parse_vector_value(chars, pos)? {
+                VectorValue::Scalar(lane) => lanes.extend(expand_repeats(&lane)),
+                _ => return None,
+            },
+        }
+    }
+    
+    match (members.is_empty(), lanes.is_empty()) {
+        (false, true) => Some(VectorValue::Members(members)),
+        (true, _) => Some(VectorValue::Lanes(lanes)),
+        (false, false) => None,
+    }
+}
+
+fn skip_vector_whitespace(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+/// Expand GDB's `0x0 <repeats 16 times>` shorthand
+fn expand_repeats(lane: &str) -> Vec<String> {
+    if let Some((value, rest)) = lane.split_once(" <repeats ") {
+        if let Some(count) = rest.strip_suffix(" times>").and_then(|n| n.parse().ok()) {
+            return vec![value.to_string(); count];
+        }
+    }
+    vec![lane.to_string()]
+}
+
+fn flatten_vector_value(path: String, value: VectorValue, views: &mut Vec<VectorView>) {
+    match value {
+        VectorValue::Lanes(lanes) => views.push(VectorView { name: path, lanes }),
+        VectorValue::Scalar(lane) => views.push(VectorView { name: path, lanes: vec![lane] }),
+        VectorValue::Members(members) => {
+            for (name, member) in members {
+                let member_path = if path.is_empty() { name } else { format!("{}.{}", path, name) };
+                flatten_vector_value(member_path, member, views);
+            }
+        }
+    }
+}
+
 /// Represents a line of disassembled code
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AssemblyLine {
@@ -257,6 +1090,27 @@ pub struct AssemblyLine {
     pub offset: Option<u32>,
     pub instruction: String,
     pub opcodes: Option<String>,
+    /// Source line the instruction was generated from, when disassembled with source
+    pub source: Option<SourceLine>,
+}
+
+/// A source location from a `src_and_asm_line` block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceLine {
+    pub file: String,
+    pub fullname: Option<String>,
+    pub line: u32,
+}
+
+impl SourceLine {
+    /// Build from a `src_and_asm_line` tuple, `None` when it has no line info
+    pub fn from_tuple(tuple: &HashMap<String, Value>) -> Option<Self> {
+        Some(SourceLine {
+            file: tuple.get("file")?.as_string()?.to_string(),
+            fullname: tuple.get("fullname").and_then(|v| v.as_string()).map(|s| s.to_string()),
+            line: tuple.get("line")?.as_u32()?,
+        })
+    }
 }
 
 /// Represents a stack frame
@@ -269,6 +1123,56 @@ pub struct StackFrame {
     pub fullname: Option<String>,
     pub line: Option<u32>,
     pub arch: Option<String>,
+    /// Filled in from `-stack-list-arguments`, which `-stack-list-frames` doesn't report
+    #[serde(default)]
+    pub args: Vec<Argument>,
+}
+
+impl StackFrame {
+    /// `frame` at `level` of the stack
+    pub fn new(level: u32, frame: Frame) -> Self {
+        // Destructured so a new `Frame` field can't be silently dropped
+        let Frame { address, function, args, file, fullname, line, arch } = frame;
+        StackFrame { level, address, function, file, fullname, line, arch, args }
+    }
+    
+    /// Build a frame from one entry of a `stack` list: a `Frame` plus its level
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        let level = value.get_path(&["frame"]).unwrap_or(value)
+            .as_tuple()
+            .and_then(|frame| frame.get("level"))
+            .and_then(Value::as_u32)
+            .ok_or("Frame missing or invalid 'level' field")?;
+        Ok(StackFrame::new(level, Frame::from_value(value)?))
+    }
+    
+    /// Frames of a `-stack-list-frames` result
+    pub fn list_from_result(result: &GdbResult) -> Result<Vec<StackFrame>, String> {
+        let frames = result.results.get("stack")
+            .and_then(|v| v.as_list())
+            .ok_or("No 'stack' field found in result")?;
+        frames.iter()
+            .enumerate()
+            .map(|(index, frame)| StackFrame::from_value(frame).map_err(|e| format!("Frame {}: {}", index, e)))
+            .collect()
+    }
+    
+    /// Arguments of each frame level from a `-stack-list-arguments` result
+    pub fn arguments_from_result(result: &GdbResult) -> Vec<(u32, Vec<Argument>)> {
+        result.results.get("stack-args")
+            .and_then(|v| v.as_list())
+            .map(|frames| frames.iter()
+                // Entries are `frame={...}` results or bare tuples
+                .map(|frame| frame.get_path(&["frame"]).unwrap_or(frame))
+                .filter_map(|frame| frame.as_tuple())
+                .filter_map(|frame| {
+                    let level = frame.get("level").and_then(Value::as_u32)?;
+                    let args = frame.get("args").map(Argument::list_from_value).unwrap_or_default();
+                    Some((level, args))
+                })
+                .collect())
+            .unwrap_or_default()
+    }
 }
 
 /// Represents a block of memory
@@ -1,12 +1,12 @@
-/// Integration tests for the GDB adapter
-/// 
-/// These tests demonstrate how to use the GDB adapter and test parsing functionality
+//! Integration tests for the GDB adapter
+//! 
+//! These tests demonstrate how to use the GDB adapter and test parsing functionality
 
 use gdbadapter::*;
 
 #[test]
 fn test_gdb_adapter_creation() {
-    let adapter = GdbAdapter::new();
+    let (adapter, _events) = GdbAdapter::new();
     assert!(!adapter.is_running());
 }
 
@@ -39,6 +39,170 @@ fn test_parse_complex_breakpoint_result() {
     }
 }
 
+#[test]
+fn test_records_round_trip_through_json() {
+    let input = r#"12^done,bkpt={number="1",type="breakpoint",addr="0x08048564",func="main",thread-groups=["i1"],times="0"}"#;
+    let output = parse_gdb_output(input).unwrap();
+    
+    let json = serde_json::to_value(&output).unwrap();
+    assert_eq!(json["type"], "result");
+    assert_eq!(json["token"], 12);
+    assert_eq!(json["class"], "done");
+    // Values keep their plain JSON shape
+    assert_eq!(json["results"]["bkpt"]["func"], "main");
+    assert_eq!(json["results"]["bkpt"]["thread-groups"][0], "i1");
+    assert_eq!(serde_json::from_value::<GdbOutput>(json).unwrap(), output);
+    
+    let stopped = parse_gdb_output(r#"*stopped,reason="breakpoint-hit",bkptno="1""#).unwrap();
+    let json = serde_json::to_string(&stopped).unwrap();
+    assert!(json.contains(r#""class":"stopped""#));
+    assert_eq!(serde_json::from_str::<GdbOutput>(&json).unwrap(), stopped);
+    
+    let notify = parse_gdb_output(r#"=breakpoint-modified,bkpt={number="1",times="1"}"#).unwrap();
+    assert!(serde_json::to_string(&notify).unwrap().contains(r#""class":"breakpoint-modified""#));
+    
+    let stream = parse_gdb_output(r#"@"hello\n""#).unwrap();
+    let json = serde_json::to_value(&stream).unwrap();
+    assert_eq!(json["stream_type"], "target");
+    assert_eq!(serde_json::from_value::<GdbOutput>(json).unwrap(), stream);
+    assert_eq!(serde_json::to_string(&GdbOutput::Prompt).unwrap(), r#"{"type":"prompt"}"#);
+}
+
+#[test]
+fn test_parse_multiple_location_breakpoint() {
+    let input = r#"^done,bkpt={number="2",type="breakpoint",disp="keep",enabled="y",addr="<MULTIPLE>",times="0",original-location="inlined_fn",locations=[{number="2.1",enabled="y",addr="0x08000124",func="inlined_fn",file="util.h",fullname="/src/util.h",line="12",thread-groups=["i1"]},{number="2.2",enabled="n",addr="0x08000340",func="inlined_fn",file="util.h",fullname="/src/util.h",line="12",thread-groups=["i1"]}]}"#;
+    
+    let result = parse_gdb_output(input).unwrap();
+    
+    match result {
+        GdbOutput::Result(result) => {
+            let bkpt = result.results.get("bkpt").unwrap().as_tuple().unwrap();
+            let breakpoint = Breakpoint::from_tuple(bkpt).unwrap();
+            
+            assert_eq!(breakpoint.number, "2");
+            assert_eq!(breakpoint.address.as_deref(), Some("<MULTIPLE>"));
+            assert_eq!(breakpoint.original_location.as_deref(), Some("inlined_fn"));
+            assert!(breakpoint.has_multiple_locations());
+            assert_eq!(breakpoint.locations.len(), 2);
+            
+            assert_eq!(breakpoint.locations[0].number, "2.1");
+            assert_eq!(breakpoint.locations[0].address.as_deref(), Some("0x08000124"));
+            assert_eq!(breakpoint.locations[0].line, Some(12));
+            assert_eq!(breakpoint.locations[1].number, "2.2");
+            assert_eq!(breakpoint.locations[1].enabled, "n");
+            assert_eq!(breakpoint.locations[1].address.as_deref(), Some("0x08000340"));
+        }
+        _ => panic!("Expected result record"),
+    }
+}
+
+#[test]
+fn test_parse_breakpoint_list() {
+    let input = r#"^done,BreakpointTable={nr_rows="2",nr_cols="6",hdr=[{width="7",alignment="-1",col_name="number",colhdr="Num"}],body=[bkpt={number="1",type="breakpoint",disp="keep",enabled="y",addr="0x08000100",func="main",file="main.c",fullname="/src/main.c",line="12",thread-groups=["i1"],times="0",original-location="main"},bkpt={number="2",type="hw watchpoint",disp="keep",enabled="n",what="counter",times="0"}]}"#;
+    
+    let result = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    
+    let breakpoints = Breakpoint::list_from_result(&result);
+    
+    assert_eq!(breakpoints.len(), 2);
+    assert_eq!(breakpoints[0].number, "1");
+    assert_eq!(breakpoints[0].enabled, "y");
+    assert_eq!(breakpoints[0].function.as_deref(), Some("main"));
+    assert!(!breakpoints[0].is_watchpoint());
+    assert_eq!(breakpoints[1].enabled, "n");
+    assert!(breakpoints[1].is_watchpoint());
+}
+
+#[test]
+fn test_conditional_temporary_breakpoint() {
+    let spec = BreakpointSpec {
+        condition: Some(r#"name == "idle""#.to_string()),
+        ignore_count: 3,
+        temporary: true,
+        hardware: true,
+        ..BreakpointSpec::new("main.c:42")
+    };
+    assert_eq!(spec.mi_command(), r#"break-insert -t -h -c "name == \"idle\"" -i 3 main.c:42"#);
+    assert_eq!(BreakpointSpec::new("main").mi_command(), "break-insert main");
+    
+    let input = r#"^done,bkpt={number="3",type="breakpoint",disp="del",enabled="y",addr="0x08000200",func="loop",file="main.c",line="42",cond="count > 10",times="0",ignore="3",original-location="main.c:42"}"#;
+    let breakpoint = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Result(result) => Breakpoint::from_tuple(result.results.get("bkpt").unwrap().as_tuple().unwrap()).unwrap(),
+        _ => panic!("Expected result record"),
+    };
+    
+    assert_eq!(breakpoint.condition.as_deref(), Some("count > 10"));
+    assert_eq!(breakpoint.ignore_count, 3);
+    assert!(breakpoint.is_temporary());
+    assert!(!breakpoint.is_pending());
+}
+
+#[test]
+fn test_location_spec_parsing_and_quoting() {
+    assert_eq!(LocationSpec::parse("main"), Ok(LocationSpec::Function("main".into())));
+    assert_eq!(LocationSpec::parse(" main.c:42 "), Ok(LocationSpec::FileLine("main.c".into(), 42)));
+    assert_eq!(LocationSpec::parse("main.c:loop"), Ok(LocationSpec::Function("main.c:loop".into())));
+    assert_eq!(LocationSpec::parse("*0x08000100"), Ok(LocationSpec::Address("0x08000100".into())));
+    assert_eq!(LocationSpec::parse(r"C:\src\main.c:7"), Ok(LocationSpec::FileLine(r"C:\src\main.c".into(), 7)));
+    
+    assert!(LocationSpec::parse("main.c:0").is_err());
+    assert!(LocationSpec::parse(":12").is_err());
+    assert!(LocationSpec::parse("*").is_err());
+    assert!(LocationSpec::parse("").is_err());
+    
+    let spaced = LocationSpec::FileLine("/home/me/my project/main.c".into(), 12);
+    assert_eq!(spaced.mi_argument(), r#""/home/me/my project/main.c:12""#);
+    assert_eq!(
+        BreakpointSpec { pending: true, ..BreakpointSpec::at(&spaced) }.mi_command(),
+        r#"break-insert -f "/home/me/my project/main.c:12""#
+    );
+    assert_eq!(BreakpointSpec::at(&LocationSpec::Address("0x100".into())).mi_command(), "break-insert *0x100");
+}
+
+#[test]
+fn test_parse_pending_breakpoint() {
+    let input = r#"^done,bkpt={number="2",type="breakpoint",disp="keep",enabled="y",addr="<PENDING>",pending="plugin.c:30",times="0",original-location="plugin.c:30"}"#;
+    let breakpoint = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Result(result) => Breakpoint::from_tuple(result.results.get("bkpt").unwrap().as_tuple().unwrap()).unwrap(),
+        _ => panic!("Expected result record"),
+    };
+    
+    assert!(breakpoint.is_pending());
+    assert_eq!(breakpoint.pending.as_deref(), Some("plugin.c:30"));
+    assert_eq!(breakpoint.line, None);
+}
+
+#[test]
+fn test_parse_watchpoint_results() {
+    let cases = [
+        (r#"^done,wpt={number="3",exp="counter"}"#, WatchKind::Write),
+        (r#"^done,hw-rwpt={number="4",exp="*0x20000000"}"#, WatchKind::Read),
+        (r#"^done,hw-awpt={number="5",exp="state.flags"}"#, WatchKind::Access),
+    ];
+    
+    for (input, expected_kind) in &cases {
+        match parse_gdb_output(input).unwrap() {
+            GdbOutput::Result(result) => {
+                let watchpoint = Watchpoint::from_result(&result).unwrap();
+                assert_eq!(watchpoint.kind, *expected_kind);
+                assert_eq!(watchpoint.kind.result_key(), expected_kind.result_key());
+            }
+            _ => panic!("Expected result record for input: {}", input),
+        }
+    }
+    
+    let result = match parse_gdb_output(r#"^done,hw-awpt={number="5",exp="state.flags"}"#).unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    let watchpoint = Watchpoint::from_result(&result).unwrap();
+    assert_eq!(watchpoint.number, "5");
+    assert_eq!(watchpoint.expression, "state.flags");
+}
+
 #[test]
 fn test_parse_stopped_with_frame_info() {
     let input = r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="1",thread-id="0",frame={addr="0x08048564",func="main",args=[{name="argc",value="1"},{name="argv",value="0xbfc4d4d4"}],file="myprog.c",fullname="/home/user/myprog.c",line="68",arch="i386:x86_64"}"#;
@@ -79,6 +243,250 @@ fn test_parse_stopped_with_frame_info() {
     }
 }
 
+#[test]
+fn test_stop_info_from_breakpoint_hit() {
+    let input = r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="1",thread-id="1",frame={addr="0x08000100",func="main",args=[{name="argc",value="1"}],file="main.c",fullname="/src/main.c",line="12",arch="armv7"},stopped-threads="all""#;
+    
+    let record = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Async(record) => record,
+        _ => panic!("Expected async record"),
+    };
+    
+    let info = StopInfo::from_record(&record).unwrap();
+    assert_eq!(info.reason, Some(StopReason::BreakpointHit));
+    assert_eq!(info.breakpoint_number.as_deref(), Some("1"));
+    assert_eq!(info.thread_id.as_deref(), Some("1"));
+    
+    let frame = info.frame.unwrap();
+    assert_eq!(frame.address, "0x08000100");
+    assert_eq!(frame.function.as_deref(), Some("main"));
+    assert_eq!(frame.file.as_deref(), Some("main.c"));
+    assert_eq!(frame.line, Some(12));
+    assert_eq!(frame.args, vec![Argument { name: "argc".into(), value: "1".into() }]);
+}
+
+#[test]
+fn test_stop_info_exit_code_is_octal() {
+    let record = match parse_gdb_output(r#"*stopped,reason="exited",exit-code="012""#).unwrap() {
+        GdbOutput::Async(record) => record,
+        _ => panic!("Expected async record"),
+    };
+    
+    let info = StopInfo::from_record(&record).unwrap();
+    
+    assert_eq!(info.reason, Some(StopReason::Exited));
+    assert_eq!(info.exit_code, Some(10));
+    assert!(info.frame.is_none());
+}
+
+#[test]
+fn test_stop_info_from_signal_received() {
+    let input = r#"*stopped,reason="signal-received",signal-name="SIGSEGV",signal-meaning="Segmentation fault",frame={addr="0x08000124",func="fault",args=[],arch="armv7"},thread-id="1",stopped-threads="all""#;
+    let record = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Async(record) => record,
+        _ => panic!("Expected async record"),
+    };
+    
+    let info = StopInfo::from_record(&record).unwrap();
+    
+    assert_eq!(info.reason, Some(StopReason::SignalReceived));
+    assert_eq!(info.signal_name.as_deref(), Some("SIGSEGV"));
+    assert_eq!(info.signal_meaning.as_deref(), Some("Segmentation fault"));
+    assert_eq!(info.frame.unwrap().address, "0x08000124");
+}
+
+#[test]
+fn test_error_results_map_to_typed_errors() {
+    let error = |input: &str| match parse_gdb_output(input).unwrap() {
+        GdbOutput::Result(result) => GdbError::from_error_result(&result),
+        _ => panic!("Expected result record"),
+    };
+    
+    assert!(matches!(
+        error(r#"^error,msg="Undefined MI command: frobnicate",code="undefined-command""#),
+        GdbError::UnknownCommand(msg) if msg == "Undefined MI command: frobnicate"
+    ));
+    assert!(matches!(error(r#"^error,msg="No symbol table is loaded.  Use the \"file\" command.""#), GdbError::NoSymbols(_)));
+    assert!(matches!(error(r#"^error,msg="The program is not being run.""#), GdbError::NotRunning(_)));
+    assert!(matches!(error(r#"^error,msg="No registers.""#), GdbError::NotRunning(_)));
+    assert!(matches!(error(r#"^error,msg="No symbol \"foo\" in current context.""#), GdbError::CommandError(_)));
+}
+
+#[test]
+fn test_parse_stack_arguments() {
+    let input = r#"^done,stack-args=[{level="0",args=[{name="argc",value="1"}]},frame={level="1",args=[]}]"#;
+    let result = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    
+    let arguments = StackFrame::arguments_from_result(&result);
+    
+    assert_eq!(arguments, vec![
+        (0, vec![Argument { name: "argc".into(), value: "1".into() }]),
+        (1, Vec::new()),
+    ]);
+}
+
+#[test]
+fn test_parse_stack_frame_list() {
+    let input = r#"^done,stack=[frame={level="0",addr="0x08000124",func="loop",file="main.c",fullname="/src/main.c",line="30",arch="armv7e-m"},frame={level="1",addr="0x08000200",func="main"}]"#;
+    let result = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    
+    let frames = StackFrame::list_from_result(&result).unwrap();
+    
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].function.as_deref(), Some("loop"));
+    assert_eq!(frames[0].line, Some(30));
+    assert_eq!(frames[0].arch.as_deref(), Some("armv7e-m"));
+    assert_eq!(frames[1].level, 1);
+    assert_eq!(frames[1].file, None);
+    assert_eq!(frames[1].fullname, None);
+}
+
+#[test]
+fn test_stack_frame_list_reports_bad_frames() {
+    let result = match parse_gdb_output(r#"^done,stack=[frame={level="0",addr="0x0"},frame={addr="0x0"}]"#).unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    let err = StackFrame::list_from_result(&result).unwrap_err();
+    assert!(err.contains("Frame 1") && err.contains("level"), "{}", err);
+    
+    let result = match parse_gdb_output("^done").unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    assert!(StackFrame::list_from_result(&result).is_err());
+}
+
+#[test]
+fn test_frames_with_and_without_details() {
+    let frame_value = |input: &str| match parse_gdb_output(input).unwrap() {
+        GdbOutput::Result(result) => result.results.get("frame").cloned().unwrap(),
+        _ => panic!("Expected result record"),
+    };
+    
+    let frame = Frame::from_value(&frame_value(r#"^done,frame={addr="0x08048564",func="main",args=[{name="argc",value="1"}],file="myprog.c",line="68"}"#)).unwrap();
+    assert_eq!(frame.args, vec![Argument { name: "argc".into(), value: "1".into() }]);
+    assert_eq!(frame.file.as_deref(), Some("myprog.c"));
+    assert_eq!(frame.line, Some(68));
+    
+    let frame = Frame::from_value(&frame_value(r#"^done,frame={addr="0x08000100"}"#)).unwrap();
+    assert!(frame.args.is_empty());
+    assert_eq!((frame.function, frame.file, frame.line), (None, None, None));
+    
+    let err = Frame::from_value(&frame_value(r#"^done,frame={func="main"}"#)).unwrap_err();
+    assert!(err.contains("addr"), "{}", err);
+    assert!(Frame::from_value(&Value::String("main".into())).is_err());
+}
+
+#[test]
+fn test_parse_changed_registers() {
+    let result = match parse_gdb_output(r#"^done,changed-registers=["0","13","15"]"#).unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    
+    assert_eq!(Register::changed_numbers(&result), vec![0, 13, 15]);
+}
+
+#[test]
+fn test_stop_info_ignores_other_async_records() {
+    let record = match parse_gdb_output(r#"*running,thread-id="all""#).unwrap() {
+        GdbOutput::Async(record) => record,
+        _ => panic!("Expected async record"),
+    };
+    
+    assert!(StopInfo::from_record(&record).is_none());
+}
+
+#[test]
+fn test_parse_thread_info() {
+    let input = r#"^done,threads=[{id="2",target-id="Thread 0x7ffff7d8a640 (LWP 4102)",name="worker",frame={level="0",addr="0x0000555555555189",func="work",args=[{name="arg",value="0x0"}],file="main.c",fullname="/src/main.c",line="8",arch="i386:x86-64"},state="stopped",core="1"},{id="1",target-id="Thread 0x7ffff7d8b740 (LWP 4101)",state="running"}],current-thread-id="2""#;
+    
+    let result = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    
+    let list = ThreadList::from_result(&result);
+    
+    assert_eq!(list.current_thread_id, Some(2));
+    assert_eq!(list.threads.len(), 2);
+    
+    let worker = &list.threads[0];
+    assert_eq!(worker.id, 2);
+    assert_eq!(worker.name.as_deref(), Some("worker"));
+    assert_eq!(worker.state, "stopped");
+    let frame = worker.frame.as_ref().unwrap();
+    assert_eq!(frame.function.as_deref(), Some("work"));
+    assert_eq!(frame.line, Some(8));
+    assert_eq!(frame.args[0].name, "arg");
+    
+    let main = &list.threads[1];
+    assert_eq!(main.target_id, "Thread 0x7ffff7d8b740 (LWP 4101)");
+    assert_eq!(main.state, "running");
+    assert!(main.frame.is_none());
+}
+
+#[test]
+fn test_vector_views_x86() {
+    let register = Register {
+        number: 40,
+        name: "xmm0".to_string(),
+        value: "{v8_bfloat16 = {0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0}, v4_float = {0x1, 0x0, 0x0, 0x0}, v2_double = {0x0, 0x0}, v16_int8 = {0x1, 0x0 <repeats 15 times>}, v4_int32 = {0x1, 0x2, 0xffffffff, 0x0}, uint128 = 0x1}".to_string(),
+    };
+    
+    let views = register.vector_views().unwrap();
+    
+    let view = |name: &str| views.iter().find(|view| view.name == name).unwrap();
+    assert_eq!(view("v4_int32").lanes, ["0x1", "0x2", "0xffffffff", "0x0"]);
+    assert_eq!(view("v16_int8").lanes.len(), 16);
+    assert_eq!(view("v16_int8").lanes[1], "0x0");
+    assert_eq!(view("uint128").lanes, ["0x1"]);
+}
+
+#[test]
+fn test_vector_views_neon_nested_union() {
+    let value = "{d = {f = {0x0, 0x0}, u = {0x1, 0x2}, s = {0x1, 0x2}}, s = {f = {0x0, 0x0, 0x0, 0x0}, u = {0x1, 0x0, 0x2, 0x0}, s = {0x1, 0x0, 0x2, 0x0}}, q = {u = {0x20000000000000001}, s = {0x20000000000000001}}}";
+    
+    let views = VectorView::parse(value).unwrap();
+    
+    assert_eq!(views.len(), 8);
+    assert_eq!(views[1].name, "d.u");
+    assert_eq!(views[1].lanes, ["0x1", "0x2"]);
+    assert_eq!(views[6].name, "q.u");
+}
+
+#[test]
+fn test_structured_register_value_round_trips_to_vector_views() {
+    let result = match parse_gdb_output(r#"^done,register-values=[{number="17",value={u8=["0x1","0x0"],u64="0x1",f64="4.9e-324"}}]"#).unwrap() {
+        GdbOutput::Result(result) => result,
+        _ => panic!("Expected result record"),
+    };
+    let entry = result.results.get("register-values").unwrap().as_list().unwrap()[0].as_tuple().unwrap();
+    let value = entry.get("value").unwrap();
+    assert!(matches!(value, Value::Tuple(_)));
+    
+    let register = Register { number: 17, name: "d0".to_string(), value: value.to_string() };
+    
+    assert_eq!(register.value, "{f64 = 4.9e-324, u64 = 0x1, u8 = {0x1, 0x0}}");
+    let views = register.vector_views().unwrap();
+    assert_eq!(views.iter().map(|view| view.name.as_str()).collect::<Vec<_>>(), ["f64", "u64", "u8"]);
+    assert_eq!(views[2].lanes, ["0x1", "0x0"]);
+}
+
+#[test]
+fn test_vector_views_scalar_register() {
+    assert!(VectorView::parse("0x08000124").is_none());
+    assert!(VectorView::parse("{0x1, 0x2}").is_none());
+    assert!(VectorView::parse("{v4_int32 = {0x1").is_none());
+}
+
 #[test]
 fn test_parse_error_with_message() {
     let input = r#"^error,msg="No symbol table is loaded.  Use the \"file\" command.",code="undefined-command""#;
@@ -103,6 +511,13 @@ fn test_parse_error_with_message() {
     }
 }
 
+#[test]
+fn test_parse_prompt() {
+    assert_eq!(parse_gdb_output("(gdb)"), Ok(GdbOutput::Prompt));
+    assert_eq!(parse_gdb_output("(gdb) "), Ok(GdbOutput::Prompt));
+    assert!(parse_gdb_output("").is_err());
+}
+
 #[test]
 fn test_parse_thread_group_notifications() {
     let inputs = [
@@ -148,3 +563,289 @@ fn test_stop_reason_parsing() {
         assert_eq!(parsed.to_string(), *reason_str);
     }
 }
+
+#[test]
+fn test_value_numeric_accessors() {
+    let value = |s: &str| Value::String(s.to_string());
+
+    assert_eq!(value("42").as_u64(), Some(42));
+    assert_eq!(value("42").as_u32(), Some(42));
+    assert_eq!(value("-7").as_i64(), Some(-7));
+    assert_eq!(value("-7").as_u64(), None);
+    assert_eq!(value("4294967296").as_u32(), None);
+    assert_eq!(value("0x1f").as_u64(), None);
+
+    assert_eq!(value("0x08000124").as_address(), Some(0x08000124));
+    assert_eq!(value("0XFFFF").as_address(), Some(0xffff));
+    assert_eq!(value("1234").as_address(), None);
+    assert_eq!(value("0xzz").as_address(), None);
+
+    assert_eq!(value("").as_u64(), None);
+    assert_eq!(Value::List(vec![]).as_u64(), None);
+    assert_eq!(Value::Tuple(Default::default()).as_address(), None);
+    // The same parser reads addresses held as plain strings
+    assert_eq!(parse_address(" 0x20000000 "), Some(0x20000000));
+    assert_eq!(parse_address("20000000"), None);
+}
+
+#[test]
+fn test_get_path_walks_nested_tuples() {
+    let input = r#"*stopped,reason="breakpoint-hit",frame={addr="0x08000124",func="main",args=[]},thread-id="1""#;
+    let record = match parse_gdb_output(input).unwrap() {
+        GdbOutput::Async(record) => record,
+        _ => panic!("Expected async record"),
+    };
+
+    assert_eq!(record.get_path(&["frame", "addr"]).and_then(Value::as_address), Some(0x08000124));
+    assert_eq!(record.get_path(&["reason"]).and_then(Value::as_string), Some("breakpoint-hit"));
+
+    // Missing keys, and keys below a non-tuple, both miss
+    assert!(record.get_path(&["frame", "line"]).is_none());
+    assert!(record.get_path(&["thread-id", "id"]).is_none());
+    assert!(record.get_path(&[]).is_none());
+
+    let frame = record.get_path(&["frame"]).unwrap();
+    assert_eq!(frame.get_path(&[]), Some(frame));
+}
+
+#[tokio::test]
+async fn test_breakpoint_continue_and_stop_against_mock_gdb() {
+    let gdb = mock::MockGdb::new()
+        .expect("break-insert main", &[
+            r#"{token}^done,bkpt={number="1",type="breakpoint",disp="keep",enabled="y",addr="0x00401136",func="main",file="main.c",line="5",times="0"}"#,
+        ])
+        .expect("exec-continue", &[
+            "{token}^running",
+            r#"*running,thread-id="all""#,
+            r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="1",frame={addr="0x00401136",func="main",args=[],file="main.c",line="5"},thread-id="1",stopped-threads="all""#,
+        ]);
+    let (mut adapter, mut events) = GdbAdapter::new();
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    let result = adapter.set_breakpoint("main").await.unwrap();
+    let breakpoint = Breakpoint::from_tuple(result.results["bkpt"].as_tuple().unwrap()).unwrap();
+    assert_eq!(breakpoint.number, "1");
+    assert_eq!(breakpoint.line, Some(5));
+
+    let result = adapter.continue_execution().await.unwrap();
+    assert_eq!(result.class, ResultClass::Running);
+
+    // The stop arrives as an event once the reply has been routed to the command
+    let stop = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            if let Ok(GdbEvent::Async(record)) = events.recv().await {
+                if let Some(stop) = StopInfo::from_record(&record) {
+                    return stop;
+                }
+            }
+        }
+    }).await.unwrap();
+    assert_eq!(stop.reason, Some(StopReason::BreakpointHit));
+    assert_eq!(stop.breakpoint_number.as_deref(), Some("1"));
+    assert_eq!(stop.frame.unwrap().function.as_deref(), Some("main"));
+
+    adapter.stop_session().await.unwrap();
+    assert!(gdb.remaining().is_empty());
+    assert_eq!(gdb.unscripted(), DEFAULT_STARTUP_COMMANDS.iter().map(|c| c.to_string()).chain(["gdb-exit".to_string()]).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_variable_objects_expand_and_update() {
+    let gdb = mock::MockGdb::new()
+        .expect("var-create", &[r#"{token}^done,name="var1",numchild="2",value="{...}",type="struct point",thread-id="1",has_more="0""#])
+        .expect("var-list-children", &[
+            r#"{token}^done,numchild="2",children=[child={name="var1.x",exp="x",numchild="0",value="1",type="int",thread-id="1"},child={name="var1.y",exp="y",numchild="0",value="2",type="int",thread-id="1"}],has_more="0""#,
+        ])
+        .expect("var-create", &[r#"{token}^done,name="var2",numchild="0",value="std::vector of length 3",type="std::vector<int>",thread-id="1",displayhint="array",dynamic="1",has_more="1""#])
+        .expect("var-list-children", &[
+            r#"{token}^done,numchild="2",displayhint="array",children=[child={name="var2.[0]",exp="[0]",numchild="0",value="7",type="int"},child={name="var2.[1]",exp="[1]",numchild="0",value="8",type="int"}],has_more="1""#,
+        ])
+        .expect("var-update", &[
+            r#"{token}^done,changelist=[{name="var1.x",value="5",in_scope="true",type_changed="false",has_more="0"},{name="var2",value="std::vector of length 4",in_scope="true",type_changed="false",new_num_children="3",displayhint="array",dynamic="1",has_more="1",new_children=[{name="var2.[2]",exp="[2]",numchild="0",value="9",type="int"}]},{name="var3",in_scope="false",type_changed="false",has_more="0"}]"#,
+        ])
+        .expect("var-delete", &[r#"{token}^done,ndeleted="3""#]);
+    let (mut adapter, _events) = GdbAdapter::new();
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    let point = adapter.create_varobj("origin").await.unwrap();
+    assert_eq!(point.name, "var1");
+    assert_eq!(point.expression, "origin");
+    assert_eq!(point.type_name.as_deref(), Some("struct point"));
+    assert!(point.has_children() && !point.dynamic);
+    let members = adapter.list_children("var1", None).await.unwrap();
+    assert_eq!(members.children.iter().map(|c| (c.expression.as_str(), c.value.as_deref())).collect::<Vec<_>>(), [("x", Some("1")), ("y", Some("2"))]);
+    assert!(!members.has_more);
+
+    // Pretty-printed containers report a lower bound and page their elements
+    let vector = adapter.create_varobj("values").await.unwrap();
+    assert!(vector.dynamic && vector.has_more && vector.has_children());
+    assert_eq!(vector.display_hint.as_deref(), Some("array"));
+    let elements = adapter.list_children("var2", Some((0, 2))).await.unwrap();
+    assert_eq!(elements.children[1].name, "var2.[1]");
+    assert!(elements.has_more);
+
+    let changes = adapter.update_varobjs().await.unwrap();
+    assert_eq!(changes.len(), 3);
+    assert_eq!(changes[0].value.as_deref(), Some("5"));
+    assert_eq!(changes[1].new_num_children, Some(3));
+    assert_eq!(changes[1].new_children[0].expression, "[2]");
+    assert!(!changes[2].is_in_scope());
+
+    adapter.delete_varobj("var1").await.unwrap();
+    assert!(gdb.remaining().is_empty());
+    let received = gdb.received();
+    assert!(received.contains(&r#"var-create - @ "origin""#.to_string()));
+    assert!(received.contains(&"var-list-children --all-values var2 0 2".to_string()));
+    assert!(received.contains(&"var-update --all-values *".to_string()));
+}
+
+#[tokio::test]
+async fn test_mi_log_records_both_directions() {
+    let path = std::env::temp_dir().join(format!("katori-mi-log-{}.txt", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let gdb = mock::MockGdb::new()
+        .expect("data-evaluate-expression", &[r#"{token}^done,value="42""#]);
+    let (mut adapter, _events) = GdbAdapter::new();
+    adapter.set_mi_log(&path).unwrap();
+    assert!(adapter.is_logging_mi());
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    adapter.evaluate_expression("answer").await.unwrap();
+    adapter.stop_session().await.unwrap();
+
+    // Stopping the session flushed the log
+    let log = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let sent = log.lines().find(|line| line.contains("> ") && line.ends_with(r#"-data-evaluate-expression "answer""#)).unwrap();
+    let token = sent.split_once("> ").unwrap().1.split('-').next().unwrap();
+    let timestamp: f64 = sent.split(' ').next().unwrap().parse().unwrap();
+    assert!(timestamp > 0.0);
+    assert!(log.lines().any(|line| line.ends_with(&format!(r#"< {token}^done,value="42""#))));
+    assert!(log.lines().any(|line| line.ends_with("< (gdb)")));
+    assert!(log.lines().any(|line| line.ends_with("-gdb-exit")));
+}
+
+#[tokio::test]
+async fn test_megabyte_record_is_read_whole() {
+    // Reading 512 KiB of memory comes back as a single 1 MiB line of hex
+    let contents = "ab".repeat(512 * 1024);
+    let reply = format!(r#"{{token}}^done,memory=[{{begin="0x20000000",offset="0x0",end="0x20080000",contents="{contents}"}}]"#);
+    let gdb = mock::MockGdb::new().expect("data-read-memory-bytes", &[&reply]);
+    let (mut adapter, _events) = GdbAdapter::new();
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    let result = adapter.send_command("data-read-memory-bytes 0x20000000 524288").await.unwrap();
+    let memory = result.results["memory"].as_list().unwrap();
+    let block = memory[0].as_tuple().unwrap();
+    assert_eq!(block["contents"].as_string().map(str::len), Some(contents.len()));
+    assert!(adapter.is_running());
+    adapter.stop_session().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_register_subset_lists_only_requested_numbers() {
+    let gdb = mock::MockGdb::new()
+        .expect("data-list-register-values", &[r#"{token}^done,register-values=[{number="0",value="0x1"},{number="15",value="0x8000"}]"#])
+        .expect("data-list-register-values", &[r#"{token}^done,register-values=[{number="13",value="0x20001000"}]"#])
+        .expect("data-list-register-values", &[r#"{token}^done,register-values=[{number="0",value="0x1"}]"#]);
+    let (mut adapter, _events) = GdbAdapter::new();
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    let result = adapter.get_registers_subset(&[0, 15], RegisterFormat::Hex).await.unwrap();
+    let numbers: Vec<u32> = result.results["register-values"].as_list().unwrap().iter()
+        .filter_map(|reg| reg.as_tuple()?.get("number")?.as_u32())
+        .collect();
+    assert_eq!(numbers, [0, 15]);
+
+    // The subset takes precedence over the register set, until the set changes
+    adapter.set_register_subset(Some(vec![13]));
+    assert_eq!(adapter.register_subset(), Some(&[13][..]));
+    adapter.get_registers().await.unwrap();
+    adapter.set_register_set(RegisterSet::All);
+    assert_eq!(adapter.register_subset(), None);
+    adapter.get_registers_subset(&[], RegisterFormat::Natural).await.unwrap();
+
+    let received = gdb.received();
+    assert!(received.contains(&"data-list-register-values x 0 15".to_string()));
+    assert!(received.contains(&"data-list-register-values x 13".to_string()));
+    assert!(received.contains(&"data-list-register-values N".to_string()));
+    adapter.stop_session().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_watchpoint_expressions_are_quoted() {
+    let gdb = mock::MockGdb::new();
+    let (mut adapter, _events) = GdbAdapter::new();
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    adapter.set_watchpoint("table[i] + 1", WatchKind::Write).await.unwrap();
+    adapter.set_watchpoint("*(char *) \"buf\"", WatchKind::Read).await.unwrap();
+
+    let received = gdb.received();
+    assert!(received.contains(&r#"break-watch "table[i] + 1""#.to_string()), "{received:?}");
+    assert!(received.contains(&r#"break-watch -r "*(char *) \"buf\"""#.to_string()), "{received:?}");
+    adapter.stop_session().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_disassembled_function_names_are_quoted() {
+    let gdb = mock::MockGdb::new();
+    let (mut adapter, _events) = GdbAdapter::new();
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    adapter.disassemble_function("ns::Widget::draw(int)").await.unwrap();
+
+    assert!(gdb.received().contains(&r#"data-disassemble -a "ns::Widget::draw(int)" -- 0"#.to_string()));
+    adapter.stop_session().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_settings_made_before_the_session_apply_at_start() {
+    let gdb = mock::MockGdb::new();
+    let (mut adapter, _events) = GdbAdapter::new();
+    adapter.set_architecture("armv7e-m").await.unwrap();
+    adapter.set_disassembly_flavor("intel").await.unwrap();
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    let received = gdb.received();
+    assert!(received.contains(&"gdb-set architecture armv7e-m".to_string()));
+    assert!(received.contains(&"gdb-set disassembly-flavor intel".to_string()));
+    adapter.stop_session().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_target_architecture_falls_back_to_show_architecture() {
+    let gdb = mock::MockGdb::new()
+        .expect("stack-list-frames", &[r#"{token}^error,msg="No stack.""#])
+        .expect("show architecture", &[
+            r#"~"The target architecture is set to \"auto\" (currently \"i386:x86-64\").\n""#,
+            "{token}^done",
+        ])
+        .expect("stack-list-frames", &[
+            r#"{token}^done,stack=[frame={level="0",addr="0x08000130",func="main",file="main.c",line="5",arch="armv7e-m"}]"#,
+        ]);
+    let (mut adapter, mut events) = GdbAdapter::new();
+    let (output, input) = gdb.serve();
+    adapter.start_session_with_io(output, input).await.unwrap();
+
+    assert_eq!(adapter.target_architecture().await.as_deref(), Some("i386:x86-64"));
+    // The lookup's console output isn't shown to the user
+    while let Ok(event) = events.try_recv() {
+        assert!(!matches!(event, GdbEvent::Stream(_)), "{event:?}");
+    }
+    // Remembered until another target is attached
+    assert_eq!(adapter.target_architecture().await.as_deref(), Some("i386:x86-64"));
+    adapter.attach_to_gdbserver("localhost:3333").await.unwrap();
+    assert_eq!(adapter.target_architecture().await.as_deref(), Some("armv7e-m"));
+
+    assert!(gdb.remaining().is_empty());
+    adapter.stop_session().await.unwrap();
+}
@@ -0,0 +1,62 @@
+//! Katori as a Debug Adapter Protocol server over stdio
+//!
+//! Lets editors such as VS Code drive the GDB adapter: requests arrive on
+//! stdin, and responses and events leave on stdout, so nothing else may be
+//! printed there.
+
+mod protocol;
+mod server;
+
+use gdbadapter::GdbAdapter;
+use serde_json::Value;
+use tokio::io::BufReader;
+use tokio::sync::mpsc;
+
+use protocol::{read_message, write_message, Request};
+use server::{forward_events, DapServer};
+
+#[tokio::main]
+async fn main() {
+    let (adapter, gdb_events) = GdbAdapter::new();
+    let (output, mut outgoing) = mpsc::unbounded_channel::<Value>();
+
+    // One writer numbers and frames every response and event
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        let mut seq = 1;
+        while let Some(mut message) = outgoing.recv().await {
+            message["seq"] = seq.into();
+            seq += 1;
+            if let Err(e) = write_message(&mut stdout, &message).await {
+                log::error!("main: Failed to write message: {}", e);
+                break;
+            }
+        }
+    });
+    tokio::spawn(forward_events(gdb_events, output.clone()));
+
+    let mut server = DapServer::new(adapter, output);
+    let mut stdin = BufReader::new(tokio::io::stdin());
+    loop {
+        let message = match read_message(&mut stdin).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                log::error!("main: Failed to read message: {}", e);
+                break;
+            }
+        };
+        match serde_json::from_value::<Request>(message) {
+            Ok(request) => {
+                if !server.handle(request).await {
+                    break;
+                }
+            }
+            Err(e) => log::warn!("main: Ignoring message that is not a request: {}", e),
+        }
+    }
+
+    // Dropping the server drops the adapter, which kills GDB; then flush what's queued
+    drop(server);
+    let _ = writer.await;
+}
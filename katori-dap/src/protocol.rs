@@ -0,0 +1,117 @@
+//! Debug Adapter Protocol framing and messages
+//!
+//! Every message is a JSON object preceded by a `Content-Length` header and a
+//! blank line. Requests come from the editor; responses and events are built
+//! here without a `seq`, which the writer assigns as it sends them.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// A request from the client
+#[derive(Debug, Clone, Deserialize)]
+pub struct Request {
+    pub seq: i64,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// Read one message body; `None` once the input is closed
+pub async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> std::io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            // Stray blank lines between messages are tolerated
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or_default()];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Write one message with its header
+pub async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &Value) -> std::io::Result<()> {
+    let body = message.to_string();
+    writer.write_all(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes()).await?;
+    writer.flush().await
+}
+
+/// Response to `request`: the body on success, the error message otherwise
+pub fn response(request: &Request, result: Result<Value, String>) -> Value {
+    let mut response = json!({
+        "type": "response",
+        "request_seq": request.seq,
+        "command": request.command,
+        "success": result.is_ok(),
+    });
+    match result {
+        Ok(Value::Null) => {}
+        Ok(body) => response["body"] = body,
+        Err(message) => response["message"] = Value::String(message),
+    }
+    response
+}
+
+pub fn event(name: &str, body: Value) -> Value {
+    let mut event = json!({ "type": "event", "event": name });
+    if !body.is_null() {
+        event["body"] = body;
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_messages_round_trip() {
+        let mut framed = Vec::new();
+        write_message(&mut framed, &json!({"seq": 1, "type": "request", "command": "threads"})).await.unwrap();
+        write_message(&mut framed, &json!({"seq": 2, "type": "request", "command": "next", "arguments": {"threadId": 1}})).await.unwrap();
+        assert!(framed.starts_with(b"Content-Length: 46\r\n\r\n{"));
+
+        let mut reader = framed.as_slice();
+        let first: Request = serde_json::from_value(read_message(&mut reader).await.unwrap().unwrap()).unwrap();
+        let second: Request = serde_json::from_value(read_message(&mut reader).await.unwrap().unwrap()).unwrap();
+
+        assert_eq!(first.command, "threads");
+        assert!(first.arguments.is_null());
+        assert_eq!(second.seq, 2);
+        assert_eq!(second.arguments["threadId"], 1);
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_response_carries_body_or_message() {
+        let request = Request { seq: 7, command: "evaluate".to_string(), arguments: Value::Null };
+
+        let ok = response(&request, Ok(json!({"result": "42"})));
+        assert_eq!(ok["request_seq"], 7);
+        assert_eq!(ok["success"], true);
+        assert_eq!(ok["body"]["result"], "42");
+
+        let failed = response(&request, Err("No symbol \"x\" in current context.".to_string()));
+        assert_eq!(failed["success"], false);
+        assert!(failed.get("body").is_none());
+        assert_eq!(failed["message"], "No symbol \"x\" in current context.");
+    }
+}
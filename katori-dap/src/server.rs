@@ -0,0 +1,478 @@
+//! Translation between DAP requests and `GdbAdapter` calls
+//!
+//! Requests are handled one at a time against the adapter. GDB's async
+//! records reach the client separately, as `stopped`/`continued`/`output`
+//! events, from `forward_events`. MI async mode is turned on at the start of
+//! every session so GDB keeps answering (threads, pause) while the program runs.
+
+use std::collections::HashMap;
+
+use gdbadapter::{
+    Argument, AsyncClass, AttachTarget, Breakpoint, BreakpointSpec, GdbAdapter, GdbEvent, GdbResult,
+    LocationSpec, StackFrame, StopInfo, StopReason, StreamType, ThreadList,
+};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::protocol::{event, response, Request};
+
+/// Frame ids pack the thread id above the frame level
+const FRAME_LEVELS: i64 = 1 << 16;
+
+fn frame_id(thread: u32, level: u32) -> i64 {
+    i64::from(thread) * FRAME_LEVELS + i64::from(level)
+}
+
+/// Thread and frame level of a frame id
+fn split_frame_id(id: i64) -> Option<(u32, u32)> {
+    Some((u32::try_from(id / FRAME_LEVELS).ok()?, u32::try_from(id % FRAME_LEVELS).ok()?))
+}
+
+/// A DAP `StackFrame` for a GDB frame of `thread`
+pub fn dap_stack_frame(thread: u32, frame: &StackFrame) -> Value {
+    let mut dap_frame = json!({
+        "id": frame_id(thread, frame.level),
+        "name": frame.function.clone().unwrap_or_else(|| frame.address.clone()),
+        "line": frame.line.unwrap_or(0),
+        "column": 0,
+        "instructionPointerReference": frame.address,
+    });
+    if let Some(path) = frame.fullname.as_ref().or(frame.file.as_ref()) {
+        dap_frame["source"] = json!({ "name": frame.file.clone().unwrap_or_else(|| path.clone()), "path": path });
+    }
+    dap_frame
+}
+
+/// A DAP `Breakpoint` for a breakpoint GDB inserted
+pub fn dap_breakpoint(breakpoint: &Breakpoint) -> Value {
+    let mut dap_breakpoint = json!({
+        "id": breakpoint.number.parse::<i64>().ok(),
+        "verified": !breakpoint.is_pending(),
+        "line": breakpoint.line,
+        "instructionReference": breakpoint.address,
+    });
+    if let Some(path) = breakpoint.fullname.as_ref().or(breakpoint.file.as_ref()) {
+        dap_breakpoint["source"] = json!({ "path": path });
+    }
+    dap_breakpoint
+}
+
+/// DAP stop reason for a `*stopped` record
+fn stop_reason(info: &StopInfo) -> &'static str {
+    match info.reason {
+        Some(StopReason::BreakpointHit) => "breakpoint",
+        Some(StopReason::WatchpointTrigger | StopReason::ReadWatchpointTrigger | StopReason::AccessWatchpointTrigger) => "data breakpoint",
+        Some(StopReason::EndSteppingRange | StopReason::FunctionFinished | StopReason::LocationReached) => "step",
+        // An interrupt shows up as SIGINT
+        Some(StopReason::SignalReceived) if info.signal_name.as_deref() != Some("SIGINT") => "exception",
+        _ => "pause",
+    }
+}
+
+/// Events the client should see for a GDB event
+pub fn dap_events(gdb_event: &GdbEvent) -> Vec<Value> {
+    match gdb_event {
+        GdbEvent::Async(record) if record.class == AsyncClass::Stopped => {
+            let Some(info) = StopInfo::from_record(record) else {
+                return Vec::new();
+            };
+            match info.reason {
+                Some(StopReason::Exited | StopReason::ExitedNormally | StopReason::ExitedSignalled) => vec![
+                    event("exited", json!({ "exitCode": info.exit_code.unwrap_or(0) })),
+                    event("terminated", Value::Null),
+                ],
+                _ => {
+                    let mut body = json!({
+                        "reason": stop_reason(&info),
+                        "threadId": info.thread_id.as_deref().and_then(|id| id.parse::<u32>().ok()),
+                        "allThreadsStopped": true,
+                    });
+                    if let Some(signal) = &info.signal_name {
+                        body["description"] = Value::String(info.signal_meaning.clone().unwrap_or_else(|| signal.clone()));
+                    }
+                    vec![event("stopped", body)]
+                }
+            }
+        }
+        GdbEvent::Async(record) if record.class == AsyncClass::Running => {
+            // Continue responses already report `thread-id="all"`
+            record.results.get("thread-id")
+                .and_then(|id| id.as_string())
+                .and_then(|id| id.parse::<u32>().ok())
+                .map(|thread| event("continued", json!({ "threadId": thread })))
+                .into_iter()
+                .collect()
+        }
+        // Pending breakpoints get their address once the code is loaded
+        GdbEvent::Async(record) if record.class == AsyncClass::BreakpointModified => record.results.get("bkpt")
+            .and_then(|bkpt| bkpt.as_tuple())
+            .and_then(Breakpoint::from_tuple)
+            .map(|breakpoint| event("breakpoint", json!({ "reason": "changed", "breakpoint": dap_breakpoint(&breakpoint) })))
+            .into_iter()
+            .collect(),
+        GdbEvent::Stream(stream) => {
+            let category = if stream.stream_type == StreamType::Target { "stdout" } else { "console" };
+            vec![event("output", json!({ "category": category, "output": stream.content }))]
+        }
+        GdbEvent::ConnectionLost => vec![event("terminated", Value::Null)],
+        _ => Vec::new(),
+    }
+}
+
+/// Send the client every GDB event until the adapter goes away
+pub async fn forward_events(mut events: broadcast::Receiver<GdbEvent>, output: mpsc::UnboundedSender<Value>) {
+    loop {
+        match events.recv().await {
+            Ok(gdb_event) => {
+                for dap_event in dap_events(&gdb_event) {
+                    if output.send(dap_event).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log::warn!("forward_events: Skipped {} GDB events", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+pub struct DapServer {
+    adapter: GdbAdapter,
+    output: mpsc::UnboundedSender<Value>,
+    /// Breakpoint numbers set for each source path, replaced by every `setBreakpoints`
+    source_breakpoints: HashMap<String, Vec<u32>>,
+    /// `launch` loaded the program; it runs once configuration is done
+    launched: bool,
+}
+
+impl DapServer {
+    pub fn new(adapter: GdbAdapter, output: mpsc::UnboundedSender<Value>) -> Self {
+        DapServer { adapter, output, source_breakpoints: HashMap::new(), launched: false }
+    }
+
+    /// Handle one request and send its response; returns `false` after `disconnect`
+    pub async fn handle(&mut self, request: Request) -> bool {
+        log::debug!("handle: {} {}", request.command, request.arguments);
+        let result = self.dispatch(&request).await;
+        let succeeded = result.is_ok();
+        let _ = self.output.send(response(&request, result));
+
+        match request.command.as_str() {
+            // Breakpoints can only be set once GDB runs
+            "launch" | "attach" if succeeded => {
+                let _ = self.output.send(event("initialized", Value::Null));
+            }
+            "disconnect" => return false,
+            _ => {}
+        }
+        true
+    }
+
+    async fn dispatch(&mut self, request: &Request) -> Result<Value, String> {
+        let args = &request.arguments;
+        match request.command.as_str() {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsEvaluateForHovers": true,
+                "supportsConditionalBreakpoints": true,
+            })),
+            "launch" => self.launch(args).await,
+            "attach" => self.attach(args).await,
+            "setBreakpoints" => self.set_breakpoints(args).await,
+            "configurationDone" => {
+                if self.launched {
+                    self.adapter.run_program().await.map_err(|e| format!("Run failed: {}", e))?;
+                }
+                Ok(Value::Null)
+            }
+            "threads" => {
+                let result = self.adapter.list_threads().await.map_err(|e| e.to_string())?;
+                let threads: Vec<Value> = ThreadList::from_result(&result).threads.iter()
+                    .map(|thread| json!({ "id": thread.id, "name": thread.name.clone().unwrap_or_else(|| thread.target_id.clone()) }))
+                    .collect();
+                Ok(json!({ "threads": threads }))
+            }
+            "stackTrace" => self.stack_trace(args).await,
+            "scopes" => {
+                let frame = args["frameId"].as_i64().ok_or("Missing frameId")?;
+                // Variable references are frame ids shifted past 0, which means "no children"
+                Ok(json!({ "scopes": [{ "name": "Locals", "variablesReference": frame + 1, "expensive": false }] }))
+            }
+            "variables" => self.variables(args).await,
+            "evaluate" => self.evaluate(args).await,
+            "continue" => {
+                self.select_request_thread(args).await?;
+                self.adapter.continue_execution().await.map_err(|e| e.to_string())?;
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "next" => {
+                self.select_request_thread(args).await?;
+                self.adapter.next().await.map(|_| Value::Null).map_err(|e| e.to_string())
+            }
+            "stepIn" => {
+                self.select_request_thread(args).await?;
+                self.adapter.step().await.map(|_| Value::Null).map_err(|e| e.to_string())
+            }
+            "stepOut" => {
+                self.select_request_thread(args).await?;
+                self.adapter.step_out().await.map(|_| Value::Null).map_err(|e| e.to_string())
+            }
+            "pause" => self.adapter.interrupt().await.map(|_| Value::Null).map_err(|e| e.to_string()),
+            "disconnect" => self.disconnect(args).await,
+            command => Err(format!("Unsupported request: {}", command)),
+        }
+    }
+
+    async fn start_session(&mut self) -> Result<(), String> {
+        if !self.adapter.is_running() {
+            self.adapter.start_session().await.map_err(|e| format!("Failed to start GDB: {}", e))?;
+            self.adapter.set_mi_async(true).await.map_err(|e| format!("Failed to enable MI async mode: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// `program`, with optional `args`, `cwd` and `stopOnEntry`; runs at `configurationDone`
+    async fn launch(&mut self, args: &Value) -> Result<Value, String> {
+        let program = args["program"].as_str().ok_or("Missing program")?;
+        self.start_session().await?;
+        self.adapter.load_executable(program).await.map_err(|e| format!("Load executable failed: {}", e))?;
+
+        let arguments: Vec<String> = args["args"].as_array()
+            .map(|list| list.iter().filter_map(|arg| arg.as_str()).map(String::from).collect())
+            .unwrap_or_default();
+        self.adapter.set_args(&arguments).await.map_err(|e| format!("Set arguments failed: {}", e))?;
+        if let Some(cwd) = args["cwd"].as_str() {
+            self.adapter.set_cwd(cwd).await.map_err(|e| format!("Set working directory failed: {}", e))?;
+        }
+        if args["stopOnEntry"].as_bool() == Some(true) {
+            let spec = BreakpointSpec { temporary: true, ..BreakpointSpec::new("main") };
+            self.adapter.set_breakpoint_spec(&spec).await.map_err(|e| format!("Stop on entry failed: {}", e))?;
+        }
+        self.launched = true;
+        Ok(Value::Null)
+    }
+
+    /// A local `pid` or a gdbserver `target` (`host:port`), with an optional `program` for symbols
+    async fn attach(&mut self, args: &Value) -> Result<Value, String> {
+        let target = match (args["pid"].as_u64(), args["target"].as_str()) {
+            (Some(pid), _) => AttachTarget::Process(u32::try_from(pid).map_err(|_| "Invalid pid")?),
+            (None, Some(host_port)) => AttachTarget::GdbServer(host_port.to_string()),
+            (None, None) => return Err("Attach needs a pid or a target".to_string()),
+        };
+        self.start_session().await?;
+        if let Some(program) = args["program"].as_str() {
+            self.adapter.load_executable(program).await.map_err(|e| format!("Load executable failed: {}", e))?;
+        }
+        self.adapter.attach_and_stop(&target).await.map_err(|e| format!("Attach failed: {}", e))?;
+        self.launched = false;
+        Ok(Value::Null)
+    }
+
+    async fn set_breakpoints(&mut self, args: &Value) -> Result<Value, String> {
+        let path = args["source"]["path"].as_str().ok_or("Missing source path")?.to_string();
+        for number in self.source_breakpoints.remove(&path).unwrap_or_default() {
+            if let Err(e) = self.adapter.remove_breakpoint(number).await {
+                log::warn!("set_breakpoints: Failed to remove breakpoint {}: {}", number, e);
+            }
+        }
+
+        let mut numbers = Vec::new();
+        let mut breakpoints = Vec::new();
+        for requested in args["breakpoints"].as_array().into_iter().flatten() {
+            let line = requested["line"].as_u64().unwrap_or(0);
+            let location = LocationSpec::FileLine(path.clone(), u32::try_from(line).unwrap_or(0));
+            if let Err(e) = location.validate() {
+                breakpoints.push(json!({ "verified": false, "line": line, "message": e }));
+                continue;
+            }
+            // Sources of libraries that aren't loaded yet resolve later
+            let spec = BreakpointSpec {
+                condition: requested["condition"].as_str().map(String::from),
+                pending: true,
+                ..BreakpointSpec::at(&location)
+            };
+            let inserted = self.adapter.set_breakpoint_spec(&spec).await
+                .map(|result| Self::inserted_breakpoint(&result));
+            breakpoints.push(match inserted {
+                Ok(Some(breakpoint)) => {
+                    numbers.extend(breakpoint.number.parse::<u32>().ok());
+                    dap_breakpoint(&breakpoint)
+                }
+                Ok(None) => json!({ "verified": false, "line": line }),
+                Err(e) => json!({ "verified": false, "line": line, "message": e.to_string() }),
+            });
+        }
+        self.source_breakpoints.insert(path, numbers);
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn inserted_breakpoint(result: &GdbResult) -> Option<Breakpoint> {
+        result.results.get("bkpt").and_then(|bkpt| bkpt.as_tuple()).and_then(Breakpoint::from_tuple)
+    }
+
+    async fn stack_trace(&mut self, args: &Value) -> Result<Value, String> {
+        let thread = args["threadId"].as_u64().and_then(|id| u32::try_from(id).ok()).ok_or("Missing threadId")?;
+        self.adapter.select_thread(thread).await.map_err(|e| e.to_string())?;
+        let result = self.adapter.get_stack_frames().await.map_err(|e| e.to_string())?;
+        let frames = StackFrame::list_from_result(&result)?;
+
+        let start = args["startFrame"].as_u64().unwrap_or(0) as usize;
+        let levels = args["levels"].as_u64().filter(|&levels| levels > 0).map_or(usize::MAX, |levels| levels as usize);
+        let stack_frames: Vec<Value> = frames.iter().skip(start).take(levels).map(|frame| dap_stack_frame(thread, frame)).collect();
+        Ok(json!({ "stackFrames": stack_frames, "totalFrames": frames.len() }))
+    }
+
+    /// Select the request's `threadId`, so stepping acts on the thread the client
+    /// asked for rather than whichever one `stackTrace` or `scopes` selected last
+    async fn select_request_thread(&mut self, args: &Value) -> Result<(), String> {
+        let thread = args["threadId"].as_u64().and_then(|id| u32::try_from(id).ok()).ok_or("Missing threadId")?;
+        self.adapter.select_thread(thread).await.map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Make the frame behind a frame id the selected one
+    async fn select_frame(&mut self, id: i64) -> Result<(), String> {
+        let (thread, level) = split_frame_id(id).ok_or("Invalid frame id")?;
+        self.adapter.select_thread(thread).await.map_err(|e| e.to_string())?;
+        self.adapter.select_frame(level).await.map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    async fn variables(&mut self, args: &Value) -> Result<Value, String> {
+        let reference = args["variablesReference"].as_i64().ok_or("Missing variablesReference")?;
+        self.select_frame(reference - 1).await?;
+        let result = self.adapter.list_stack_variables(false).await.map_err(|e| e.to_string())?;
+        let variables: Vec<Value> = result.results.get("variables")
+            .map(Argument::list_from_value)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|variable| json!({ "name": variable.name, "value": variable.value, "variablesReference": 0 }))
+            .collect();
+        Ok(json!({ "variables": variables }))
+    }
+
+    async fn evaluate(&mut self, args: &Value) -> Result<Value, String> {
+        let expression = args["expression"].as_str().ok_or("Missing expression")?;
+        if let Some(frame) = args["frameId"].as_i64() {
+            self.select_frame(frame).await?;
+        }
+        let result = self.adapter.evaluate_expression(expression).await.map_err(|e| e.to_string())?;
+        let value = result.results.get("value").and_then(|v| v.as_string()).unwrap_or_default();
+        Ok(json!({ "result": value, "variablesReference": 0 }))
+    }
+
+    /// Kill a launched program (or any, with `terminateDebuggee`), detach from an attached one
+    async fn disconnect(&mut self, args: &Value) -> Result<Value, String> {
+        if self.adapter.is_running() {
+            let terminate = args["terminateDebuggee"].as_bool().unwrap_or(self.launched);
+            // GDB can only detach or kill a stopped target
+            let outcome = match self.adapter.stop_target().await {
+                Ok(()) if terminate => self.adapter.kill().await,
+                Ok(()) => self.adapter.detach().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = outcome {
+                log::warn!("disconnect: Failed to end the debuggee: {}", e);
+            }
+            self.adapter.stop_session().await.map_err(|e| e.to_string())?;
+        }
+        Ok(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn async_event(input: &str) -> GdbEvent {
+        match gdbadapter::parse_gdb_output(input).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => GdbEvent::Async(record),
+            _ => panic!("Expected async record"),
+        }
+    }
+
+    fn request(command: &str, arguments: Value) -> Request {
+        Request { seq: 1, command: command.to_string(), arguments }
+    }
+
+    #[tokio::test]
+    async fn test_steps_run_on_the_requested_thread() {
+        let gdb = gdbadapter::mock::MockGdb::new()
+            .expect("thread-select 2", &["{token}^done"])
+            .expect("exec-next", &["{token}^running", r#"*running,thread-id="all""#]);
+        let (mut adapter, _events) = GdbAdapter::new();
+        let (output, input) = gdb.serve();
+        adapter.start_session_with_io(output, input).await.unwrap();
+        let (sender, mut messages) = mpsc::unbounded_channel();
+        let mut server = DapServer::new(adapter, sender);
+
+        assert!(server.handle(request("next", json!({ "threadId": 2 }))).await);
+        assert_eq!(messages.recv().await.unwrap()["success"], true);
+        assert!(gdb.remaining().is_empty());
+
+        server.handle(request("stepIn", json!({}))).await;
+        assert_eq!(messages.recv().await.unwrap()["message"], "Missing threadId");
+    }
+
+    #[tokio::test]
+    async fn test_initialized_only_follows_a_successful_launch() {
+        let (adapter, _events) = GdbAdapter::new();
+        let (sender, mut messages) = mpsc::unbounded_channel();
+        let mut server = DapServer::new(adapter, sender);
+
+        server.handle(request("launch", json!({}))).await;
+        assert_eq!(messages.recv().await.unwrap()["success"], false);
+        assert!(messages.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_frame_ids_round_trip() {
+        assert_eq!(split_frame_id(frame_id(3, 7)), Some((3, 7)));
+        assert_eq!(split_frame_id(-1), None);
+    }
+
+    #[test]
+    fn test_stopped_records_become_stopped_events() {
+        let events = dap_events(&async_event(r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="1",frame={addr="0x08000100",func="main",args=[]},thread-id="2",stopped-threads="all""#));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["event"], "stopped");
+        assert_eq!(events[0]["body"]["reason"], "breakpoint");
+        assert_eq!(events[0]["body"]["threadId"], 2);
+
+        let events = dap_events(&async_event(r#"*stopped,reason="signal-received",signal-name="SIGINT",signal-meaning="Interrupt",thread-id="1""#));
+        assert_eq!(events[0]["body"]["reason"], "pause");
+
+        let events = dap_events(&async_event(r#"*stopped,reason="exited",exit-code="03""#));
+        assert_eq!(events[0]["event"], "exited");
+        assert_eq!(events[0]["body"]["exitCode"], 3);
+        assert_eq!(events[1]["event"], "terminated");
+    }
+
+    #[test]
+    fn test_stack_frame_and_breakpoint_payloads() {
+        let frame = StackFrame {
+            level: 1,
+            address: "0x08000200".to_string(),
+            function: Some("main".to_string()),
+            file: Some("main.c".to_string()),
+            fullname: Some("/src/main.c".to_string()),
+            line: Some(12),
+            arch: None,
+            args: Vec::new(),
+        };
+        let dap_frame = dap_stack_frame(2, &frame);
+        assert_eq!(dap_frame["id"], frame_id(2, 1));
+        assert_eq!(dap_frame["name"], "main");
+        assert_eq!(dap_frame["source"]["path"], "/src/main.c");
+
+        let record = async_event(r#"=breakpoint-modified,bkpt={number="4",type="breakpoint",disp="keep",enabled="y",addr="0x08000124",func="loop",file="main.c",fullname="/src/main.c",line="30",thread-groups=["i1"],times="0"}"#);
+        let GdbEvent::Async(record) = record else { unreachable!() };
+        let breakpoint = record.results.get("bkpt").and_then(|bkpt| bkpt.as_tuple()).and_then(Breakpoint::from_tuple).unwrap();
+        let dap = dap_breakpoint(&breakpoint);
+        assert_eq!(dap["id"], 4);
+        assert_eq!(dap["line"], 30);
+        assert_eq!(dap["verified"], true);
+    }
+}
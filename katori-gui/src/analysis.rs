@@ -0,0 +1,169 @@
+//! Disassembly analysis
+//!
+//! Finds the addresses an instruction refers to (branch and call targets,
+//! literal loads) and names them after the function they fall in, using the
+//! `func-name`/`offset` GDB reported for the instructions already
+//! disassembled. Targets GDB annotated itself (`bl 0x8000124 <foo>`) are left
+//! as they are.
+
+use std::collections::BTreeMap;
+
+use gdbadapter::{parse_address, AssemblyLine};
+
+/// Function and offset of every disassembled instruction, by address
+#[derive(Debug, Default)]
+pub struct SymbolMap {
+    symbols: BTreeMap<u64, (String, u32)>,
+}
+
+impl SymbolMap {
+    pub fn new(lines: &[AssemblyLine]) -> Self {
+        let symbols = lines.iter()
+            .filter_map(|line| {
+                let address = parse_address(&line.address)?;
+                let function = line.function.clone()?;
+                Some((address, (function, line.offset.unwrap_or(0))))
+            })
+            .collect();
+        SymbolMap { symbols }
+    }
+
+    /// Name an address as `foo+0x4`.
+    ///
+    /// Addresses between two instructions of the same function (inside a
+    /// multi-byte instruction, or data the compiler placed there) count as
+    /// part of it; anything past the last known instruction is unknown.
+    pub fn resolve(&self, address: u64) -> Option<String> {
+        let (&start, (function, offset)) = self.symbols.range(..=address).next_back()?;
+        if start != address {
+            let (_, (next_function, _)) = self.symbols.range(address..).next()?;
+            if next_function != function {
+                return None;
+            }
+        }
+        // A bogus offset larger than the address names nothing
+        let function_start = start.checked_sub(u64::from(*offset))?;
+        match address - function_start {
+            0 => Some(function.clone()),
+            offset => Some(format!("{function}+0x{offset:x}")),
+        }
+    }
+}
+
+/// An address literal among an instruction's operands
+struct AddressOperand {
+    /// Byte index just past the last digit
+    end: usize,
+    value: u64,
+    /// GDB already follows it with `<symbol>`
+    annotated: bool,
+}
+
+/// Hex literals in the operands that can be addresses.
+///
+/// Immediates (`#0x10`, `$0x10`) and displacements (`0x10(%rbp)`) are skipped.
+fn address_operands(instruction: &str) -> Vec<AddressOperand> {
+    let mut operands = Vec::new();
+    let mut position = instruction.find(char::is_whitespace).unwrap_or(instruction.len());
+    while let Some(found) = instruction[position..].find("0x") {
+        let begin = position + found;
+        let digits = begin + 2;
+        let end = instruction[digits..].find(|c: char| !c.is_ascii_hexdigit()).map_or(instruction.len(), |n| digits + n);
+        position = end;
+
+        let previous = instruction[..begin].chars().next_back();
+        if matches!(previous, Some('#' | '$')) || previous.is_some_and(|c| c.is_ascii_alphanumeric()) {
+            continue;
+        }
+        let rest = &instruction[end..];
+        if rest.starts_with('(') {
+            continue;
+        }
+        if let Ok(value) = u64::from_str_radix(&instruction[digits..end], 16) {
+            operands.push(AddressOperand { end, value, annotated: rest.trim_start().starts_with('<') });
+        }
+    }
+    operands
+}
+
+/// The instruction with `<foo+0x4>` after every address `symbols` can name
+pub fn annotate(instruction: &str, symbols: &SymbolMap) -> String {
+    let mut annotated = String::with_capacity(instruction.len());
+    let mut copied = 0;
+    for operand in address_operands(instruction) {
+        if operand.annotated {
+            continue;
+        }
+        if let Some(symbol) = symbols.resolve(operand.value) {
+            annotated.push_str(&instruction[copied..operand.end]);
+            annotated.push_str(&format!(" <{symbol}>"));
+            copied = operand.end;
+        }
+    }
+    annotated.push_str(&instruction[copied..]);
+    annotated
+}
+
+/// First address the instruction refers to, where clicking it navigates
+pub fn target_address(instruction: &str) -> Option<u64> {
+    address_operands(instruction).first().map(|operand| operand.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(address: &str, function: &str, offset: u32) -> AssemblyLine {
+        AssemblyLine {
+            address: address.to_string(),
+            function: Some(function.to_string()),
+            offset: Some(offset),
+            instruction: "nop".to_string(),
+            opcodes: None,
+            source: None,
+        }
+    }
+
+    fn symbols() -> SymbolMap {
+        SymbolMap::new(&[
+            line("0x08000120", "foo", 0),
+            line("0x08000124", "foo", 4),
+            line("0x08000128", "foo", 8),
+            line("0x0800012c", "bar", 0),
+        ])
+    }
+
+    #[test]
+    fn test_resolve_names_function_and_offset() {
+        let symbols = symbols();
+
+        assert_eq!(symbols.resolve(0x08000120).as_deref(), Some("foo"));
+        assert_eq!(symbols.resolve(0x08000124).as_deref(), Some("foo+0x4"));
+        assert_eq!(symbols.resolve(0x08000126).as_deref(), Some("foo+0x6"));
+        assert_eq!(symbols.resolve(0x0800012e), None);
+        assert_eq!(symbols.resolve(0x08000100), None);
+
+        let symbols = SymbolMap::new(&[line("0x10", "low", 0x20)]);
+        assert_eq!(symbols.resolve(0x10), None);
+    }
+
+    #[test]
+    fn test_annotate_branch_targets() {
+        let symbols = symbols();
+
+        assert_eq!(annotate("b\t0x8000124", &symbols), "b\t0x8000124 <foo+0x4>");
+        assert_eq!(annotate("call   0x800012c", &symbols), "call   0x800012c <bar>");
+        // Already named by GDB, immediates and unknown addresses are left alone
+        assert_eq!(annotate("bl\t0x8000124 <foo+4>", &symbols), "bl\t0x8000124 <foo+4>");
+        assert_eq!(annotate("movs\tr0, #0x8000124", &symbols), "movs\tr0, #0x8000124");
+        assert_eq!(annotate("b\t0x9000000", &symbols), "b\t0x9000000");
+    }
+
+    #[test]
+    fn test_target_address_skips_immediates_and_displacements() {
+        assert_eq!(target_address("bl\t0x8000124 <foo+4>"), Some(0x8000124));
+        assert_eq!(target_address("mov    0x10(%rbp),%eax"), None);
+        assert_eq!(target_address("add\tr0, #0x10"), None);
+        assert_eq!(target_address("0x10"), None);
+    }
+}
@@ -0,0 +1,133 @@
+//! Console panel history
+//!
+//! Output is kept as separate entries in a ring buffer capped at
+//! `CONSOLE_LIMIT`, so appending never touches older output and each entry
+//! remembers where it came from for coloring.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Most entries kept; the oldest are dropped first
+pub const CONSOLE_LIMIT: usize = 1000;
+
+/// Where a console entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConsoleKind {
+    /// Messages from Katori itself
+    Info,
+    /// GDB's console stream (`~`), e.g. answers to CLI commands
+    Gdb,
+    /// The debugged program's output (`@`), shown in its own panel rather than the console
+    Target,
+    /// GDB's log stream (`&`): echoed commands and internal messages
+    Log,
+    /// Failures, including anything GDB writes to stderr
+    Error,
+}
+
+impl ConsoleKind {
+    /// Sources the console shows and can filter
+    pub const ALL: [ConsoleKind; 4] = [ConsoleKind::Info, ConsoleKind::Gdb, ConsoleKind::Log, ConsoleKind::Error];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConsoleKind::Info => "Katori",
+            ConsoleKind::Gdb => "GDB",
+            ConsoleKind::Target => "Target",
+            ConsoleKind::Log => "Log",
+            ConsoleKind::Error => "Errors",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsoleEntry {
+    pub text: String,
+    pub kind: ConsoleKind,
+    pub timestamp: Instant,
+}
+
+#[derive(Debug)]
+pub struct Console {
+    entries: VecDeque<ConsoleEntry>,
+    limit: usize,
+    started: Instant,
+}
+
+impl Console {
+    pub fn new(limit: usize) -> Self {
+        Console {
+            entries: VecDeque::with_capacity(limit),
+            limit: limit.max(1),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn push(&mut self, kind: ConsoleKind, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if self.entries.len() == self.limit {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(ConsoleEntry { text: text.to_string(), kind, timestamp: Instant::now() });
+    }
+
+    /// Append a message from Katori itself
+    pub fn push_str(&mut self, text: &str) {
+        self.push(ConsoleKind::Info, text);
+    }
+
+    pub fn push_error(&mut self, text: &str) {
+        self.push(ConsoleKind::Error, text);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &ConsoleEntry> {
+        self.entries.iter()
+    }
+
+    /// Seconds between creating the console and `entry`
+    pub fn elapsed(&self, entry: &ConsoleEntry) -> f32 {
+        entry.timestamp.duration_since(self.started).as_secs_f32()
+    }
+
+    /// Everything still retained, concatenated
+    pub fn text(&self) -> String {
+        self.entries.iter().map(|entry| entry.text.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_keeps_only_the_latest_entries() {
+        let mut console = Console::new(CONSOLE_LIMIT);
+        for i in 0..CONSOLE_LIMIT {
+            console.push_str(&format!("message {i}\n"));
+        }
+        let capacity = console.entries.capacity();
+
+        for i in CONSOLE_LIMIT..5000 {
+            console.push_str(&format!("message {i}\n"));
+        }
+
+        assert_eq!(console.entries().count(), CONSOLE_LIMIT);
+        assert_eq!(console.entries().next().unwrap().text, format!("message {}\n", 5000 - CONSOLE_LIMIT));
+        assert!(console.text().ends_with("message 4999\n"));
+        // Dropping the oldest entry makes room; the buffer never regrows
+        assert_eq!(console.entries.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_console_entries_keep_their_kind() {
+        let mut console = Console::new(10);
+        console.push_str("info\n");
+        console.push_error("oops\n");
+        console.push(ConsoleKind::Target, "");
+
+        let kinds: Vec<_> = console.entries().map(|entry| entry.kind).collect();
+        assert_eq!(kinds, [ConsoleKind::Info, ConsoleKind::Error]);
+    }
+}
@@ -1,11 +1,33 @@
 use eframe::{egui, CreationContext};
 use egui_extras::Column;
-use gdbadapter::{AssemblyLine, AsyncClass, GdbAdapter, GdbEvent, Register, StackFrame, Value};
-use syntect::parsing::{SyntaxDefinition, SyntaxReference, SyntaxSet, SyntaxSetBuilder};
+use gdbadapter::{parse_address, AssemblyLine, AsyncClass, AsyncRecord, AttachTarget, Breakpoint, BreakpointSpec, CommandCanceller, GdbAdapter, GdbError, GdbEvent, GdbResult, LatencyMonitor, LocationSpec, Register, RegisterFormat, RegisterSet, ResultClass, SourceLine, StackFrame, StopInfo, StopReason, StreamType, ThreadList, Value, VarObj, VarObjChange, VarObjChildren, VectorView, WatchKind, Watchpoint};
+use syntect::parsing::SyntaxDefinition;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use log::{info, warn, error, debug};
 
+mod analysis;
+mod console;
+mod memory;
+mod mi;
+mod queue;
+mod references;
+mod registers;
+mod variables;
+
+use analysis::{annotate, target_address, SymbolMap};
+use console::{Console, ConsoleKind, CONSOLE_LIMIT};
+use memory::{decode_hex, format_ascii, format_hex_dump, format_hex_row, Endian, MemoryView, BYTES_PER_ROW_OPTIONS, GROUP_SIZE_OPTIONS, MEMORY_CHUNK_SIZE};
+use mi::{parse_assembly, parse_memory, parse_register_names, parse_registers, MemoryReadResult};
+use queue::CommandQueue;
+use references::{find_in_frames, find_in_memory, find_in_registers, parse_register_value, pointer_size, ReferenceLocation};
+use registers::{
+    core_register_numbers, format_registers, group_registers, has_vector_registers, is_thumb_state,
+    merge_register_values, pinned_registers, visible_register_numbers, RegisterGroup,
+};
+use variables::{VarObjTree, VAROBJ_PAGE_SIZE};
+
 pub fn run_gui() -> i32 {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -33,33 +55,305 @@ enum GdbCommand {
     StepOver,
     StepInto,
     StepOut,
+    /// Step this many instructions
+    StepInstructions(u32),
+    /// Continue to an address through a temporary breakpoint
+    RunToAddress(String),
+    /// Run backwards through the execution recording
+    ReverseContinue,
+    /// Step back one instruction, entering calls
+    ReverseStepInto,
+    /// Step back one instruction, over calls
+    ReverseStepOver,
+    /// Start (`true`) or stop (`false`) recording execution for reverse debugging
+    SetRecording(bool),
     Interrupt,
-    SetBreakpoint(String),
+    SetBreakpoint(BreakpointSpec),
+    RemoveBreakpoint(u32),
+    /// Re-read the breakpoint table (`-break-list`)
+    RefreshBreakpoints,
+    /// Enable (`true`) or disable (`false`) a breakpoint by number
+    ToggleBreakpoint(u32, bool),
+    /// Enable or disable one location of a multi-location breakpoint, e.g. `2.1`
+    ToggleBreakpointLocation(String, bool),
+    /// Continue until a breakpoint has been hit this many more times
+    ContinueUntilHit(u32, u32),
+    /// Set how many upcoming hits a breakpoint ignores (0 to stop at every hit)
+    SetIgnoreCount(u32, u32),
+    /// Evaluate the watch panel's expressions in one pipelined batch
+    EvaluateWatches(Vec<String>),
+    /// Create a variable object for a watch expression and list its first children
+    ExpandWatch(String),
+    /// List children `from..to` of a variable object
+    ListVarObjChildren(String, u32, u32),
+    /// Evaluate every variable object again
+    UpdateVarObjs,
+    DeleteVarObj(String),
+    SetWatchpoint(String, WatchKind),
     RefreshDebugInfo,
-    ReadMemory(String, u32),
+    /// Read a range into the memory view with this id
+    ReadMemory(usize, String, u32),
+    /// Read one chunk of a large range for a memory view: view id, read generation,
+    /// address, offset and length
+    ReadMemoryChunk(usize, u64, String, u32, u32),
+    WriteMemory(String, Vec<u8>),
+    /// Read a range only for the reference search, leaving the memory viewer alone
+    ScanMemory(String, u32),
     // Session management commands
     StartSession,
     StopSession,
     Attach(AttachMode, String), // mode and target (PID or host:port)
     Detach,
+    /// Load a local executable and its symbols, starting GDB if needed
+    LoadExecutable(String),
+    /// Load an executable and a core file it dumped, starting GDB if needed
+    LoadCore(String, String),
+    /// Start the loaded executable (`-exec-run`) with these arguments,
+    /// in this working directory if given
+    Run(Vec<String>, Option<String>),
+    /// Set these environment variables and unset these names for programs started from now on
+    SetEnvironment(Vec<(String, String)>, Vec<String>),
+    /// Command typed into the console, MI (`-exec-next`) or CLI (`info registers`)
+    Raw(String),
+    /// Text typed into the Program Output panel, for the program's standard input
+    SendProgramInput(String),
+    /// Resolve an address/symbol/expression and open it in a view
+    GoTo(String, GoToTarget),
+    /// Disassemble a whole function by name; a hex address that is not a symbol is disassembled from there
+    DisassembleFunction(String),
+    /// Switch the current thread and refresh debug info for it
+    SelectThread(u32),
+    /// Interrupt one thread, leaving the others running (non-stop mode)
+    InterruptThread(u32),
+    /// Switch GDB to non-stop mode, where threads stop and resume individually; before attaching only
+    SetNonStop(bool),
+    /// Select a stack frame by level and refresh debug info for it
+    SelectFrame(u32),
+    /// One attempt to re-attach to a gdbserver after the connection dropped; the UI schedules retries
+    Reconnect(String),
+    /// `set architecture`, for targets where GDB can't detect it
+    SetArchitecture(String),
+    /// `set disassembly-flavor`, then re-disassemble
+    SetDisassemblyFlavor(String),
+    /// Bytes per instruction when disassembling, `None` to follow the Thumb state
+    SetInstructionWidth(Option<u32>),
+    /// Change the register display format and re-read the registers
+    SetRegisterFormat(RegisterFormat),
+    /// Switch between the core and full register sets and re-read the registers
+    SetRegisterSet(RegisterSet),
+    /// Read only these registers at each stop, `None` for the whole set; any
+    /// coming into view are read right away
+    SetRegisterSubset(Option<Vec<u32>>),
+    /// Append the raw MI traffic to this file, `None` to stop logging
+    SetMiLog(Option<String>),
+    /// Run the program again from the start, stopping a live one first
+    Restart,
+    /// Kill the program being debugged, keeping GDB and the breakpoints
+    Kill,
+}
+
+impl GdbCommand {
+    /// Whether the command needs a running GDB session; session management
+    /// (including loading an executable or core file and attaching, which start
+    /// GDB when needed) and display preferences are always allowed
+    fn requires_session(&self) -> bool {
+        !matches!(
+            self,
+            GdbCommand::StartSession | GdbCommand::StopSession | GdbCommand::LoadExecutable(_)
+                | GdbCommand::LoadCore(_, _) | GdbCommand::Attach(_, _)
+                | GdbCommand::SetRegisterFormat(_) | GdbCommand::SetRegisterSet(_) | GdbCommand::SetNonStop(_)
+                | GdbCommand::SetRegisterSubset(_) | GdbCommand::SetMiLog(_) | GdbCommand::SetArchitecture(_)
+                | GdbCommand::SetInstructionWidth(_) | GdbCommand::SetDisassemblyFlavor(_)
+        )
+    }
+    
+    /// Whether the command resumes a stopped target, which makes no sense while it runs
+    fn is_step(&self) -> bool {
+        matches!(self, GdbCommand::StepOver | GdbCommand::StepInto | GdbCommand::StepOut | GdbCommand::StepInstructions(_) | GdbCommand::ReverseStepInto | GdbCommand::ReverseStepOver)
+    }
+}
+
+/// Wait before reconnect `attempt` (1-based): one second, doubling each time, at most 30 seconds
+fn reconnect_delay(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_secs((1u64 << attempt.saturating_sub(1).min(5)).min(30))
+}
+
+/// Progress of an automatic reconnect
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PendingReconnect {
+    /// 1-based number of the current attempt
+    attempt: u32,
+    /// When the attempt is due; `None` once it has been sent
+    due: Option<std::time::Instant>,
+}
+
+impl PendingReconnect {
+    fn after_backoff(attempt: u32) -> Self {
+        Self { attempt, due: Some(std::time::Instant::now() + reconnect_delay(attempt)) }
+    }
+}
+
+/// Instructions disassembled before and after the PC on each refresh, keeping it mid-view
+const DISASSEMBLY_WINDOW: (u32, u32) = (40, 40);
+
+/// Space left before each disassembly line for breakpoint dots
+const ASSEMBLY_GUTTER: &str = "  ";
+
+/// x86 disassembly syntaxes GDB offers (`set disassembly-flavor`), with their labels
+const DISASSEMBLY_FLAVORS: &[(&str, &str)] = &[("att", "AT&T"), ("intel", "Intel")];
+
+/// Whether the disassembly flavor applies; an unknown architecture might be x86
+fn is_x86(arch: Option<&str>) -> bool {
+    arch.is_none_or(|arch| arch.starts_with("i386") || arch.starts_with("i8086"))
+}
+
+/// Status bar text for command round-trip times
+fn format_latency(last: Option<std::time::Duration>, average: Option<std::time::Duration>) -> String {
+    match last.zip(average) {
+        Some((last, average)) => format!("GDB latency: {} ms (avg {} ms)", last.as_millis(), average.as_millis()),
+        None => "GDB latency: -".to_string(),
+    }
+}
+
+/// Whether GDB output reports that the remote target connection went away
+fn is_remote_disconnect(text: &str) -> bool {
+    text.contains("Remote connection closed") || text.contains("Remote communication error")
+}
+
+/// Prefix the adapter puts on lines GDB wrote to stderr
+const GDB_STDERR_PREFIX: &str = "GDB stderr: ";
+
+/// Console message for commands issued before a session is started
+const NO_SESSION_MESSAGE: &str = "No active GDB session\n";
+
+/// Where the command palette sends a resolved address
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GoToTarget {
+    Disassembly,
+    Memory,
+    Breakpoint,
+    /// Find where the address is referenced in registers, frames and memory
+    References,
+}
+
+/// Which inputs the breakpoint panel shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LocationKind {
+    Function,
+    FileLine,
+    Address,
+}
+
+impl LocationKind {
+    fn label(self) -> &'static str {
+        match self {
+            LocationKind::Function => "Function",
+            LocationKind::FileLine => "File:line",
+            LocationKind::Address => "Address",
+        }
+    }
+}
+
+/// What to do with an attached process when Katori is closed
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExitAction {
+    /// Detach and leave the process running
+    Detach,
+    Kill,
 }
 
 #[derive(Debug)]
 enum DebugEvent {
-    RegistersUpdated(Vec<Register>),
+    /// Register values, and the numbers GDB reports as changed (`None` if it couldn't say)
+    RegistersUpdated(Vec<Register>, Option<Vec<u32>>),
+    /// Values of some of the registers shown, read while others were folded away,
+    /// with the changed numbers as in `RegistersUpdated`
+    RegisterValuesUpdated(Vec<Register>, Option<Vec<u32>>),
     StackFramesUpdated(Vec<StackFrame>),
+    ThreadsUpdated(ThreadList),
     AssemblyUpdated(Vec<AssemblyLine>),
+    BreakpointInserted(Breakpoint),
+    /// Breakpoint list as reported by `-break-list`
+    BreakpointsUpdated(Vec<Breakpoint>),
+    /// Breakpoint details changed, e.g. its hit count after being hit
+    BreakpointModified(Breakpoint),
+    /// The target stopped at this breakpoint number
+    BreakpointHit(String),
+    /// A "run to here" is under way, using this temporary breakpoint
+    RunToAddressStarted(String),
+    /// GDB deleted a breakpoint, e.g. a temporary one after it was hit
+    BreakpointDeleted(String),
+    /// Watch expression values (or per-expression errors), in panel order
+    WatchesUpdated(Vec<(String, Result<String, String>)>),
+    /// The variable object made for this watch expression
+    VarObjCreated(String, VarObj),
+    /// Children of a variable object, listed from this index
+    VarObjChildren(String, u32, VarObjChildren),
+    VarObjsUpdated(Vec<VarObjChange>),
+    WatchpointInserted(Watchpoint),
     ConsoleMessage(String),
+    /// Output from GDB's console or log stream
+    StreamOutput(ConsoleKind, String),
+    /// What the debugged program printed (target stream)
+    ProgramOutput(String),
+    /// The program exited on its own with this code (`exited` or `exited-normally`)
+    ProgramExited(i32),
+    /// Architecture GDB found for the target, e.g. after attaching
+    ArchitectureDetected(String),
     AttachSuccess(Option<u32>), // PID for process attach, None for gdbserver
     AttachFailed(String),
+    /// GDB reported that the remote target connection closed
+    RemoteConnectionLost,
+    /// A reconnect attempt failed
+    ReconnectFailed(String),
     DetachSuccess,
-    MemoryRead(MemoryReadResult),
-    MemoryReadFailed(String),
+    /// Executable and symbols loaded from this path
+    ExecutableLoaded(String),
+    /// Contents for the memory view with this id
+    MemoryRead(usize, MemoryReadResult),
+    /// One chunk of a large read: view id, read generation, the offset it was read at, and the data
+    MemoryChunk(usize, u64, u32, MemoryReadResult),
+    /// Point the memory viewer at a resolved address
+    MemoryGoTo(String),
+    /// Search for references to a resolved address
+    FindReferences(String),
+    /// Extra memory read for the reference search
+    ReferenceScanRead(MemoryReadResult),
+    /// Reading the memory view with this id failed
+    MemoryReadFailed(usize, String),
     // Command completion events
     CommandCompleted(GdbCommand),
     CommandFailed(GdbCommand, String),
     GdbConnectionLost,
     TargetStateChanged(TargetState),
+    /// GDB started (`true`) or stopped (`false`) recording execution
+    RecordingChanged(bool),
+    /// Readable form of a notify record (library loaded, thread created, ...)
+    Notification(String),
+    /// The program stopped because it received a signal
+    SignalReceived(SignalAlert),
+    /// Memory was written outside of Katori (`=memory-changed`): address and length
+    MemoryChanged(u64, u64),
+    /// A thread resumed (`*running`), or every thread when `None`
+    ThreadRunning(Option<u32>),
+}
+
+/// A signal that stopped the program, shown as a banner until it runs again
+#[derive(Debug, Clone, PartialEq)]
+struct SignalAlert {
+    name: String,
+    meaning: Option<String>,
+    /// Address of the faulting instruction
+    address: Option<String>,
+}
+
+impl std::fmt::Display for SignalAlert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.meaning {
+            Some(meaning) => write!(f, "Program received {}: {meaning}", self.name),
+            None => write!(f, "Program received {}", self.name),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -67,17 +361,67 @@ enum TargetState {
     Running,
     Stopped,
     Detached,
+    /// The program exited, normally or killed by a signal
+    Exited(ExitStatus),
+    /// A core file is loaded; state can be inspected but not executed
+    CoreDump,
+}
+
+/// How the program terminated
+#[derive(Debug, Clone, PartialEq)]
+enum ExitStatus {
+    Code(i32),
+    /// Terminated by a signal (`exited-signalled`)
+    Signal(String),
+    /// Killed from the debugger
+    Killed,
+}
+
+impl std::fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExitStatus::Code(0) => write!(f, "Program exited normally"),
+            ExitStatus::Code(code) => write!(f, "Program exited with code {code}"),
+            ExitStatus::Signal(signal) => write!(f, "Program terminated by {signal}"),
+            ExitStatus::Killed => write!(f, "Program killed"),
+        }
+    }
+}
+
+impl TargetState {
+    /// Whether continue/step/break make sense in this state
+    fn can_execute(&self) -> bool {
+        !matches!(self, TargetState::Exited(_) | TargetState::CoreDump)
+    }
+    
+    /// Whether the program has ended and can be started again from the beginning
+    fn can_restart(&self) -> bool {
+        matches!(self, TargetState::Exited(_))
+    }
+    
+    /// Whether there is a live program to kill
+    fn can_kill(&self) -> bool {
+        matches!(self, TargetState::Running | TargetState::Stopped)
+    }
 }
 
 /// Main application state
 pub struct KatoriApp {
     /// GDB adapter instance
     gdb_adapter: Arc<Mutex<GdbAdapter>>,
+    /// Fails commands stuck waiting on GDB (e.g. a continue) without taking the adapter lock
+    command_canceller: CommandCanceller,
+    /// Command round-trip times, shown in the status bar even while a command holds the adapter
+    latency_monitor: LatencyMonitor,
 
-    syntax_set: syntect::parsing::SyntaxSet,
+    /// Syntaxes and syntect's bundled color themes, built once and shared by every view
+    syntax: egui_extras::syntax_highlighting::SyntectSettings,
+    /// Colors for the disassembly, picked apart from the egui theme
+    code_theme: egui_extras::syntax_highlighting::CodeTheme,
     
     /// Event communication
     event_receiver: tokio::sync::mpsc::UnboundedReceiver<DebugEvent>,
+    #[allow(dead_code)]
     event_sender: tokio::sync::mpsc::UnboundedSender<DebugEvent>,
     
     /// Command channel for async GDB operations from GUI
@@ -86,47 +430,259 @@ pub struct KatoriApp {
     /// Debug session state
     is_debugging: bool,
     is_attached: bool,
+    /// GDB runs in non-stop mode; only changed while detached
+    non_stop: bool,
+    /// The target was started with Run rather than attached to, so it can be restarted while live
+    launched: bool,
+    /// Re-attach to the gdbserver when its connection drops, and how often to try
+    auto_reconnect: bool,
+    reconnect_attempts: u32,
+    /// A reconnect is in progress
+    reconnect: Option<PendingReconnect>,
+    /// Signal behind the last stop, until the program runs again
+    signal_alert: Option<SignalAlert>,
+    /// How the program ended, shown as a banner until dismissed or run again
+    exit_banner: Option<ExitStatus>,
+    /// GDB is recording execution, so the reverse controls work
+    is_recording: bool,
     current_pid: Option<u32>,
     current_host_port: String,
     target_state: TargetState,
     
     /// UI state
     attach_mode: AttachMode,
-    console_output: String,
+    console_output: Console,
+    /// Prefix console entries with the seconds since startup
+    console_timestamps: bool,
+    /// Console sources unticked in the console's filter row
+    hidden_console_kinds: HashSet<ConsoleKind>,
     error_message: String,
+    /// Repaint interval while the target is running, in milliseconds
+    running_repaint_ms: u64,
+    /// Echo GDB notifications (libraries, threads, processes) to the console
+    show_notifications: bool,
     
     /// Debug information
     registers: Vec<Register>,
     assembly_lines: Vec<AssemblyLine>,
+    /// Source files read for the mixed disassembly view, `None` if unreadable
+    source_files: HashMap<String, Option<Vec<String>>>,
+    /// Location the source view last scrolled to, so it only jumps on a new stop
+    source_scroll_location: Option<(String, u32)>,
     stack_frames: Vec<StackFrame>,
-    breakpoints: Vec<String>,
+    /// Level of the frame registers, disassembly and watches refer to
+    selected_frame: u32,
+    /// Target architecture as reported on stack frames (e.g. `armv7e-m`)
+    target_arch: Option<String>,
+    threads: ThreadList,
+    /// Watch panel expressions and their last values
+    watch_expressions: Vec<String>,
+    watch_values: Vec<(String, Result<String, String>)>,
+    /// Expressions whose value changed at the last evaluation
+    changed_watches: HashSet<String>,
+    /// Members of the watch expressions that were expanded
+    watch_tree: VarObjTree,
+    /// Registers highlighted as changed at the last refresh
+    changed_registers: HashSet<u32>,
+    /// Register values by name as of the previous refresh; empty after attaching
+    /// or changing the format, so nothing is highlighted until the next stop
+    register_baseline: HashMap<String, String>,
+    breakpoints: Vec<Breakpoint>,
+    /// Breakpoint hit totals at the last reset, keyed by breakpoint number
+    hit_count_baselines: HashMap<String, u32>,
+    /// Breakpoint number and hit count a "continue until hit" is waiting for
+    pending_hit_target: Option<(u32, u32)>,
+    /// Temporary breakpoint of a "run to here" that has not been reached yet
+    run_to_breakpoint: Option<String>,
+    /// Instructions stepped by "Step N"
+    step_count: u32,
+    /// Instruction address under the pointer when the assembly context menu opened
+    assembly_context_address: Option<String>,
+    watchpoints: Vec<Watchpoint>,
     
     /// UI panels visibility
     show_registers: bool,
     show_assembly: bool,
+    show_source: bool,
     show_stack: bool,
+    show_threads: bool,
+    show_watch: bool,
+    /// Start the float and vector register groups folded
+    collapse_wide_registers: bool,
+    /// Expand SIMD registers into their lanes (only offered when the architecture has them)
+    show_vector_lanes: bool,
     show_memory: bool,
     show_console: bool,
+    show_program_output: bool,
+    
+    /// Memory viewer panes, and the id the next added view gets
+    memory_views: Vec<MemoryView>,
+    next_memory_view_id: usize,
+    
+    /// Executable loaded with File > Open Executable, and the dialog's state
+    executable: Option<String>,
+    show_open_executable: bool,
+    executable_input: String,
+    /// Run settings, applied on the next run: one argument per line, a working directory
+    /// and environment variables
+    show_run_settings: bool,
+    run_arguments: String,
+    run_directory: String,
+    run_environment: Vec<(String, String)>,
+    /// Variables deleted from the table since the last run; GDB keeps them until unset
+    removed_environment: Vec<String>,
+    /// Preferences: whether the raw MI traffic is logged, and to which file
+    show_preferences: bool,
+    mi_log_enabled: bool,
+    mi_log_path: String,
+    
+    /// Reference search: the value, its matches and an optional extra memory range
+    show_references: bool,
+    reference_target: Option<u64>,
+    reference_results: Vec<ReferenceLocation>,
+    reference_scan_address: String,
+    reference_scan_size: String,
+    scanned_memory: Option<MemoryReadResult>,
+    
+    /// Command palette (Ctrl+P) state
+    show_palette: bool,
+    palette_input: String,
+    
+    /// Function typed into the assembly view's "Function" field
+    disassemble_input: String,
+    /// Assembly search: the query, the selected match, and whether to scroll to it
+    assembly_search: String,
+    assembly_match: usize,
+    assembly_jump: bool,
+    /// Name the addresses instructions refer to, and navigate to them on click
+    assembly_analysis: bool,
+    /// Branch target to scroll to once it is in the listing
+    assembly_target: Option<u64>,
+    /// The next listing follows a stop or frame change, so it scrolls to the PC
+    assembly_follow_pc: bool,
+    
+    /// Close confirmation while attached to a process, and the choice made
+    show_exit_confirmation: bool,
+    exit_action: Option<ExitAction>,
     
-    /// Memory viewer state
-    memory_address: String,
-    memory_size: u32,
-    memory_data: Option<MemoryReadResult>,
+    /// What the debugged program printed, and the line being typed for its stdin
+    program_output: Console,
+    program_input: String,
+    
+    /// Raw command console input and its history
+    raw_command_input: String,
+    command_history: CommandHistory,
+    
+    /// Developer mode: records every GDB/MI record for the event overlay
+    developer_mode: bool,
+    show_mi_overlay: bool,
+    mi_tap_receiver: tokio::sync::mpsc::UnboundedReceiver<GdbEvent>,
+    mi_log: VecDeque<MiLogEntry>,
     
     /// Input fields
+    breakpoint_kind: LocationKind,
+    /// Function, source file or address, depending on `breakpoint_kind`
     breakpoint_input: String,
+    breakpoint_line_input: u32,
+    breakpoint_pending: bool,
+    breakpoint_condition_input: String,
+    breakpoint_ignore_input: u32,
+    breakpoint_temporary: bool,
+    breakpoint_hardware: bool,
+    watchpoint_input: String,
+    watch_kind: WatchKind,
+    watch_expression_input: String,
+    /// Hit count for the breakpoint menu's "continue until hit" action
+    continue_hits_input: u32,
     pid_input: String,
+    /// Executable and core file for the core dump attach mode
+    core_executable_input: String,
+    core_file_input: String,
+    /// Architecture forced on GDB, `auto` to let it detect
+    architecture: String,
+    /// x86 disassembly syntax, `att` (GDB's default) or `intel`
+    disassembly_flavor: String,
+    /// Instruction width forced on the disassembler, `None` to detect Thumb code
+    instruction_width: Option<u32>,
+    register_format: RegisterFormat,
+    register_set: RegisterSet,
+    /// Registers last asked to be read at stops, leaving out folded groups
+    register_subset: Option<Vec<u32>>,
+    /// Names of the registers shown above the groups, in the order they were
+    /// pinned; kept across sessions since names outlive register numbers
+    pinned_registers: Vec<String>,
 }
 
+/// Architectures offered in the attach panel; GDB accepts many more via the console
+const SUPPORTED_ARCHITECTURES: &[&str] = &[
+    "auto",
+    "arm",
+    "armv6-m",
+    "armv7e-m",
+    "armv8-m.main",
+    "aarch64",
+    "i386",
+    "i386:x86-64",
+    "riscv:rv32",
+    "riscv:rv64",
+    "mips",
+    "avr",
+];
+
+/// How long to wait for the GDB session to stop when the window closes: enough
+/// for a running target to stop, then be detached or killed, and for GDB to exit
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(gdbadapter::STOP_TIMEOUT.as_secs() + 3);
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AttachMode {
     Process,
     GdbServer,
+    /// Post-mortem: an executable and the core file it dumped
+    CoreDump,
 }
 
+/// Toggleable UI panels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    Registers,
+    Assembly,
+    Source,
+    Stack,
+    Threads,
+    Watch,
+    Memory,
+    Console,
+    ProgramOutput,
+}
+
+/// Storage keys for the settings kept between runs
+const PINNED_REGISTERS_KEY: &str = "pinned_registers";
+const CODE_THEME_KEY: &str = "code_theme";
+
 impl KatoriApp {
     pub fn new(cc: &CreationContext) -> Self {
-        let (gdb_adapter, gdb_event_receiver) = GdbAdapter::new();
+        let mut app = Self::with_context(cc.egui_ctx.clone());
+        if let Some(storage) = cc.storage {
+            app.pinned_registers = eframe::get_value(storage, PINNED_REGISTERS_KEY).unwrap_or_default();
+            if let Some(theme) = eframe::get_value(storage, CODE_THEME_KEY) {
+                app.code_theme = theme;
+            }
+        }
+        app
+    }
+    
+    /// An app that isn't attached to a window, for driving the UI state from tests.
+    /// Like `new`, it must be created inside a Tokio runtime.
+    pub fn headless() -> Self {
+        Self::with_context(egui::Context::default())
+    }
+    
+    fn with_context(ctx: egui::Context) -> Self {
+        let (mut gdb_adapter, gdb_event_receiver) = GdbAdapter::new();
+        let (mi_tap_sender, mi_tap_receiver) = tokio::sync::mpsc::unbounded_channel();
+        gdb_adapter.set_event_tap(Some(mi_tap_sender));
+        let command_canceller = gdb_adapter.canceller();
+        let latency_monitor = gdb_adapter.latency_monitor();
         let gdb_adapter = Arc::new(Mutex::new(gdb_adapter));
         let (event_sender, event_receiver) = tokio::sync::mpsc::unbounded_channel();
         let (command_sender, command_receiver) = tokio::sync::mpsc::unbounded_channel();
@@ -134,13 +690,14 @@ impl KatoriApp {
         // Start the background command processor
         let adapter_clone = gdb_adapter.clone();
         let event_sender_clone = event_sender.clone();
-        let ctx = cc.egui_ctx.clone();
-        tokio::spawn(Self::command_processor_task(ctx, adapter_clone, command_receiver, event_sender_clone, gdb_event_receiver));
+        tokio::spawn(Self::command_processor_task(ctx.clone(), adapter_clone, command_receiver, event_sender_clone));
+        tokio::spawn(Self::gdb_event_task(ctx, gdb_event_receiver, event_sender.clone()));
 
         // Create syntax set
 
         let s = include_str!("../syntax/arm.sublime-syntax");
-        let mut builder = syntect::parsing::SyntaxSetBuilder::new();
+        // Start from syntect's bundled syntaxes so source files (C, Rust...) highlight too
+        let mut builder = syntect::parsing::SyntaxSet::load_defaults_newlines().into_builder();
         let arm_syntax = SyntaxDefinition::load_from_str(s, true, None).unwrap();
         builder.add(arm_syntax);
 
@@ -153,106 +710,202 @@ impl KatoriApp {
         
         Self {
             gdb_adapter,
-            syntax_set: ps,
+            command_canceller,
+            latency_monitor,
+            syntax: egui_extras::syntax_highlighting::SyntectSettings {
+                ps,
+                ts: syntect::highlighting::ThemeSet::load_defaults(),
+            },
+            code_theme: egui_extras::syntax_highlighting::CodeTheme::default(),
             event_receiver,
             event_sender,
             command_sender,
             is_debugging: false,
             is_attached: false,
+            launched: false,
+            non_stop: false,
+            auto_reconnect: false,
+            reconnect_attempts: 5,
+            reconnect: None,
+            signal_alert: None,
+            exit_banner: None,
+            is_recording: false,
             current_pid: None,
             current_host_port: "localhost:1337".to_string(),
             target_state: TargetState::Detached,
             attach_mode: AttachMode::GdbServer,
-            console_output: "Welcome to Katori GDB Frontend\n".to_string(),
+            console_output: {
+                let mut console = Console::new(CONSOLE_LIMIT);
+                console.push_str("Welcome to Katori GDB Frontend\n");
+                console
+            },
+            console_timestamps: false,
+            // The log stream mostly echoes commands; opt in to see it
+            hidden_console_kinds: HashSet::from([ConsoleKind::Log]),
             error_message: String::new(),
+            running_repaint_ms: 250,
+            show_notifications: true,
             registers: Vec::new(),
             assembly_lines: Vec::new(),
+            source_files: HashMap::new(),
+            source_scroll_location: None,
             stack_frames: Vec::new(),
+            selected_frame: 0,
+            threads: ThreadList::default(),
+            watch_expressions: Vec::new(),
+            watch_values: Vec::new(),
+            changed_watches: HashSet::new(),
+            watch_tree: VarObjTree::default(),
+            changed_registers: HashSet::new(),
+            register_baseline: HashMap::new(),
+            target_arch: None,
             breakpoints: Vec::new(),
+            hit_count_baselines: HashMap::new(),
+            pending_hit_target: None,
+            run_to_breakpoint: None,
+            step_count: 10,
+            assembly_context_address: None,
+            watchpoints: Vec::new(),
             show_registers: true,
             show_assembly: true,
+            show_source: true,
             show_stack: true,
+            show_threads: true,
+            show_watch: true,
+            collapse_wide_registers: true,
+            show_vector_lanes: true,
             show_memory: false,
             show_console: true,
-            memory_address: "0x0".to_string(),
-            memory_size: 256,
-            memory_data: None,
+            show_program_output: false,
+            memory_views: vec![MemoryView::new(0, "0x0")],
+            next_memory_view_id: 1,
+            executable: None,
+            show_open_executable: false,
+            executable_input: String::new(),
+            show_run_settings: false,
+            run_arguments: String::new(),
+            run_directory: String::new(),
+            run_environment: Vec::new(),
+            removed_environment: Vec::new(),
+            show_preferences: false,
+            mi_log_enabled: false,
+            mi_log_path: std::env::temp_dir().join("katori-mi.log").display().to_string(),
+            show_references: false,
+            reference_target: None,
+            reference_results: Vec::new(),
+            reference_scan_address: "$sp".to_string(),
+            reference_scan_size: "4096".to_string(),
+            scanned_memory: None,
+            show_palette: false,
+            palette_input: String::new(),
+            disassemble_input: String::new(),
+            assembly_search: String::new(),
+            assembly_match: 0,
+            assembly_jump: false,
+            assembly_analysis: false,
+            assembly_target: None,
+            assembly_follow_pc: false,
+            show_exit_confirmation: false,
+            exit_action: None,
+            program_output: Console::new(CONSOLE_LIMIT),
+            program_input: String::new(),
+            raw_command_input: String::new(),
+            command_history: CommandHistory::default(),
+            developer_mode: false,
+            show_mi_overlay: false,
+            mi_tap_receiver,
+            mi_log: VecDeque::new(),
+            breakpoint_kind: LocationKind::Function,
             breakpoint_input: String::new(),
+            breakpoint_line_input: 1,
+            breakpoint_pending: false,
+            breakpoint_condition_input: String::new(),
+            breakpoint_ignore_input: 0,
+            breakpoint_temporary: false,
+            breakpoint_hardware: false,
+            watchpoint_input: String::new(),
+            watch_kind: WatchKind::Write,
+            watch_expression_input: String::new(),
+            continue_hits_input: 1,
             pid_input: String::new(),
+            core_executable_input: String::new(),
+            core_file_input: String::new(),
+            architecture: "auto".to_string(),
+            disassembly_flavor: "att".to_string(),
+            instruction_width: None,
+            register_format: RegisterFormat::default(),
+            register_set: RegisterSet::default(),
+            register_subset: None,
+            pinned_registers: Vec::new(),
         }
     }
 
-    /// Background task that processes GDB commands asynchronously
+    /// Background task that processes GDB commands asynchronously.
+    ///
+    /// Everything the UI has sent is drained into a `CommandQueue` before the
+    /// next command runs; see the `queue` module for the ordering guarantees.
     async fn command_processor_task(
         ctx: egui::Context,
         gdb_adapter: Arc<Mutex<GdbAdapter>>,
         mut command_receiver: tokio::sync::mpsc::UnboundedReceiver<GdbCommand>,
         event_sender: tokio::sync::mpsc::UnboundedSender<DebugEvent>,
-        mut gdb_event_receiver: tokio::sync::mpsc::UnboundedReceiver<GdbEvent>,
     ) {
         info!("Command processor task started");
         
+        let mut queue = CommandQueue::default();
         loop {
-            tokio::select! {
-                // Wait for commands from the UI
-                command = command_receiver.recv() => {
-                    if let Some(command) = command {
-                        log::debug!("Command processor received command: {:?}", command);
-
-                        // Process the command with timeout
-                        let result = tokio::time::timeout(
-                            Self::get_command_timeout(&command),
-                            Self::process_command(gdb_adapter.clone(), command.clone(), event_sender.clone())
-                        ).await;
-                            
-                        match result {
-                            Ok(Ok(())) => {
-                                info!("Command completed successfully: {command:?}");
-                                let _ = event_sender.send(DebugEvent::CommandCompleted(command.clone()));
-                            }
-                            Ok(Err(error)) => {
-                                error!("Command failed: {command:?} - {error}");
-                                let _ = event_sender.send(DebugEvent::CommandFailed(command, error));
-                            }
-                            Err(_) => {
-                                error!("Command timed out: {command:?}");
-                                let _ = event_sender.send(DebugEvent::CommandFailed(
-                                    command, 
-                                    "Command timed out".to_string()
-                                ));
-                            }
-                        }
-                    } else {
-                        // Channel closed, exit the task
-                        info!("Command processor task shutting down - command channel closed");
-                        break;
-                    }
+            // Wait for commands from the UI, then take whatever else has piled up
+            if queue.is_empty() {
+                match command_receiver.recv().await {
+                    Some(command) => queue.push(command),
+                    None => break,
                 }
-                
-                // Wait for GDB events - now using direct async receive!
-                gdb_event = gdb_event_receiver.recv() => {
-                    if let Some(event) = gdb_event {
-                        log::debug!("Command processor task received GDB event: {event:?}");
-                        // Handle the GDB event (e.g., update UI)
-                        if let GdbEvent::Async(record) = event {
-                            log::debug!("Processing async record: {:?}", record);
-                            match record.class {
-                                AsyncClass::Stopped => {
-                                    // Update target state to Stopped
-                                    let _ = event_sender.send(DebugEvent::TargetStateChanged(TargetState::Stopped));
-                                }
-                                _ => {
-                                    // Handle other async classes as needed
-                                    log::debug!("Unhandled async class: {:?}", record.class);
-                                }
-                            }
-                        }
-                    } else {
-                        // GDB event channel closed
-                        info!("Command processor task shutting down - GDB event channel closed");
-                        break;
-                    }
+            }
+            while let Ok(command) = command_receiver.try_recv() {
+                queue.push(command);
+            }
+            let Some(command) = queue.pop() else {
+                continue;
+            };
+            
+            log::debug!("Command processor running command: {:?} ({} queued)", command, queue.len());
+            Self::dispatch_command(gdb_adapter.clone(), command, &event_sender).await;
+            
+            // Request repaint to update UI with new events
+            ctx.request_repaint();
+        }
+        
+        // Channel closed, exit the task
+        info!("Command processor task shutting down - command channel closed");
+    }
+    
+    /// Background task that turns GDB events into UI events.
+    ///
+    /// Runs separately from the command processor so stops and console output
+    /// still reach the UI while a long command holds the adapter.
+    async fn gdb_event_task(
+        ctx: egui::Context,
+        mut gdb_event_receiver: tokio::sync::broadcast::Receiver<GdbEvent>,
+        event_sender: tokio::sync::mpsc::UnboundedSender<DebugEvent>,
+    ) {
+        info!("GDB event task started");
+        
+        loop {
+            let event = match gdb_event_receiver.recv().await {
+                Ok(event) => event,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("GDB event task fell behind and missed {missed} GDB events");
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    info!("GDB event task shutting down - GDB event channel closed");
+                    break;
                 }
+            };
+            
+            log::debug!("GDB event task received GDB event: {event:?}");
+            for debug_event in Self::gdb_event_to_debug_events(event) {
+                let _ = event_sender.send(debug_event);
             }
             
             // Request repaint to update UI with new events
@@ -260,17 +913,202 @@ impl KatoriApp {
         }
     }
     
+    /// Map a GDB event to the events the UI should see
+    fn gdb_event_to_debug_events(event: GdbEvent) -> Vec<DebugEvent> {
+        match event {
+            GdbEvent::Async(record) => {
+                log::debug!("Processing async record: {:?}", record);
+                let notification = describe_notification(&record).map(DebugEvent::Notification);
+                let events = match record.class {
+                    // Report why we stopped, then update target state (which triggers a refresh)
+                    AsyncClass::Stopped => Self::stop_events(&record),
+                    AsyncClass::BreakpointModified => record.results.get("bkpt")
+                        .and_then(|bkpt| bkpt.as_tuple())
+                        .and_then(Breakpoint::from_tuple)
+                        .map(DebugEvent::BreakpointModified)
+                        .into_iter()
+                        .collect(),
+                    AsyncClass::BreakpointDeleted => record.results.get("id")
+                        .and_then(|id| id.as_string())
+                        .map(|id| DebugEvent::BreakpointDeleted(id.to_string()))
+                        .into_iter()
+                        .collect(),
+                    AsyncClass::RecordStarted => vec![DebugEvent::RecordingChanged(true)],
+                    AsyncClass::RecordStopped => vec![DebugEvent::RecordingChanged(false)],
+                    AsyncClass::Running => record.results.get("thread-id")
+                        .and_then(|id| id.as_string())
+                        .map(|id| DebugEvent::ThreadRunning(id.parse().ok()))
+                        .into_iter()
+                        .collect(),
+                    AsyncClass::MemoryChanged => {
+                        let field = |name: &str| record.results.get(name).and_then(Value::as_address);
+                        field("addr").zip(field("len"))
+                            .map(|(address, len)| DebugEvent::MemoryChanged(address, len))
+                            .into_iter()
+                            .collect()
+                    }
+                    _ => {
+                        // Handle other async classes as needed
+                        log::debug!("Unhandled async class: {:?}", record.class);
+                        Vec::new()
+                    }
+                };
+                notification.into_iter().chain(events).collect()
+            }
+            // The program's own output may mention a lost connection; only GDB's streams report one
+            GdbEvent::Stream(stream) if stream.stream_type != StreamType::Target && is_remote_disconnect(&stream.content) => {
+                let kind = if stream.stream_type == StreamType::Console { ConsoleKind::Gdb } else { ConsoleKind::Log };
+                vec![DebugEvent::StreamOutput(kind, stream.content), DebugEvent::RemoteConnectionLost]
+            }
+            // Console output answers CLI commands typed into the console
+            GdbEvent::Stream(stream) if stream.stream_type == StreamType::Console => {
+                vec![DebugEvent::StreamOutput(ConsoleKind::Gdb, stream.content)]
+            }
+            GdbEvent::Stream(stream) if stream.stream_type == StreamType::Target => {
+                vec![DebugEvent::ProgramOutput(stream.content)]
+            }
+            // The adapter forwards GDB's stderr as prefixed log lines without a newline
+            GdbEvent::Stream(stream) if stream.content.starts_with(GDB_STDERR_PREFIX) => {
+                vec![DebugEvent::StreamOutput(ConsoleKind::Error, format!("{}\n", stream.content))]
+            }
+            GdbEvent::Stream(stream) => {
+                vec![DebugEvent::StreamOutput(ConsoleKind::Log, stream.content)]
+            }
+            GdbEvent::ConnectionLost => vec![DebugEvent::GdbConnectionLost],
+            _ => Vec::new(),
+        }
+    }
+    
+    /// Map a `*stopped` async record to the events the UI should see
+    fn stop_events(record: &AsyncRecord) -> Vec<DebugEvent> {
+        let mut events = Vec::new();
+        let mut state = TargetState::Stopped;
+        if let Some(info) = StopInfo::from_record(record) {
+            events.push(DebugEvent::ConsoleMessage(format!("{}\n", describe_stop(&info))));
+            match info.reason {
+                Some(StopReason::Exited | StopReason::ExitedNormally) => {
+                    let code = info.exit_code.unwrap_or(0);
+                    events.push(DebugEvent::ProgramExited(code));
+                    state = TargetState::Exited(ExitStatus::Code(code));
+                }
+                Some(StopReason::ExitedSignalled) => {
+                    let signal = info.signal_name.clone().unwrap_or_else(|| "a signal".to_string());
+                    state = TargetState::Exited(ExitStatus::Signal(signal));
+                }
+                Some(StopReason::BreakpointHit) => {
+                    if let Some(number) = info.breakpoint_number {
+                        events.push(DebugEvent::BreakpointHit(number));
+                    }
+                }
+                Some(StopReason::SignalReceived) => {
+                    events.push(DebugEvent::SignalReceived(SignalAlert {
+                        name: info.signal_name.unwrap_or_else(|| "a signal".to_string()),
+                        meaning: info.signal_meaning,
+                        address: info.frame.map(|frame| frame.address),
+                    }));
+                }
+                _ => {}
+            }
+        }
+        events.push(DebugEvent::TargetStateChanged(state));
+        events
+    }
+    
     /// Get appropriate timeout for different command types
     fn get_command_timeout(command: &GdbCommand) -> std::time::Duration {
         match command {
             GdbCommand::Continue => std::time::Duration::from_secs(u64::MAX), // Effectively no timeout for continue
             GdbCommand::StepOver | GdbCommand::StepInto | GdbCommand::StepOut => std::time::Duration::from_secs(10),
+            GdbCommand::StepInstructions(_) => std::time::Duration::from_secs(10),
+            GdbCommand::RunToAddress(_) => std::time::Duration::from_secs(u64::MAX), // Runs like continue
+            GdbCommand::ReverseContinue => std::time::Duration::from_secs(u64::MAX), // Runs like continue
+            GdbCommand::ReverseStepInto | GdbCommand::ReverseStepOver => std::time::Duration::from_secs(10),
+            GdbCommand::SetRecording(_) => std::time::Duration::from_secs(5),
             GdbCommand::Interrupt => std::time::Duration::from_secs(10),
             GdbCommand::RefreshDebugInfo => std::time::Duration::from_secs(5),
             GdbCommand::SetBreakpoint(_) => std::time::Duration::from_secs(5),
-            GdbCommand::ReadMemory(_, _) => std::time::Duration::from_secs(10),
+            GdbCommand::RemoveBreakpoint(_) | GdbCommand::ToggleBreakpoint(_, _) => std::time::Duration::from_secs(5),
+            GdbCommand::ToggleBreakpointLocation(_, _) => std::time::Duration::from_secs(5),
+            GdbCommand::RefreshBreakpoints => std::time::Duration::from_secs(5),
+            GdbCommand::ContinueUntilHit(_, _) => std::time::Duration::from_secs(u64::MAX), // Runs like continue
+            GdbCommand::SetIgnoreCount(_, _) => std::time::Duration::from_secs(5),
+            GdbCommand::EvaluateWatches(_) => std::time::Duration::from_secs(10),
+            GdbCommand::ExpandWatch(_) | GdbCommand::ListVarObjChildren(_, _, _) => std::time::Duration::from_secs(10),
+            GdbCommand::UpdateVarObjs => std::time::Duration::from_secs(10),
+            GdbCommand::DeleteVarObj(_) => std::time::Duration::from_secs(5),
+            GdbCommand::SetWatchpoint(_, _) => std::time::Duration::from_secs(5),
+            GdbCommand::ReadMemory(_, _, _) => std::time::Duration::from_secs(10),
+            GdbCommand::ReadMemoryChunk(_, _, _, _, _) => std::time::Duration::from_secs(10),
+            GdbCommand::WriteMemory(_, _) => std::time::Duration::from_secs(10),
+            GdbCommand::ScanMemory(_, _) => std::time::Duration::from_secs(30),
             GdbCommand::StartSession | GdbCommand::StopSession => std::time::Duration::from_secs(15),
             GdbCommand::Attach(_, _) | GdbCommand::Detach => std::time::Duration::from_secs(15),
+            GdbCommand::Reconnect(_) => std::time::Duration::from_secs(15),
+            GdbCommand::LoadExecutable(_) => std::time::Duration::from_secs(30),
+            GdbCommand::LoadCore(_, _) => std::time::Duration::from_secs(30),
+            GdbCommand::Run(_, _) => std::time::Duration::from_secs(10),
+            GdbCommand::SetEnvironment(_, _) => std::time::Duration::from_secs(10),
+            GdbCommand::Raw(_) => std::time::Duration::from_secs(10),
+            GdbCommand::SendProgramInput(_) => std::time::Duration::from_secs(5),
+            GdbCommand::GoTo(_, _) => std::time::Duration::from_secs(10),
+            GdbCommand::DisassembleFunction(_) => std::time::Duration::from_secs(10),
+            GdbCommand::SelectThread(_) => std::time::Duration::from_secs(5),
+            GdbCommand::InterruptThread(_) => std::time::Duration::from_secs(10),
+            GdbCommand::SetNonStop(_) => std::time::Duration::from_secs(5),
+            GdbCommand::SelectFrame(_) => std::time::Duration::from_secs(5),
+            GdbCommand::SetArchitecture(_) => std::time::Duration::from_secs(5),
+            GdbCommand::SetInstructionWidth(_) => std::time::Duration::from_secs(5),
+            GdbCommand::SetDisassemblyFlavor(_) => std::time::Duration::from_secs(5),
+            GdbCommand::SetRegisterFormat(_) | GdbCommand::SetRegisterSet(_) => std::time::Duration::from_secs(5),
+            GdbCommand::SetRegisterSubset(_) => std::time::Duration::from_secs(10),
+            GdbCommand::SetMiLog(_) => std::time::Duration::from_secs(5),
+            GdbCommand::Restart => std::time::Duration::from_secs(5),
+            GdbCommand::Kill => std::time::Duration::from_secs(5),
+        }
+    }
+    
+    /// Run a single UI command and report its outcome as events
+    async fn dispatch_command(
+        gdb_adapter: Arc<Mutex<GdbAdapter>>,
+        command: GdbCommand,
+        event_sender: &tokio::sync::mpsc::UnboundedSender<DebugEvent>,
+    ) {
+        // Shortcuts and stale UI state can issue commands with no session running
+        if command.requires_session() && !gdb_adapter.lock().await.is_running() {
+            info!("Ignoring command without an active session: {command:?}");
+            let _ = event_sender.send(DebugEvent::ConsoleMessage(NO_SESSION_MESSAGE.to_string()));
+            return;
+        }
+        
+        // A step queued behind Continue would otherwise wait for a stop that may never come
+        if command.is_step() && gdb_adapter.lock().await.is_target_running() {
+            info!("Rejecting step while the target is running: {command:?}");
+            let _ = event_sender.send(DebugEvent::CommandFailed(command, "Target is running; interrupt it first".to_string()));
+            return;
+        }
+        
+        // Process the command with timeout
+        let result = tokio::time::timeout(
+            Self::get_command_timeout(&command),
+            Self::process_command(gdb_adapter, command.clone(), event_sender.clone())
+        ).await;
+            
+        match result {
+            Ok(Ok(())) => {
+                info!("Command completed successfully: {command:?}");
+                let _ = event_sender.send(DebugEvent::CommandCompleted(command));
+            }
+            Ok(Err(error)) => {
+                error!("Command failed: {command:?} - {error}");
+                let _ = event_sender.send(DebugEvent::CommandFailed(command, error));
+            }
+            Err(_) => {
+                error!("Command timed out: {command:?}");
+                let _ = event_sender.send(DebugEvent::CommandFailed(
+                    command, 
+                    "Command timed out".to_string()
+                ));
+            }
         }
     }
     
@@ -304,14 +1142,146 @@ impl KatoriApp {
                     .map_err(|e| format!("Step out failed: {e}"))?;
                 Ok(())
             }
+            GdbCommand::StepInstructions(count) => {
+                adapter.step_instructions(count).await
+                    .map_err(|e| format!("Step failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::RunToAddress(address) => {
+                let breakpoint = adapter.set_temporary_breakpoint_at_address(&address).await
+                    .map_err(|e| format!("Run to {address} failed: {e}"))?;
+                // The UI must know the breakpoint before the stop that hits it can arrive
+                let _ = event_sender.send(DebugEvent::RunToAddressStarted(breakpoint.number.clone()));
+                if let Err(e) = adapter.continue_execution().await {
+                    if let Ok(number) = breakpoint.number.parse() {
+                        let _ = adapter.remove_breakpoint(number).await;
+                    }
+                    return Err(format!("Run to {address} failed: {e}"));
+                }
+                Ok(())
+            }
+            GdbCommand::ReverseContinue => {
+                adapter.reverse_continue().await
+                    .map_err(|e| format!("Reverse continue failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::ReverseStepInto => {
+                adapter.reverse_step_instruction().await
+                    .map_err(|e| format!("Reverse step into failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::ReverseStepOver => {
+                adapter.reverse_next_instruction().await
+                    .map_err(|e| format!("Reverse step over failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::SetRecording(true) => {
+                adapter.start_recording().await
+                    .map_err(|e| format!("Start recording failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::SetRecording(false) => {
+                adapter.stop_recording().await
+                    .map_err(|e| format!("Stop recording failed: {e}"))?;
+                Ok(())
+            }
             GdbCommand::Interrupt => {
                 adapter.interrupt().await
                     .map_err(|e| format!("Interrupt failed: {e}"))?;
                 Ok(())
             }
-            GdbCommand::SetBreakpoint(location) => {
-                adapter.set_breakpoint(&location).await
-                    .map_err(|e| format!("Set breakpoint failed: {e}"))?;
+            GdbCommand::SetBreakpoint(spec) => {
+                let result = adapter.set_breakpoint_spec(&spec).await
+                    .map_err(|e| format!("Set breakpoint failed: {e}{}", error_hint(&e)))?;
+                if let Some(breakpoint) = result.results.get("bkpt")
+                    .and_then(|v| v.as_tuple())
+                    .and_then(Breakpoint::from_tuple)
+                {
+                    let _ = event_sender.send(DebugEvent::BreakpointInserted(breakpoint));
+                }
+                Ok(())
+            }
+            GdbCommand::RemoveBreakpoint(number) => {
+                adapter.remove_breakpoint(number).await
+                    .map_err(|e| format!("Remove breakpoint failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::RefreshBreakpoints => Self::send_breakpoint_list(&mut adapter, &event_sender).await,
+            GdbCommand::ToggleBreakpoint(number, enabled) => {
+                if enabled {
+                    adapter.enable_breakpoint(number).await
+                } else {
+                    adapter.disable_breakpoint(number).await
+                }.map_err(|e| format!("Toggle breakpoint failed: {e}"))?;
+                Self::send_breakpoint_list(&mut adapter, &event_sender).await
+            }
+            GdbCommand::ToggleBreakpointLocation(location, enabled) => {
+                if enabled {
+                    adapter.enable_breakpoint_location(&location).await
+                } else {
+                    adapter.disable_breakpoint_location(&location).await
+                }.map_err(|e| format!("Toggle breakpoint failed: {e}"))?;
+                Self::send_breakpoint_list(&mut adapter, &event_sender).await
+            }
+            GdbCommand::ContinueUntilHit(number, hits) => {
+                // GDB stops on the first hit past the ignore count
+                adapter.set_ignore_count(number, hits.saturating_sub(1)).await
+                    .map_err(|e| format!("Set ignore count failed: {e}"))?;
+                adapter.continue_execution().await
+                    .map_err(|e| format!("Continue failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::SetIgnoreCount(number, count) => {
+                adapter.set_ignore_count(number, count).await
+                    .map_err(|e| format!("Set ignore count failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::EvaluateWatches(expressions) => {
+                let values: Vec<Result<String, String>> = adapter.evaluate_expressions(&expressions).await
+                    .into_iter()
+                    .map(|result| result
+                        .map(|r| r.results.get("value").and_then(|v| v.as_string()).unwrap_or_default().to_string())
+                        .map_err(|e| e.to_string()))
+                    .collect();
+                let _ = event_sender.send(DebugEvent::WatchesUpdated(expressions.into_iter().zip(values).collect()));
+                Ok(())
+            }
+            GdbCommand::ExpandWatch(expression) => {
+                let varobj = adapter.create_varobj(&expression).await
+                    .map_err(|e| format!("Expand watch failed: {e}"))?;
+                let name = varobj.name.clone();
+                let has_children = varobj.has_children();
+                let _ = event_sender.send(DebugEvent::VarObjCreated(expression, varobj));
+                if has_children {
+                    let children = adapter.list_children(&name, Some((0, VAROBJ_PAGE_SIZE))).await
+                        .map_err(|e| format!("Expand watch failed: {e}"))?;
+                    let _ = event_sender.send(DebugEvent::VarObjChildren(name, 0, children));
+                }
+                Ok(())
+            }
+            GdbCommand::ListVarObjChildren(name, from, to) => {
+                let children = adapter.list_children(&name, Some((from, to))).await
+                    .map_err(|e| format!("List children failed: {e}"))?;
+                let _ = event_sender.send(DebugEvent::VarObjChildren(name, from, children));
+                Ok(())
+            }
+            GdbCommand::UpdateVarObjs => {
+                let changes = adapter.update_varobjs().await
+                    .map_err(|e| format!("Update watches failed: {e}"))?;
+                let _ = event_sender.send(DebugEvent::VarObjsUpdated(changes));
+                Ok(())
+            }
+            GdbCommand::DeleteVarObj(name) => {
+                adapter.delete_varobj(&name).await
+                    .map_err(|e| format!("Delete variable object failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::SetWatchpoint(expression, kind) => {
+                let result = adapter.set_watchpoint(&expression, kind).await
+                    .map_err(|e| format!("Set watchpoint failed: {e}"))?;
+                if let Some(watchpoint) = Watchpoint::from_result(&result) {
+                    let _ = event_sender.send(DebugEvent::WatchpointInserted(watchpoint));
+                }
                 Ok(())
             }
             GdbCommand::RefreshDebugInfo => {
@@ -321,7 +1291,95 @@ impl KatoriApp {
                     .map_err(|e| format!("RefreshDebugInfo failed: {e}"))?;
                 Ok(())
             }
-            GdbCommand::ReadMemory(address, size) => {
+            GdbCommand::WriteMemory(address, bytes) => {
+                adapter.write_memory(&address, &bytes).await
+                    .map_err(|e| format!("Write memory failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::SetArchitecture(arch) => {
+                adapter.set_architecture(&arch).await
+                    .map_err(|e| format!("Set architecture failed: {e}"))?;
+                let _ = event_sender.send(DebugEvent::ConsoleMessage(format!("Architecture set to {arch}\n")));
+                // Before attaching it's only remembered, and applied once GDB starts
+                if adapter.is_running() {
+                    Self::send_target_architecture(&mut adapter, &event_sender).await;
+                }
+                Ok(())
+            }
+            GdbCommand::SetDisassemblyFlavor(flavor) => {
+                adapter.set_disassembly_flavor(&flavor).await
+                    .map_err(|e| format!("Set disassembly flavor failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::SetInstructionWidth(width) => {
+                adapter.set_instruction_width(width);
+                Ok(())
+            }
+            GdbCommand::SetRegisterFormat(format) => {
+                adapter.set_register_format(format);
+                if adapter.is_running() {
+                    let arch = adapter.target_architecture().await;
+                    Self::send_registers(&mut adapter, arch.as_deref(), &event_sender).await;
+                }
+                Ok(())
+            }
+            GdbCommand::SetRegisterSubset(numbers) => {
+                let previous = adapter.register_subset().map(<[u32]>::to_vec);
+                adapter.set_register_subset(numbers);
+                // Registers coming into view were skipped at the last stop
+                if let Some(previous) = previous.filter(|_| adapter.is_running() && !adapter.is_target_running()) {
+                    let result = adapter.get_registers().await
+                        .map_err(|e| format!("Reading registers failed: {e}"))?;
+                    if let Some(registers) = parse_registers(&result, &[]) {
+                        let shown = registers.into_iter().filter(|reg| !previous.contains(&reg.number)).collect();
+                        // Not a new stop, so GDB's list of changes doesn't apply
+                        let _ = event_sender.send(DebugEvent::RegisterValuesUpdated(shown, None));
+                    }
+                }
+                Ok(())
+            }
+            GdbCommand::SetMiLog(path) => {
+                match path {
+                    Some(path) => adapter.set_mi_log(std::path::Path::new(&path))
+                        .map_err(|e| format!("MI logging failed: {e}"))?,
+                    None => adapter.close_mi_log(),
+                }
+                Ok(())
+            }
+            GdbCommand::SetRegisterSet(set) => {
+                adapter.set_register_set(set);
+                if adapter.is_running() {
+                    let arch = adapter.target_architecture().await;
+                    Self::send_registers(&mut adapter, arch.as_deref(), &event_sender).await;
+                }
+                Ok(())
+            }
+            GdbCommand::SelectThread(id) => {
+                adapter.select_thread(id).await
+                    .map_err(|e| format!("Select thread failed: {e}"))?;
+                Self::send_refresh_debug_info_internal(adapter, event_sender).await
+                    .map_err(|e| format!("RefreshDebugInfo failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::InterruptThread(id) => {
+                adapter.interrupt_thread(id).await
+                    .map_err(|e| format!("Interrupt thread failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::SetNonStop(enabled) => {
+                adapter.set_non_stop(enabled).await
+                    .map_err(|e| format!("Set non-stop mode failed: {e}"))?;
+                let _ = event_sender.send(DebugEvent::ConsoleMessage(format!("Non-stop mode {}\n", if enabled { "on" } else { "off" })));
+                Ok(())
+            }
+            GdbCommand::SelectFrame(level) => {
+                adapter.select_frame(level).await
+                    .map_err(|e| format!("Select frame failed: {e}"))?;
+                Self::send_refresh_debug_info_internal(adapter, event_sender).await
+                    .map_err(|e| format!("RefreshDebugInfo failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::ReadMemory(id, address, size) => {
                 match adapter.read_memory(&address, size).await {
                     Ok(result) => {
                         info!("Memory read command completed: {result:?}");
@@ -330,18 +1388,18 @@ impl KatoriApp {
                         if let Some(memory_value) = result.results.get("memory") {
                             if let Some(memory_result) = parse_memory(memory_value) {
                                 // Send the parsed memory data
-                                let _ = event_sender.send(DebugEvent::MemoryRead(memory_result));
+                                let _ = event_sender.send(DebugEvent::MemoryRead(id, memory_result));
                             } else {
                                 error!("Failed to parse memory data from response");
-                                let _ = event_sender.send(DebugEvent::MemoryReadFailed("Failed to parse memory data".to_string()));
+                                let _ = event_sender.send(DebugEvent::MemoryReadFailed(id, "Failed to parse memory data".to_string()));
                             }
                         } else {
-                            let _ = event_sender.send(DebugEvent::MemoryReadFailed("No memory data in response".to_string()));
+                            let _ = event_sender.send(DebugEvent::MemoryReadFailed(id, "No memory data in response".to_string()));
                         }
                     }
                     Err(e) => {
                         error!("Failed to read memory: {e}");
-                        let _ = event_sender.send(DebugEvent::MemoryReadFailed(e.to_string()));
+                        let _ = event_sender.send(DebugEvent::MemoryReadFailed(id, e.to_string()));
                     }
                 }
                 Ok(())
@@ -363,20 +1421,50 @@ impl KatoriApp {
                         .map_err(|e| format!("Failed to start GDB: {e}"))?;
                 }
                 
-                match mode {
-                    AttachMode::GdbServer => {
-                        adapter.attach_to_gdbserver(&target).await
-                            .map_err(|e| format!("Attach to GDB server failed: {e}"))?;
+                let attach_target = match mode {
+                    AttachMode::GdbServer => Ok(AttachTarget::GdbServer(target)),
+                    AttachMode::Process => target.parse::<u32>()
+                        .map(AttachTarget::Process)
+                        .map_err(|_| "Invalid PID format".to_string()),
+                    AttachMode::CoreDump => Err("Core files are loaded with LoadCore".to_string()),
+                };
+                
+                // Attach and make sure the target is stopped so the follow-up refresh succeeds
+                let attached = match attach_target {
+                    Ok(attach_target) => adapter.attach_and_stop(&attach_target).await
+                        .map(|_| match attach_target {
+                            AttachTarget::Process(pid) => Some(pid),
+                            AttachTarget::GdbServer(_) => None,
+                        })
+                        .map_err(|e| match attach_target {
+                            AttachTarget::Process(_) => format!("Attach to process failed: {e}"),
+                            AttachTarget::GdbServer(_) => format!("Attach to GDB server failed: {e}"),
+                        }),
+                    Err(error) => Err(error),
+                };
+                
+                match attached {
+                    Ok(pid) => {
                         // Send success event
+                        let _ = event_sender.send(DebugEvent::AttachSuccess(pid));
+                        Self::send_target_architecture(&mut adapter, &event_sender).await;
+                        Ok(())
+                    }
+                    Err(error) => {
+                        let _ = event_sender.send(DebugEvent::AttachFailed(error.clone()));
+                        Err(error)
+                    }
+                }
+            }
+            GdbCommand::Reconnect(host_port) => {
+                match adapter.attach_and_stop(&AttachTarget::GdbServer(host_port)).await {
+                    Ok(_) => {
                         let _ = event_sender.send(DebugEvent::AttachSuccess(None));
+                        Self::send_target_architecture(&mut adapter, &event_sender).await;
                     }
-                    AttachMode::Process => {
-                        let pid: u32 = target.parse()
-                            .map_err(|_| "Invalid PID format".to_string())?;
-                        adapter.attach_to_process(pid).await
-                            .map_err(|e| format!("Attach to process failed: {e}"))?;
-                        // Send success event
-                        let _ = event_sender.send(DebugEvent::AttachSuccess(Some(pid)));
+                    // The UI decides whether to retry, so this is not a command failure
+                    Err(e) => {
+                        let _ = event_sender.send(DebugEvent::ReconnectFailed(e.to_string()));
                     }
                 }
                 Ok(())
@@ -388,52 +1476,329 @@ impl KatoriApp {
                 let _ = event_sender.send(DebugEvent::DetachSuccess);
                 Ok(())
             }
-        }
-    }
-    
-    /// Internal helper to send debug info refresh events
-    async fn send_refresh_debug_info_internal(
-        mut adapter: tokio::sync::MutexGuard<'_, GdbAdapter>,
-        event_sender: tokio::sync::mpsc::UnboundedSender<DebugEvent>,
-    ) -> Result<(), String> {
-        // Get register names first, then register values
-        let mut register_names = Vec::new();
-        debug!("send_refresh_debug_info_internal: Getting register names...");
-        match adapter.get_register_names().await {
-            Ok(names_result) => {
-                if let Some(Value::List(names_list)) = names_result.results.get("register-names") {
-                    for (i, name_value) in names_list.iter().enumerate() {
-                        if let Some(name) = name_value.as_string() {
-                            register_names.push((i, name.to_string()));
-                        }
-                    }
-                }
-                debug!("send_refresh_debug_info_internal: Parsed {} register names", register_names.len());
-            }
-            Err(e) => {
-                error!("send_refresh_debug_info_internal: Failed to get register names: {e}");
+            GdbCommand::SendProgramInput(text) => {
+                adapter.write_inferior_input(&text)
+                    .map_err(|e| format!("Send program input failed: {e}"))?;
+                Ok(())
             }
-        }
-        
-        // Get registers
-        debug!("send_refresh_debug_info_internal: Getting registers...");
-        match adapter.get_registers().await {
-            Ok(result) => {
-                if let Some(registers) = Self::parse_registers(&result, &register_names) {
-                    let _ = event_sender.send(DebugEvent::RegistersUpdated(registers));
+            GdbCommand::Raw(command) => {
+                let result = adapter.send_command(&raw_to_mi(&command)).await
+                    .map_err(|e| format!("{e}"))?;
+                let _ = event_sender.send(DebugEvent::ConsoleMessage(format!("{}\n", format_mi_result(&result))));
+                if loads_core_file(&command) {
+                    let _ = event_sender.send(DebugEvent::TargetStateChanged(TargetState::CoreDump));
                 }
+                Ok(())
             }
-            Err(e) => {
-                error!("send_refresh_debug_info_internal: Failed to get registers: {e}");
+            GdbCommand::Restart => {
+                // With confirmations off, -exec-run kills a live program and starts it again,
+                // but only once it's stopped; breakpoints carry over to the new run
+                adapter.stop_target().await
+                    .map_err(|e| format!("Restart failed: {e}"))?;
+                adapter.run_program().await
+                    .map_err(|e| format!("Restart failed: {e}"))?;
+                Ok(())
             }
+            GdbCommand::Kill => {
+                adapter.stop_target().await
+                    .map_err(|e| format!("Kill failed: {e}"))?;
+                adapter.kill().await
+                    .map_err(|e| format!("Kill failed: {e}"))?;
+                // GDB reports no *stopped record for a kill
+                let _ = event_sender.send(DebugEvent::TargetStateChanged(TargetState::Exited(ExitStatus::Killed)));
+                Ok(())
+            }
+            GdbCommand::LoadExecutable(path) => {
+                if !adapter.is_running() {
+                    adapter.start_session().await
+                        .map_err(|e| format!("Failed to start GDB: {e}"))?;
+                }
+                adapter.load_executable(&path).await
+                    .map_err(|e| format!("Load executable failed: {e}"))?;
+                let _ = event_sender.send(DebugEvent::ExecutableLoaded(path));
+                Ok(())
+            }
+            GdbCommand::LoadCore(executable, core) => {
+                if !adapter.is_running() {
+                    adapter.start_session().await
+                        .map_err(|e| format!("Failed to start GDB: {e}"))?;
+                }
+                adapter.load_core(&executable, &core).await
+                    .map_err(|e| format!("Load core file failed: {e}"))?;
+                let _ = event_sender.send(DebugEvent::ExecutableLoaded(executable));
+                Self::send_target_architecture(&mut adapter, &event_sender).await;
+                // Refreshes registers, stack and disassembly at the crash site
+                let _ = event_sender.send(DebugEvent::TargetStateChanged(TargetState::CoreDump));
+                Ok(())
+            }
+            GdbCommand::SetEnvironment(variables, removed) => {
+                for name in removed {
+                    adapter.unset_environment(&name).await
+                        .map_err(|e| format!("Unset environment failed: {e}"))?;
+                }
+                for (name, value) in variables {
+                    adapter.set_environment(&name, &value).await
+                        .map_err(|e| format!("Set environment failed: {e}"))?;
+                }
+                Ok(())
+            }
+            GdbCommand::Run(args, cwd) => {
+                // Give the program a terminal of its own once per session, so its
+                // output reaches the Program Output panel and it can read input
+                if !adapter.has_inferior_tty() {
+                    match adapter.create_inferior_tty().await {
+                        Ok(path) => {
+                            let _ = event_sender.send(DebugEvent::ConsoleMessage(format!("Program terminal: {path}\n")));
+                        }
+                        Err(e) => {
+                            let _ = event_sender.send(DebugEvent::ConsoleMessage(format!("Program output stays on GDB's terminal: {e}\n")));
+                        }
+                    }
+                }
+                adapter.set_args(&args).await
+                    .map_err(|e| format!("Set arguments failed: {e}"))?;
+                if let Some(dir) = cwd {
+                    adapter.set_cwd(&dir).await
+                        .map_err(|e| format!("Set working directory failed: {e}"))?;
+                }
+                adapter.run_program().await
+                    .map_err(|e| format!("Run failed: {e}"))?;
+                Ok(())
+            }
+            GdbCommand::DisassembleFunction(function) => {
+                let result = match adapter.disassemble_function(&function).await {
+                    Ok(result) => result,
+                    // Not a symbol GDB knows; a `0x` number is still an address
+                    Err(e) => match parse_address(&function) {
+                        Some(address) => adapter.disassemble_at_address(&format!("0x{address:x}"), 80).await
+                            .map_err(|e| format!("Disassemble failed: {e}"))?,
+                        None => return Err(format!("No function {function}: {e}{}", error_hint(&e))),
+                    },
+                };
+                if let Some(assembly_lines) = parse_assembly(&result) {
+                    let _ = event_sender.send(DebugEvent::AssemblyUpdated(assembly_lines));
+                }
+                Ok(())
+            }
+            GdbCommand::GoTo(expression, target) => {
+                let address = Self::resolve_address(&mut adapter, &expression).await?;
+                let _ = event_sender.send(DebugEvent::ConsoleMessage(format!("{expression} resolved to {address}\n")));
+                match target {
+                    GoToTarget::Disassembly => {
+                        let result = adapter.disassemble_at_address(&address, 80).await
+                            .map_err(|e| format!("Disassemble failed: {e}"))?;
+                        if let Some(assembly_lines) = parse_assembly(&result) {
+                            let _ = event_sender.send(DebugEvent::AssemblyUpdated(assembly_lines));
+                        }
+                    }
+                    GoToTarget::Memory => {
+                        let _ = event_sender.send(DebugEvent::MemoryGoTo(address));
+                    }
+                    GoToTarget::Breakpoint => {
+                        let result = adapter.set_breakpoint_at_address(&address).await
+                            .map_err(|e| format!("Set breakpoint failed: {e}{}", error_hint(&e)))?;
+                        if let Some(breakpoint) = result.results.get("bkpt")
+                            .and_then(|v| v.as_tuple())
+                            .and_then(Breakpoint::from_tuple)
+                        {
+                            let _ = event_sender.send(DebugEvent::BreakpointInserted(breakpoint));
+                        }
+                    }
+                    GoToTarget::References => {
+                        let _ = event_sender.send(DebugEvent::FindReferences(address));
+                    }
+                }
+                Ok(())
+            }
+            GdbCommand::ReadMemoryChunk(id, generation, address, offset, len) => {
+                let result = adapter.read_memory_at(&address, offset, len).await
+                    .map_err(|e| format!("Memory read failed: {e}"))?;
+                let chunk = result.results.get("memory")
+                    .and_then(parse_memory)
+                    .ok_or_else(|| "Memory read failed: no memory data in response".to_string())?;
+                let _ = event_sender.send(DebugEvent::MemoryChunk(id, generation, offset, chunk));
+                Ok(())
+            }
+            GdbCommand::ScanMemory(address, size) => {
+                let result = adapter.read_memory(&address, size).await
+                    .map_err(|e| format!("Memory scan failed: {e}"))?;
+                let memory = result.results.get("memory")
+                    .and_then(parse_memory)
+                    .ok_or_else(|| "Memory scan failed: no memory data in response".to_string())?;
+                let _ = event_sender.send(DebugEvent::ReferenceScanRead(memory));
+                Ok(())
+            }
+        }
+    }
+    
+    /// Read registers in the adapter's register set. The core set depends on
+    /// `arch`; without a known architecture all registers are read.
+    async fn send_registers(
+        adapter: &mut GdbAdapter,
+        arch: Option<&str>,
+        event_sender: &tokio::sync::mpsc::UnboundedSender<DebugEvent>,
+    ) {
+        // Get register names first, then register values
+        let mut register_names = Vec::new();
+        debug!("send_registers: Getting register names...");
+        match adapter.get_register_names().await {
+            Ok(names_result) => {
+                register_names = parse_register_names(&names_result);
+                debug!("send_registers: Parsed {} register names", register_names.len());
+            }
+            Err(e) => {
+                error!("send_registers: Failed to get register names: {e}");
+            }
+        }
+        
+        if adapter.register_set() == RegisterSet::Core {
+            adapter.set_core_registers(core_register_numbers(arch, &register_names));
+        }
+        
+        // Get registers
+        debug!("send_registers: Getting registers...");
+        match adapter.get_registers().await {
+            Ok(result) => {
+                if let Some(registers) = parse_registers(&result, &register_names) {
+                    // Disassembly that follows sizes its range from this
+                    let thumb = is_thumb_state(arch, &registers, adapter.register_format());
+                    adapter.set_thumb(thumb);
+                    let changed = match adapter.get_changed_registers().await {
+                        Ok(changed) => Some(changed),
+                        Err(e) => {
+                            debug!("send_registers: Changed registers unavailable ({e}), comparing values instead");
+                            None
+                        }
+                    };
+                    if adapter.register_subset().is_some() {
+                        let _ = event_sender.send(DebugEvent::RegisterValuesUpdated(registers, changed));
+                    } else {
+                        let _ = event_sender.send(DebugEvent::RegistersUpdated(registers, changed));
+                    }
+                }
+            }
+            Err(e) => {
+                error!("send_registers: Failed to get registers: {e}");
+            }
+        }
+    }
+    
+    /// Re-read the breakpoint table so the panel matches GDB after a change
+    async fn send_breakpoint_list(
+        adapter: &mut GdbAdapter,
+        event_sender: &tokio::sync::mpsc::UnboundedSender<DebugEvent>,
+    ) -> Result<(), String> {
+        let result = adapter.list_breakpoints().await
+            .map_err(|e| format!("List breakpoints failed: {e}"))?;
+        let breakpoints = Breakpoint::list_from_result(&result)
+            .into_iter()
+            .filter(|bp| !bp.is_watchpoint())
+            .collect();
+        let _ = event_sender.send(DebugEvent::BreakpointsUpdated(breakpoints));
+        Ok(())
+    }
+    
+    /// Resolve user input to an address: hex literals are used as-is, bare
+    /// symbol names go to the symbol itself, and other expressions go through
+    /// `-data-evaluate-expression`
+    async fn resolve_address(adapter: &mut GdbAdapter, expression: &str) -> Result<String, String> {
+        if let Some(address) = hex_literal(expression) {
+            return Ok(address);
+        }
+        let symbol = is_symbol_name(expression);
+        if let Some(address) = adapter.cached_symbol(expression).filter(|_| symbol) {
+            return Ok(address.to_string());
         }
         
-        // Get stack frames
+        let result = adapter.evaluate_expression(expression).await
+            .map_err(|e| format!("Could not evaluate '{expression}': {e}{}", error_hint(&e)))?;
+        let value = result.results.get("value")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| "No value in evaluate response".to_string())?;
+        
+        // A function is its address, worth caching; a variable such as a pointer
+        // goes to the value it holds
+        if symbol && is_function_value(value) {
+            return adapter.resolve_symbol(expression).await
+                .map_err(|e| format!("Could not find symbol '{}': {e}{}", expression.trim(), error_hint(&e)));
+        }
+        
+        extract_address(value)
+            .or_else(|| value.trim().parse::<u64>().ok().map(|n| format!("0x{n:x}")))
+            .ok_or_else(|| format!("'{expression}' does not evaluate to an address: {value}"))
+    }
+    
+    /// Stop the GDB session on shutdown, giving up after `timeout` (e.g. when a
+    /// long-running command still holds the adapter). Returns whether it stopped.
+    async fn shutdown_session(gdb_adapter: Arc<Mutex<GdbAdapter>>, exit_action: Option<ExitAction>, timeout: std::time::Duration) -> bool {
+        let result = tokio::time::timeout(timeout, async move {
+            let mut adapter = gdb_adapter.lock().await;
+            if let Some(action) = exit_action {
+                // GDB can only detach or kill a stopped target
+                let outcome = match adapter.stop_target().await {
+                    Ok(()) if action == ExitAction::Detach => adapter.detach().await,
+                    Ok(()) => adapter.kill().await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = outcome {
+                    warn!("shutdown_session: {action:?} before exit failed: {e}");
+                }
+            }
+            adapter.stop_session().await
+        }).await;
+        
+        match result {
+            Ok(Ok(())) => true,
+            Ok(Err(e)) => {
+                warn!("shutdown_session: Failed to stop GDB session: {e}");
+                false
+            }
+            Err(_) => {
+                warn!("shutdown_session: Timed out waiting for GDB session to stop");
+                false
+            }
+        }
+    }
+    
+    /// Publish the target's architecture, which register grouping, the core
+    /// register set and Thumb detection follow
+    async fn send_target_architecture(
+        adapter: &mut GdbAdapter,
+        event_sender: &tokio::sync::mpsc::UnboundedSender<DebugEvent>,
+    ) {
+        match adapter.target_architecture().await {
+            Some(arch) => {
+                let _ = event_sender.send(DebugEvent::ArchitectureDetected(arch));
+            }
+            None => debug!("send_target_architecture: GDB reported no architecture"),
+        }
+    }
+    
+    /// Internal helper to send debug info refresh events
+    async fn send_refresh_debug_info_internal(
+        mut adapter: tokio::sync::MutexGuard<'_, GdbAdapter>,
+        event_sender: tokio::sync::mpsc::UnboundedSender<DebugEvent>,
+    ) -> Result<(), String> {
+        // Stack frames first: their architecture decides the core register set
+        let mut arch = None;
         debug!("send_refresh_debug_info_internal: Getting stack frames...");
         match adapter.get_stack_frames().await {
             Ok(result) => {
-                match Self::parse_stack_frames(&result) {
-                    Ok(stack_frames) => {
+                match StackFrame::list_from_result(&result) {
+                    Ok(mut stack_frames) => {
+                        match adapter.list_stack_arguments(true).await {
+                            Ok(result) => {
+                                for (level, args) in StackFrame::arguments_from_result(&result) {
+                                    if let Some(frame) = stack_frames.iter_mut().find(|frame| frame.level == level) {
+                                        frame.args = args;
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                debug!("send_refresh_debug_info_internal: Failed to get frame arguments: {e}");
+                            }
+                        }
+                        arch = stack_frames.iter().find_map(|frame| frame.arch.clone());
                         let _ = event_sender.send(DebugEvent::StackFramesUpdated(stack_frames));
                     }
                     Err(e) => {
@@ -445,12 +1810,40 @@ impl KatoriApp {
                 error!("send_refresh_debug_info_internal: Failed to get stack frames: {e}");
             }
         }
+        if arch.is_none() {
+            // Stubs may leave the frames without one
+            arch = adapter.target_architecture().await;
+            if let Some(arch) = &arch {
+                let _ = event_sender.send(DebugEvent::ArchitectureDetected(arch.clone()));
+            }
+        }
+        
+        Self::send_registers(&mut adapter, arch.as_deref(), &event_sender).await;
+        
+        // Get threads
+        debug!("send_refresh_debug_info_internal: Getting threads...");
+        match adapter.list_threads().await {
+            Ok(result) => {
+                let _ = event_sender.send(DebugEvent::ThreadsUpdated(ThreadList::from_result(&result)));
+            }
+            Err(e) => {
+                error!("send_refresh_debug_info_internal: Failed to get threads: {e}");
+            }
+        }
         
-        // Get assembly around current PC
+        // Get assembly around current PC, with source when debug info allows it
         debug!("send_refresh_debug_info_internal: Getting assembly...");
-        match adapter.disassemble_current(80).await {
+        let (before, after) = DISASSEMBLY_WINDOW;
+        let disassembly = match adapter.disassemble_current_with_source(before, after).await {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                debug!("send_refresh_debug_info_internal: Source disassembly failed ({e}), falling back to plain");
+                adapter.disassemble_current(before, after).await
+            }
+        };
+        match disassembly {
             Ok(result) => {
-                if let Some(assembly_lines) = Self::parse_assembly(&result) {
+                if let Some(assembly_lines) = parse_assembly(&result) {
                     let _ = event_sender.send(DebugEvent::AssemblyUpdated(assembly_lines));
                 }
             }
@@ -463,10 +1856,158 @@ impl KatoriApp {
     }
 
 
+    pub fn is_debugging(&self) -> bool {
+        self.is_debugging
+    }
+    
+    pub fn get_host_port(&self) -> &str {
+        &self.current_host_port
+    }
+    
+    pub fn set_host_port(&mut self, host_port: &str) {
+        self.current_host_port = host_port.to_string();
+    }
+    
+    /// The retained console output as one string
+    pub fn get_console_output(&self) -> String {
+        self.console_output.text()
+    }
+    
+    /// Append a line to the console
+    pub fn add_console_message(&mut self, message: &str) {
+        if message.ends_with('\n') {
+            self.console_output.push_str(message);
+        } else {
+            self.console_output.push_str(&format!("{message}\n"));
+        }
+    }
+    
+    pub fn get_attach_mode(&self) -> &AttachMode {
+        &self.attach_mode
+    }
+    
+    pub fn set_attach_mode(&mut self, mode: AttachMode) {
+        self.attach_mode = mode;
+    }
+    
+    /// Breakpoints GDB has confirmed
+    pub fn get_breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+    
+    /// Exit code of the program, once it has exited on its own
+    pub fn get_exit_code(&self) -> Option<i32> {
+        match self.target_state {
+            TargetState::Exited(ExitStatus::Code(code)) => Some(code),
+            _ => None,
+        }
+    }
+    
+    /// Registers pinned to the top of the registers panel
+    pub fn get_pinned_registers(&self) -> &[String] {
+        &self.pinned_registers
+    }
+    
+    /// Pin a register to the top of the registers panel, or unpin it
+    pub fn toggle_register_pin(&mut self, name: &str) {
+        let before = self.pinned_registers.len();
+        self.pinned_registers.retain(|pinned| !pinned.eq_ignore_ascii_case(name));
+        if self.pinned_registers.len() == before {
+            self.pinned_registers.push(name.to_string());
+        }
+    }
+    
+    pub fn get_breakpoint_input(&self) -> &str {
+        &self.breakpoint_input
+    }
+    
+    /// Fill the breakpoint panel's inputs from `location` (`function`, `file:line`
+    /// or `*address`), switching to the matching kind of location
+    pub fn set_breakpoint_input(&mut self, location: &str) -> Result<(), String> {
+        match LocationSpec::parse(location)? {
+            LocationSpec::FileLine(file, line) => {
+                self.breakpoint_kind = LocationKind::FileLine;
+                self.breakpoint_input = file;
+                self.breakpoint_line_input = line;
+            }
+            LocationSpec::Address(address) => {
+                self.breakpoint_kind = LocationKind::Address;
+                self.breakpoint_input = address;
+            }
+            LocationSpec::Function(function) => {
+                self.breakpoint_kind = LocationKind::Function;
+                self.breakpoint_input = function;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Set a breakpoint at `location` (`function`, `file:line` or `*address`),
+    /// as if entered into the breakpoint panel
+    pub fn add_breakpoint(&mut self, location: &str) {
+        if let Err(e) = self.set_breakpoint_input(location) {
+            self.console_output.push_error(&format!("Invalid breakpoint location: {e}\n"));
+            return;
+        }
+        self.set_breakpoint();
+    }
+    
+    /// The location the breakpoint panel's inputs describe
+    fn breakpoint_location(&self) -> Result<LocationSpec, String> {
+        let input = self.breakpoint_input.trim();
+        let location = match self.breakpoint_kind {
+            LocationKind::Function => LocationSpec::Function(input.to_string()),
+            LocationKind::FileLine => LocationSpec::FileLine(input.to_string(), self.breakpoint_line_input),
+            LocationKind::Address => LocationSpec::Address(input.trim_start_matches('*').to_string()),
+        };
+        location.validate()?;
+        Ok(location)
+    }
+    
+    fn panel_visibility(&mut self, panel: Panel) -> &mut bool {
+        match panel {
+            Panel::Registers => &mut self.show_registers,
+            Panel::Assembly => &mut self.show_assembly,
+            Panel::Source => &mut self.show_source,
+            Panel::Stack => &mut self.show_stack,
+            Panel::Threads => &mut self.show_threads,
+            Panel::Watch => &mut self.show_watch,
+            Panel::Memory => &mut self.show_memory,
+            Panel::Console => &mut self.show_console,
+            Panel::ProgramOutput => &mut self.show_program_output,
+        }
+    }
+    
+    pub fn is_panel_visible(&self, panel: Panel) -> bool {
+        match panel {
+            Panel::Registers => self.show_registers,
+            Panel::Assembly => self.show_assembly,
+            Panel::Source => self.show_source,
+            Panel::Stack => self.show_stack,
+            Panel::Threads => self.show_threads,
+            Panel::Watch => self.show_watch,
+            Panel::Memory => self.show_memory,
+            Panel::Console => self.show_console,
+            Panel::ProgramOutput => self.show_program_output,
+        }
+    }
+    
+    pub fn set_panel_visible(&mut self, panel: Panel, visible: bool) {
+        *self.panel_visibility(panel) = visible;
+    }
+
     pub fn clear_debug_info(&mut self) {
+        // The recording ends with the process
+        self.is_recording = false;
+        self.signal_alert = None;
         self.registers.clear();
+        self.changed_registers.clear();
+        self.register_baseline.clear();
         self.assembly_lines.clear();
+        self.source_files.clear();
         self.stack_frames.clear();
+        self.threads = ThreadList::default();
+        self.target_arch = None;
     }
     
     pub fn start_gdb_session(&mut self) {
@@ -479,7 +2020,7 @@ impl KatoriApp {
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::StartSession) {
             error!("start_gdb_session: Failed to send StartSession command: {e}");
-            self.console_output.push_str(&format!("Failed to send start session command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send start session command: {e}\n"));
         } else {
             info!("start_gdb_session: StartSession command sent successfully");
             // The result will come back via the event system
@@ -490,12 +2031,19 @@ impl KatoriApp {
         info!("stop_gdb_session: Starting stop session operation");
         self.console_output.push_str("Stopping GDB session...\n");
         
+        self.reconnect = None;
+        // A command still waiting on GDB (e.g. a continue) would keep StopSession queued behind it
+        let cancelled = self.command_canceller.cancel_pending();
+        if cancelled > 0 {
+            self.console_output.push_str(&format!("Cancelled {cancelled} pending command(s)\n"));
+        }
+        
         info!("stop_gdb_session: Sending StopSession command via channel");
         
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::StopSession) {
             error!("stop_gdb_session: Failed to send StopSession command: {e}");
-            self.console_output.push_str(&format!("Failed to send stop session command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send stop session command: {e}\n"));
         } else {
             info!("stop_gdb_session: StopSession command sent successfully");
             // The result will come back via the event system
@@ -504,12 +2052,39 @@ impl KatoriApp {
         // Update state immediately for UI responsiveness
         self.is_debugging = false;
         self.is_attached = false;
+        self.executable = None;
         self.clear_debug_info();
         self.breakpoints.clear();
+        self.watchpoints.clear();
+        // GDB takes its variable objects with it
+        self.watch_tree.clear();
+        self.exit_banner = None;
     }
 
-    fn attach_to_target(&mut self) {
+    fn load_core(&mut self) {
+        let executable = self.core_executable_input.trim().to_string();
+        let core = self.core_file_input.trim().to_string();
+        if executable.is_empty() || core.is_empty() {
+            self.console_output.push_error("A core dump needs both the executable and the core file\n");
+            self.error_message = "Executable and core file required".to_string();
+            return;
+        }
+        
+        info!("load_core: Loading {core} for {executable}");
+        self.console_output.push_str(&format!("Loading core file {core}...\n"));
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::LoadCore(executable, core)) {
+            error!("load_core: Failed to send LoadCore command: {e}");
+            self.console_output.push_error(&format!("Failed to send load core command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    pub fn attach_to_target(&mut self) {
         info!("attach_to_target: Starting attachment process");
+        // A manual attach replaces any scheduled reconnect
+        self.reconnect = None;
         self.console_output.push_str("Starting attachment process...\n");
         
         // Show immediate feedback and validate input
@@ -519,7 +2094,7 @@ impl KatoriApp {
                     self.console_output.push_str(&format!("Attaching to process {pid}...\n"));
                     self.pid_input.clone()
                 } else {
-                    self.console_output.push_str("Invalid PID format\n");
+                    self.console_output.push_error("Invalid PID format\n");
                     self.error_message = "Invalid PID format".to_string();
                     return;
                 }
@@ -528,20 +2103,120 @@ impl KatoriApp {
                 self.console_output.push_str(&format!("Attaching to GDB server at {}...\n", self.current_host_port));
                 self.current_host_port.clone()
             }
+            AttachMode::CoreDump => {
+                self.load_core();
+                return;
+            }
         };
-
+        
         info!("attach_to_target: Sending Attach command via channel");
         
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::Attach(self.attach_mode.clone(), target)) {
             error!("attach_to_target: Failed to send Attach command: {e}");
-            self.console_output.push_str(&format!("Failed to send attach command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send attach command: {e}\n"));
         } else {
             info!("attach_to_target: Attach command sent successfully");
             // The result will come back via the event system
         }
     }
     
+    fn send_register_set(&mut self) {
+        info!("send_register_set: Showing {:?} registers", self.register_set);
+        // The adapter drops the subset along with the old set
+        self.register_subset = None;
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetRegisterSet(self.register_set)) {
+            error!("send_register_set: Failed to send SetRegisterSet command: {e}");
+            self.console_output.push_error(&format!("Failed to send register set command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Start or stop logging MI traffic as the Preferences checkbox says
+    fn send_mi_log(&mut self) {
+        let path = Some(self.mi_log_path.trim().to_string()).filter(|path| self.mi_log_enabled && !path.is_empty());
+        info!("send_mi_log: Logging MI traffic to {path:?}");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetMiLog(path)) {
+            error!("send_mi_log: Failed to send SetMiLog command: {e}");
+            self.console_output.push_error(&format!("Failed to send MI log command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn send_register_subset(&mut self) {
+        info!("send_register_subset: Reading registers {:?}", self.register_subset);
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetRegisterSubset(self.register_subset.clone())) {
+            error!("send_register_subset: Failed to send SetRegisterSubset command: {e}");
+            self.console_output.push_error(&format!("Failed to send register subset command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn send_register_format(&mut self) {
+        info!("send_register_format: Showing registers as {}", self.register_format);
+        // Values in the old format would all look changed
+        self.register_baseline.clear();
+        self.changed_registers.clear();
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetRegisterFormat(self.register_format)) {
+            error!("send_register_format: Failed to send SetRegisterFormat command: {e}");
+            self.console_output.push_error(&format!("Failed to send register format command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Compare later refreshes against the values shown now, and drop the highlights
+    fn reset_register_baseline(&mut self) {
+        self.register_baseline = self.registers.iter().map(|reg| (reg.name.clone(), reg.value.clone())).collect();
+        self.changed_registers.clear();
+    }
+    
+    fn send_architecture(&mut self) {
+        info!("send_architecture: Setting architecture to {}", self.architecture);
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetArchitecture(self.architecture.clone())) {
+            error!("send_architecture: Failed to send SetArchitecture command: {e}");
+            self.console_output.push_error(&format!("Failed to send set architecture command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Returns whether the flavor was sent; it's skipped for targets known not to be x86
+    fn send_disassembly_flavor(&mut self) -> bool {
+        if !is_x86(self.target_arch.as_deref()) {
+            self.console_output.push_str("Disassembly flavor only applies to x86 targets\n");
+            return false;
+        }
+        info!("send_disassembly_flavor: Setting disassembly flavor to {}", self.disassembly_flavor);
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetDisassemblyFlavor(self.disassembly_flavor.clone())) {
+            error!("send_disassembly_flavor: Failed to send SetDisassemblyFlavor command: {e}");
+            self.console_output.push_error(&format!("Failed to send disassembly flavor command: {e}\n"));
+        }
+        // The result will come back via the event system
+        true
+    }
+    
+    fn send_instruction_width(&mut self) {
+        info!("send_instruction_width: Setting instruction width to {:?}", self.instruction_width);
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetInstructionWidth(self.instruction_width)) {
+            error!("send_instruction_width: Failed to send SetInstructionWidth command: {e}");
+            self.console_output.push_error(&format!("Failed to send instruction width command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
     fn detach_from_target(&mut self) {
         info!("detach_from_target: Starting detach operation");
         self.console_output.push_str("Detaching from target...\n");
@@ -551,7 +2226,7 @@ impl KatoriApp {
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::Detach) {
             error!("detach_from_target: Failed to send Detach command: {e}");
-            self.console_output.push_str(&format!("Failed to send detach command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send detach command: {e}\n"));
         } else {
             info!("detach_from_target: Detach command sent successfully");
             // The result will come back via the event system
@@ -562,59 +2237,402 @@ impl KatoriApp {
         self.is_debugging = false;
         self.current_pid = None;
         self.registers.clear();
+        self.changed_registers.clear();
+        self.register_baseline.clear();
         self.assembly_lines.clear();
         self.stack_frames.clear();
     }
     
     fn set_breakpoint(&mut self) {
-        if !self.breakpoint_input.is_empty() {
-            info!("set_breakpoint: Starting set breakpoint operation");
-            self.console_output.push_str(&format!("Setting breakpoint at: {}\n", self.breakpoint_input));
-            
-            let location = self.breakpoint_input.clone();
-            
-            // Add to breakpoints list immediately for UI feedback
-            self.breakpoints.push(location.clone());
-            self.breakpoint_input.clear();
-            
-            info!("set_breakpoint: Sending SetBreakpoint command via channel");
-            
-            // Send command via channel - non-blocking
-            if let Err(e) = self.command_sender.send(GdbCommand::SetBreakpoint(location)) {
-                error!("set_breakpoint: Failed to send SetBreakpoint command: {e}");
-                self.console_output.push_str(&format!("Failed to send set breakpoint command: {e}\n"));
-            } else {
-                info!("set_breakpoint: SetBreakpoint command sent successfully");
-                // The result will come back via the event system
+        let location = match self.breakpoint_location() {
+            Ok(location) => location,
+            Err(e) => {
+                self.console_output.push_error(&format!("Invalid breakpoint location: {e}\n"));
+                return;
             }
+        };
+        info!("set_breakpoint: Starting set breakpoint operation");
+        self.console_output.push_str(&format!("Setting breakpoint at: {location}\n"));
+        
+        let spec = BreakpointSpec {
+            condition: Some(self.breakpoint_condition_input.clone()).filter(|c| !c.trim().is_empty()),
+            ignore_count: self.breakpoint_ignore_input,
+            temporary: self.breakpoint_temporary,
+            hardware: self.breakpoint_hardware,
+            pending: self.breakpoint_pending,
+            ..BreakpointSpec::at(&location)
+        };
+        
+        // The breakpoint is added to the list once GDB confirms it
+        self.breakpoint_input.clear();
+        self.breakpoint_condition_input.clear();
+        self.breakpoint_ignore_input = 0;
+        self.breakpoint_temporary = false;
+        self.breakpoint_hardware = false;
+        self.breakpoint_pending = false;
+        
+        info!("set_breakpoint: Sending SetBreakpoint command via channel");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetBreakpoint(spec)) {
+            error!("set_breakpoint: Failed to send SetBreakpoint command: {e}");
+            self.console_output.push_error(&format!("Failed to send set breakpoint command: {e}\n"));
+        } else {
+            info!("set_breakpoint: SetBreakpoint command sent successfully");
+            // The result will come back via the event system
+            self.refresh_breakpoints();
         }
     }
     
-    fn continue_execution(&mut self) {
-        info!("continue_execution: Starting continue operation (async)");
-        self.console_output.push_str("Continuing execution...\n");
+    fn remove_breakpoint(&mut self, number: u32) {
+        info!("remove_breakpoint: Deleting breakpoint {number}");
+        self.console_output.push_str(&format!("Deleting breakpoint {number}\n"));
         
-        if !self.is_debugging || !self.is_attached {
-            warn!("continue_execution: Not attached to a debug target (debugging: {}, attached: {})", 
-                  self.is_debugging, self.is_attached);
-            self.console_output.push_str("Not attached to a debug target\n");
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::RemoveBreakpoint(number)) {
+            error!("remove_breakpoint: Failed to send RemoveBreakpoint command: {e}");
+            self.console_output.push_error(&format!("Failed to send remove breakpoint command: {e}\n"));
+        }
+        // The result will come back via the event system
+        self.refresh_breakpoints();
+    }
+    
+    /// Remove the breakpoint at an instruction address, or set one there if there is none
+    fn toggle_breakpoint_at_address(&mut self, address: &str) {
+        let Some(target) = parse_address(address) else {
+            return;
+        };
+        if let Some((breakpoint, _)) = breakpoint_at_address(&self.breakpoints, target) {
+            // Deleting it would take the other locations with it, so only this one is toggled
+            if breakpoint.has_multiple_locations() {
+                let location = breakpoint.locations.iter()
+                    .find(|location| location.address.as_deref().and_then(parse_address) == Some(target));
+                if let Some(location) = location {
+                    self.toggle_breakpoint_location(location.number.clone(), location.enabled != "y");
+                }
+                return;
+            }
+            match breakpoint.number.parse() {
+                Ok(number) => self.remove_breakpoint(number),
+                Err(_) => warn!("toggle_breakpoint_at_address: Unexpected breakpoint number {}", breakpoint.number),
+            }
             return;
         }
-
-        info!("continue_execution: Sending Continue command via channel");
+        
+        let location = LocationSpec::Address(address.to_string());
+        info!("toggle_breakpoint_at_address: Setting breakpoint at {location}");
+        self.console_output.push_str(&format!("Setting breakpoint at: {location}\n"));
         
         // Send command via channel - non-blocking
-        if let Err(e) = self.command_sender.send(GdbCommand::Continue) {
-            error!("continue_execution: Failed to send Continue command: {e}");
-            self.console_output.push_str(&format!("Failed to send continue command: {e}\n"));
+        if let Err(e) = self.command_sender.send(GdbCommand::SetBreakpoint(BreakpointSpec::at(&location))) {
+            error!("toggle_breakpoint_at_address: Failed to send SetBreakpoint command: {e}");
+            self.console_output.push_error(&format!("Failed to send set breakpoint command: {e}\n"));
         } else {
-            info!("continue_execution: Continue command sent successfully");
+            // The result will come back via the event system
+            self.refresh_breakpoints();
+        }
+    }
+    
+    /// Queue a re-read of the breakpoint table; commands run in order, so this
+    /// sees the effect of anything sent before it
+    fn refresh_breakpoints(&mut self) {
+        if let Err(e) = self.command_sender.send(GdbCommand::RefreshBreakpoints) {
+            error!("refresh_breakpoints: Failed to send RefreshBreakpoints command: {e}");
+        }
+    }
+    
+    fn toggle_breakpoint(&mut self, number: u32, enabled: bool) {
+        info!("toggle_breakpoint: Setting breakpoint {number} enabled={enabled}");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::ToggleBreakpoint(number, enabled)) {
+            error!("toggle_breakpoint: Failed to send ToggleBreakpoint command: {e}");
+            self.console_output.push_error(&format!("Failed to send toggle breakpoint command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn toggle_breakpoint_location(&mut self, location: String, enabled: bool) {
+        info!("toggle_breakpoint_location: Setting breakpoint location {location} enabled={enabled}");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::ToggleBreakpointLocation(location, enabled)) {
+            error!("toggle_breakpoint_location: Failed to send ToggleBreakpointLocation command: {e}");
+            self.console_output.push_error(&format!("Failed to send toggle breakpoint command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Count hits from the breakpoint's current total; GDB has no way to reset it
+    fn reset_hit_count(&mut self, number: &str) {
+        if let Some(bp) = self.breakpoints.iter().find(|bp| bp.number == number) {
+            self.hit_count_baselines.insert(bp.number.clone(), bp.times);
+        }
+    }
+    
+    /// Hits since the count was last reset
+    fn hits_since_reset(&self, bp: &Breakpoint) -> u32 {
+        bp.times.saturating_sub(self.hit_count_baselines.get(&bp.number).copied().unwrap_or(0))
+    }
+    
+    fn continue_until_hit(&mut self, number: u32, hits: u32) {
+        info!("continue_until_hit: Continuing until breakpoint {number} is hit {hits} times");
+        self.console_output.push_str(&format!("Continuing until breakpoint {number} is hit {hits} times\n"));
+        self.pending_hit_target = Some((number, hits));
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::ContinueUntilHit(number, hits)) {
+            error!("continue_until_hit: Failed to send ContinueUntilHit command: {e}");
+            self.console_output.push_error(&format!("Failed to send continue command: {e}\n"));
+            self.pending_hit_target = None;
+        }
+        // The result will come back via the event system
+    }
+    
+    fn step_instructions(&mut self, count: u32) {
+        info!("step_instructions: Stepping {count} instructions");
+        self.console_output.push_str(&format!("Step {count} instructions\n"));
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::StepInstructions(count)) {
+            error!("step_instructions: Failed to send StepInstructions command: {e}");
+            self.console_output.push_error(&format!("Failed to send step command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn run_to_address(&mut self, address: String) {
+        info!("run_to_address: Running to {address}");
+        self.console_output.push_str(&format!("Running to {address}\n"));
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::RunToAddress(address)) {
+            error!("run_to_address: Failed to send RunToAddress command: {e}");
+            self.console_output.push_error(&format!("Failed to send run to command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Remove the temporary breakpoint of a "run to here" that stopped or exited elsewhere
+    fn abandon_run_to_address(&mut self) {
+        let Some(number) = self.run_to_breakpoint.take() else {
+            return;
+        };
+        let Ok(number) = number.parse() else {
+            return;
+        };
+        if let Err(e) = self.command_sender.send(GdbCommand::RemoveBreakpoint(number)) {
+            error!("abandon_run_to_address: Failed to send RemoveBreakpoint command: {e}");
+        }
+        self.refresh_breakpoints();
+    }
+    
+    /// Give up on a pending "continue until hit" after stopping or exiting elsewhere
+    fn abandon_hit_target(&mut self, reason: &str) {
+        let Some((number, hits)) = self.pending_hit_target.take() else {
+            return;
+        };
+        self.console_output.push_str(&format!("{reason} before breakpoint {number} was hit {hits} times\n"));
+        // Don't leave the breakpoint skipping hits the user no longer asked for
+        if let Err(e) = self.command_sender.send(GdbCommand::SetIgnoreCount(number, 0)) {
+            error!("abandon_hit_target: Failed to send SetIgnoreCount command: {e}");
+        }
+    }
+    
+    fn add_watch_expression(&mut self) {
+        let expression = self.watch_expression_input.trim().to_string();
+        self.watch_expression_input.clear();
+        if expression.is_empty() || self.watch_expressions.contains(&expression) {
+            return;
+        }
+        self.watch_expressions.push(expression);
+        if self.is_attached {
+            self.evaluate_watches();
+        }
+    }
+    
+    /// Re-evaluate every watch expression in a single batch
+    fn evaluate_watches(&mut self) {
+        if self.watch_expressions.is_empty() {
+            self.watch_values.clear();
+            return;
+        }
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::EvaluateWatches(self.watch_expressions.clone())) {
+            error!("evaluate_watches: Failed to send EvaluateWatches command: {e}");
+            self.console_output.push_error(&format!("Failed to send evaluate command: {e}\n"));
+        }
+        if !self.watch_tree.is_empty() {
+            self.update_varobjs();
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Show the members of a watch expression, creating its variable object
+    fn expand_watch(&mut self, expression: String) {
+        info!("expand_watch: Expanding {expression}");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::ExpandWatch(expression)) {
+            error!("expand_watch: Failed to send ExpandWatch command: {e}");
+            self.console_output.push_error(&format!("Failed to send expand command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// List the next page of a variable object's children
+    fn list_varobj_children(&mut self, name: String) {
+        let (from, to) = self.watch_tree.next_page(&name);
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::ListVarObjChildren(name, from, to)) {
+            error!("list_varobj_children: Failed to send ListVarObjChildren command: {e}");
+            self.console_output.push_error(&format!("Failed to send list children command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Re-evaluate the expanded watch expressions and their members
+    fn update_varobjs(&mut self) {
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::UpdateVarObjs) {
+            error!("update_varobjs: Failed to send UpdateVarObjs command: {e}");
+            self.console_output.push_error(&format!("Failed to send update command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn delete_varobj(&mut self, name: String) {
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::DeleteVarObj(name)) {
+            error!("delete_varobj: Failed to send DeleteVarObj command: {e}");
+            self.console_output.push_error(&format!("Failed to send delete command: {e}\n"));
+        }
+    }
+    
+    fn set_watchpoint(&mut self) {
+        if !self.watchpoint_input.is_empty() {
+            info!("set_watchpoint: Starting set watchpoint operation");
+            self.console_output.push_str(&format!("Setting {} watchpoint on: {}\n", self.watch_kind, self.watchpoint_input));
+            
+            let expression = self.watchpoint_input.clone();
+            self.watchpoint_input.clear();
+            
+            info!("set_watchpoint: Sending SetWatchpoint command via channel");
+            
+            // Send command via channel - non-blocking
+            if let Err(e) = self.command_sender.send(GdbCommand::SetWatchpoint(expression, self.watch_kind)) {
+                error!("set_watchpoint: Failed to send SetWatchpoint command: {e}");
+                self.console_output.push_error(&format!("Failed to send set watchpoint command: {e}\n"));
+            } else {
+                info!("set_watchpoint: SetWatchpoint command sent successfully");
+                // The result will come back via the event system
+            }
+        }
+    }
+    
+    fn continue_execution(&mut self) {
+        info!("continue_execution: Starting continue operation (async)");
+        self.console_output.push_str("Continuing execution...\n");
+        
+        if !self.is_debugging || !self.is_attached {
+            warn!("continue_execution: Not attached to a debug target (debugging: {}, attached: {})", 
+                  self.is_debugging, self.is_attached);
+            self.console_output.push_str("Not attached to a debug target\n");
+            return;
+        }
+
+        info!("continue_execution: Sending Continue command via channel");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::Continue) {
+            error!("continue_execution: Failed to send Continue command: {e}");
+            self.console_output.push_error(&format!("Failed to send continue command: {e}\n"));
+        } else {
+            info!("continue_execution: Continue command sent successfully");
             // The result will come back via the event system
             // Update UI state immediately to show that we're running
             self.target_state = TargetState::Running;
         }
     }
     
+    fn load_executable(&mut self) {
+        let path = self.executable_input.trim().to_string();
+        if path.is_empty() {
+            return;
+        }
+        info!("load_executable: Loading {path}");
+        self.console_output.push_str(&format!("Loading executable {path}...\n"));
+        self.show_open_executable = false;
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::LoadExecutable(path)) {
+            error!("load_executable: Failed to send LoadExecutable command: {e}");
+            self.console_output.push_error(&format!("Failed to send load executable command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn run_program(&mut self) {
+        info!("run_program: Starting the loaded executable");
+        self.console_output.push_str("Running program...\n");
+        
+        let args = self.run_arguments.lines().map(str::trim).filter(|arg| !arg.is_empty()).map(String::from).collect();
+        let cwd = Some(self.run_directory.trim().to_string()).filter(|dir| !dir.is_empty());
+        self.apply_environment();
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::Run(args, cwd)) {
+            error!("run_program: Failed to send Run command: {e}");
+            self.console_output.push_error(&format!("Failed to send run command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Queue the environment table ahead of a run; GDB passes it to every program it starts
+    fn apply_environment(&mut self) {
+        let variables: Vec<_> = self.run_environment.iter()
+            .map(|(name, value)| (name.trim().to_string(), value.clone()))
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+        let removed = std::mem::take(&mut self.removed_environment);
+        if variables.is_empty() && removed.is_empty() {
+            return;
+        }
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetEnvironment(variables, removed)) {
+            error!("apply_environment: Failed to send SetEnvironment command: {e}");
+            self.console_output.push_error(&format!("Failed to send environment command: {e}\n"));
+        }
+    }
+    
+    fn restart_program(&mut self) {
+        info!("restart_program: Running the program again");
+        self.console_output.push_str("Restarting program...\n");
+        self.apply_environment();
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::Restart) {
+            error!("restart_program: Failed to send Restart command: {e}");
+            self.console_output.push_error(&format!("Failed to send restart command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn kill_program(&mut self) {
+        info!("kill_program: Killing the program");
+        self.console_output.push_str("Killing program...\n");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::Kill) {
+            error!("kill_program: Failed to send Kill command: {e}");
+            self.console_output.push_error(&format!("Failed to send kill command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
     fn step_over(&mut self) {
         info!("step_over: Starting step over operation (async)");
         self.console_output.push_str("Step over\n");
@@ -631,7 +2649,7 @@ impl KatoriApp {
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::StepOver) {
             error!("step_over: Failed to send StepOver command: {e}");
-            self.console_output.push_str(&format!("Failed to send step over command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send step over command: {e}\n"));
         } else {
             info!("step_over: StepOver command sent successfully");
             // The result will come back via the event system
@@ -654,13 +2672,37 @@ impl KatoriApp {
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::StepInto) {
             error!("step_into: Failed to send StepInto command: {e}");
-            self.console_output.push_str(&format!("Failed to send step into command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send step into command: {e}\n"));
         } else {
             info!("step_into: StepInto command sent successfully");
             // The result will come back via the event system
         }
     }
     
+    /// Send one of the reverse execution commands
+    fn reverse_execution(&mut self, command: GdbCommand) {
+        info!("reverse_execution: Sending {command:?} via channel");
+        self.console_output.push_str(&format!("{command:?}\n"));
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(command) {
+            error!("reverse_execution: Failed to send command: {e}");
+            self.console_output.push_error(&format!("Failed to send reverse command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn set_recording(&mut self, enabled: bool) {
+        info!("set_recording: {} recording", if enabled { "Starting" } else { "Stopping" });
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetRecording(enabled)) {
+            error!("set_recording: Failed to send SetRecording command: {e}");
+            self.console_output.push_error(&format!("Failed to send recording command: {e}\n"));
+        }
+        // GDB confirms with =record-started / =record-stopped
+    }
+    
     fn step_out(&mut self) {
         info!("step_out: Starting step out operation (async)");
         self.console_output.push_str("Step out\n");
@@ -677,7 +2719,7 @@ impl KatoriApp {
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::StepOut) {
             error!("step_out: Failed to send StepOut command: {e}");
-            self.console_output.push_str(&format!("Failed to send step out command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send step out command: {e}\n"));
         } else {
             info!("step_out: StepOut command sent successfully");
             // The result will come back via the event system
@@ -700,7 +2742,7 @@ impl KatoriApp {
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::Interrupt) {
             error!("interrupt_execution: Failed to send Interrupt command: {e}");
-            self.console_output.push_str(&format!("Failed to send interrupt command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send interrupt command: {e}\n"));
         } else {
             info!("interrupt_execution: Interrupt command sent successfully");
             // The result will come back via the event system
@@ -723,254 +2765,699 @@ impl KatoriApp {
         // Send command via channel - non-blocking
         if let Err(e) = self.command_sender.send(GdbCommand::RefreshDebugInfo) {
             error!("refresh_debug_info: Failed to send RefreshDebugInfo command: {e}");
-            self.console_output.push_str(&format!("Failed to send refresh command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send refresh command: {e}\n"));
         } else {
             info!("refresh_debug_info: RefreshDebugInfo command sent successfully");
             // The result will come back via the event system
         }
     }
     
-    fn read_memory(&mut self) {
-        info!("read_memory: Starting read memory operation");
-        self.console_output.push_str(&format!("Reading {} bytes from {}\n", self.memory_size, self.memory_address));
+    fn read_memory(&mut self, id: usize) {
+        let Some(view) = self.memory_views.iter_mut().find(|view| view.id == id) else {
+            return;
+        };
+        info!("read_memory: Starting read memory operation for view {id}");
+        self.console_output.push_str(&format!("Reading {} bytes from {}\n", view.size, view.address));
         
-        let address = self.memory_address.clone();
-        let size = self.memory_size;
+        let address = view.address.clone();
+        let size = view.size;
+        // A new read replaces one still in progress
+        view.chunked_read = None;
+        if size > MEMORY_CHUNK_SIZE {
+            let generation = view.start_chunked_read().generation;
+            self.read_memory_chunk(id, generation, address, 0, MEMORY_CHUNK_SIZE);
+            return;
+        }
         
         info!("read_memory: Sending ReadMemory command via channel");
         
         // Send command via channel - non-blocking
-        if let Err(e) = self.command_sender.send(GdbCommand::ReadMemory(address, size)) {
+        if let Err(e) = self.command_sender.send(GdbCommand::ReadMemory(id, address, size)) {
             error!("read_memory: Failed to send ReadMemory command: {e}");
-            self.console_output.push_str(&format!("Failed to send read memory command: {e}\n"));
+            self.console_output.push_error(&format!("Failed to send read memory command: {e}\n"));
         } else {
             info!("read_memory: ReadMemory command sent successfully");
             // The result will come back via the event system
         }
     }
     
-    /// Automatically fetch debug information when GDB is stopped
-    fn auto_refresh_debug_info(&mut self) {
-        if !self.is_debugging || !self.is_attached {
+    fn read_memory_chunk(&mut self, id: usize, generation: u64, address: String, offset: u32, len: u32) {
+        info!("read_memory_chunk: Reading {len} bytes at {address}+{offset} for view {id}");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::ReadMemoryChunk(id, generation, address, offset, len)) {
+            error!("read_memory_chunk: Failed to send ReadMemoryChunk command: {e}");
+            self.console_output.push_error(&format!("Failed to send read memory command: {e}\n"));
+            if let Some(view) = self.memory_views.iter_mut().find(|view| view.id == id) {
+                view.chunked_read = None;
+            }
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Re-read every memory view, or only the pinned ones
+    fn refresh_memory_views(&mut self, pinned_only: bool) {
+        let ids: Vec<usize> = self.memory_views.iter()
+            .filter(|view| view.pinned || !pinned_only)
+            .map(|view| view.id)
+            .collect();
+        for id in ids {
+            self.read_memory(id);
+        }
+    }
+    
+    /// Point the first memory view at `address` and read it
+    fn show_in_memory(&mut self, address: String) {
+        if self.memory_views.is_empty() {
+            self.add_memory_view();
+        }
+        let view = &mut self.memory_views[0];
+        view.address = address;
+        let id = view.id;
+        self.show_memory = true;
+        self.read_memory(id);
+    }
+    
+    fn add_memory_view(&mut self) {
+        let address = self.memory_views.last().map_or("0x0".to_string(), |view| view.address.clone());
+        self.memory_views.push(MemoryView::new(self.next_memory_view_id, &address));
+        self.next_memory_view_id += 1;
+    }
+    
+    fn write_memory_byte(&mut self, address: u64, text: &str) {
+        let Some(byte) = parse_hex_byte(text) else {
+            self.error_message = format!("Invalid byte '{text}': enter two hex digits");
+            return;
+        };
+        
+        let address = format!("0x{address:x}");
+        info!("write_memory_byte: Writing {byte:02x} to {address}");
+        self.console_output.push_str(&format!("Writing {byte:02x} to {address}\n"));
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::WriteMemory(address, vec![byte])) {
+            error!("write_memory_byte: Failed to send WriteMemory command: {e}");
+            self.console_output.push_error(&format!("Failed to send write memory command: {e}\n"));
+        }
+        // The region is re-read once the write completes
+    }
+    
+    fn send_raw_command(&mut self) {
+        let command = self.raw_command_input.trim().to_string();
+        self.raw_command_input.clear();
+        if command.is_empty() {
             return;
         }
         
-        info!("auto_refresh_debug_info: Starting auto refresh");
-        self.console_output.push_str("Refreshing debug information...\n");
+        info!("send_raw_command: Sending raw command: {command}");
+        self.console_output.push_str(&format!("(gdb) {command}\n"));
+        self.command_history.push(command.clone());
         
-        info!("auto_refresh_debug_info: Sending RefreshDebugInfo command via channel");
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::Raw(command)) {
+            error!("send_raw_command: Failed to send Raw command: {e}");
+            self.console_output.push_error(&format!("Failed to send command: {e}\n"));
+        }
+    }
+    
+    /// Send the Program Output input line to the program as one line of stdin
+    pub fn send_program_input(&mut self) {
+        let text = format!("{}\n", self.program_input);
+        self.program_input.clear();
+        
+        info!("send_program_input: Sending {} bytes to the program", text.len());
         
         // Send command via channel - non-blocking
-        if let Err(e) = self.command_sender.send(GdbCommand::RefreshDebugInfo) {
-            error!("auto_refresh_debug_info: Failed to send RefreshDebugInfo command: {e}");
-            self.console_output.push_str(&format!("Failed to send refresh command: {e}\n"));
-        } else {
-            info!("auto_refresh_debug_info: RefreshDebugInfo command sent successfully");
-            // The result will come back via the event system
+        if let Err(e) = self.command_sender.send(GdbCommand::SendProgramInput(text)) {
+            error!("send_program_input: Failed to send SendProgramInput command: {e}");
+            self.console_output.push_error(&format!("Failed to send program input: {e}\n"));
         }
+        // The terminal echoes the line back as program output
     }
     
-    /// Parse register values from GDB/MI result
-    fn parse_registers(result: &gdbadapter::GdbResult, register_names: &[(usize, String)]) -> Option<Vec<Register>> {
-        // GDB/MI uses "register-values" field for -data-list-register-values
-        if let Some(Value::List(register_list)) = result.results.get("register-values") {
-            let mut registers = Vec::new();
-            
-            for reg_value in register_list {
-                if let Some(reg_tuple) = reg_value.as_tuple() {
-                    let number = reg_tuple.get("number")?.as_string()?.parse().ok()?;
-                    let value = reg_tuple.get("value")?.as_string()?.to_string();
-                    
-                    // Use the actual register name if available, otherwise use a generic name
-                    let name = register_names.iter()
-                        .find(|(i, _)| *i == number as usize)
-                        .map(|(_, name)| name.clone())
-                        .unwrap_or_else(|| format!("r{number}"));
-                    
-                    registers.push(Register {
-                        number,
-                        name,
-                        value,
-                    });
-                }
-            }
-            
-            Some(registers)
-        } else {
-            // Check if there's a different structure
-            debug!("parse_registers: Available register result keys: {:?}", result.results.keys().collect::<Vec<_>>());
-            None
+    fn disassemble_function(&mut self) {
+        let function = self.disassemble_input.trim().to_string();
+        if function.is_empty() {
+            return;
         }
+        self.assembly_target = None;
+        
+        info!("disassemble_function: Disassembling {function}");
+        self.console_output.push_str(&format!("Disassembling {function}\n"));
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::DisassembleFunction(function)) {
+            error!("disassemble_function: Failed to send DisassembleFunction command: {e}");
+            self.console_output.push_error(&format!("Failed to send disassemble command: {e}\n"));
+        }
+        // The result will come back via the event system
     }
     
-    /// Parse stack frames from GDB/MI result
-    fn parse_stack_frames(result: &gdbadapter::GdbResult) -> Result<Vec<StackFrame>, String> {
-        // GDB/MI uses "stack" field for -stack-list-frames
-        if let Some(Value::List(frame_list)) = result.results.get("stack") {
-            let mut frames = Vec::new();
-            
-            for (index, frame_value) in frame_list.iter().enumerate() {
-                if let Some(frame_tuple) = frame_value.as_tuple() {
-                    // Check for nested frame structure (frame={...})
-                    let actual_frame = if let Some(nested_frame) = frame_tuple.get("frame") {
-                        if let Some(nested_tuple) = nested_frame.as_tuple() {
-                            nested_tuple
-                        } else {
-                            return Err(format!("Frame {index} has invalid nested frame structure"));
-                        }
-                    } else {
-                        frame_tuple
-                    };
-                    
-                    let level = actual_frame.get("level")
-                        .and_then(|v| v.as_string())
-                        .and_then(|s| s.parse().ok())
-                        .ok_or_else(|| format!("Frame {index} missing or invalid 'level' field"))?;
-                    
-                    let address = actual_frame.get("addr")
-                        .and_then(|v| v.as_string())
-                        .ok_or_else(|| format!("Frame {index} missing 'addr' field"))?
-                        .to_string();
-                    
-                    let function = actual_frame.get("func").and_then(|v| v.as_string()).map(|s| s.to_string());
-                    let file = actual_frame.get("file").and_then(|v| v.as_string()).map(|s| s.to_string());
-                    let fullname = actual_frame.get("fullname").and_then(|v| v.as_string()).map(|s| s.to_string());
-                    let line = actual_frame.get("line").and_then(|v| v.as_string()).and_then(|s| s.parse().ok());
-                    let arch = actual_frame.get("arch").and_then(|v| v.as_string()).map(|s| s.to_string());
-                    
-                    frames.push(StackFrame {
-                        level,
-                        address,
-                        function,
-                        file,
-                        fullname,
-                        line,
-                        arch,
-                    });
-                } else {
-                    return Err(format!("Frame {index} is not a tuple structure"));
-                }
-            }
-            
-            Ok(frames)
-        } else {
-            // Check if there's a different structure
-            debug!("parse_stack_frames: Available stack result keys: {:?}", result.results.keys().collect::<Vec<_>>());
-            Err(format!("No 'stack' field found in result. Available keys: {:?}", 
-                result.results.keys().collect::<Vec<_>>()))
+    /// Show the innermost frame and the instructions around the address that raised the signal
+    fn view_faulting_frame(&mut self) {
+        self.select_frame(0);
+        if let Some(address) = self.signal_alert.as_ref().and_then(|alert| alert.address.clone()) {
+            self.show_assembly = true;
+            self.disassemble_input = address;
+            self.disassemble_function();
         }
     }
     
-    /// Parse assembly instructions from GDB/MI result
-    fn parse_assembly(result: &gdbadapter::GdbResult) -> Option<Vec<AssemblyLine>> {
-        // GDB/MI uses "asm_insns" field for -data-disassemble
-        if let Some(Value::List(asm_list)) = result.results.get("asm_insns") {
-            let mut assembly = Vec::new();
-            
-            for asm_value in asm_list {
-                if let Some(asm_tuple) = asm_value.as_tuple() {
-                    let address = asm_tuple.get("address")?.as_string()?.to_string();
-                    let instruction = asm_tuple.get("inst")?.as_string()?.to_string();
-                    let function = asm_tuple.get("func-name").and_then(|v| v.as_string()).map(|s| s.to_string());
-                    let offset = asm_tuple.get("offset").and_then(|v| v.as_string()).and_then(|s| s.parse().ok());
-                    let opcodes = asm_tuple.get("opcodes").and_then(|v| v.as_string()).map(|s| s.to_string());
-                    
-                    assembly.push(AssemblyLine {
-                        address,
-                        function,
-                        offset,
-                        instruction,
-                        opcodes,
-                    });
-                }
-            }
-            
-            Some(assembly)
-        } else {
-            // Check if there's a different structure
-            debug!("parse_assembly: Available assembly result keys: {:?}", result.results.keys().collect::<Vec<_>>());
-            None
+    /// Scroll to a branch target, disassembling the function around it when it isn't listed
+    fn follow_branch(&mut self, target: u64) {
+        let listed = self.assembly_lines.iter().any(|line| parse_address(&line.address) == Some(target));
+        if !listed {
+            self.disassemble_input = format!("0x{target:x}");
+            self.disassemble_function();
         }
+        self.assembly_target = Some(target);
     }
-}
-
-impl eframe::App for KatoriApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Process events from async operations
-        while let Ok(event) = self.event_receiver.try_recv() {
-            match event {
-                DebugEvent::RegistersUpdated(registers) => {
-                    self.registers = registers;
-                    info!("Event: Updated registers: {} items", self.registers.len());
-                }
-                DebugEvent::StackFramesUpdated(stack_frames) => {
-                    self.stack_frames = stack_frames;
-                    info!("Event: Updated stack frames: {} items", self.stack_frames.len());
-                }
-                DebugEvent::AssemblyUpdated(assembly_lines) => {
-                    self.assembly_lines = assembly_lines;
-                    info!("Event: Updated assembly: {} items", self.assembly_lines.len());
-                }
-                DebugEvent::ConsoleMessage(message) => {
-                    self.console_output.push_str(&message);
-                }
-                DebugEvent::AttachSuccess(pid) => {
-                    self.is_attached = true;
-                    self.is_debugging = true;
-                    if let Some(pid) = pid {
-                        self.current_pid = Some(pid);
-                        self.console_output.push_str(&format!("Successfully attached to process {pid}\n"));
-                    } else {
-                        self.console_output.push_str("Successfully attached to GDB server\n");
+    
+    fn interrupt_thread(&mut self, id: u32) {
+        info!("interrupt_thread: Interrupting thread {id}");
+        self.console_output.push_str(&format!("Interrupting thread {id}\n"));
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::InterruptThread(id)) {
+            error!("interrupt_thread: Failed to send InterruptThread command: {e}");
+            self.console_output.push_error(&format!("Failed to send interrupt thread command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn send_non_stop(&mut self) {
+        info!("send_non_stop: Setting non-stop mode to {}", self.non_stop);
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SetNonStop(self.non_stop)) {
+            error!("send_non_stop: Failed to send SetNonStop command: {e}");
+            self.console_output.push_error(&format!("Failed to send non-stop mode command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    fn select_thread(&mut self, id: u32) {
+        info!("select_thread: Switching to thread {id}");
+        self.console_output.push_str(&format!("Switching to thread {id}\n"));
+        // GDB selects the innermost frame of the new thread
+        self.selected_frame = 0;
+        self.assembly_follow_pc = true;
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SelectThread(id)) {
+            error!("select_thread: Failed to send SelectThread command: {e}");
+            self.console_output.push_error(&format!("Failed to send select thread command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// The gdbserver connection dropped: mark the target gone and, if enabled, start reconnecting
+    fn remote_connection_lost(&mut self) {
+        if !self.is_attached || self.attach_mode != AttachMode::GdbServer || self.reconnect.is_some() {
+            return;
+        }
+        self.is_attached = false;
+        self.target_state = TargetState::Detached;
+        self.clear_debug_info();
+        
+        if !self.auto_reconnect {
+            self.console_output.push_error("Remote connection lost\n");
+            return;
+        }
+        info!("remote_connection_lost: Reconnecting to {}", self.current_host_port);
+        self.console_output.push_str("Remote connection lost, reconnecting...\n");
+        self.reconnect = Some(PendingReconnect::after_backoff(1));
+    }
+    
+    /// Send the scheduled reconnect attempt once its backoff has passed
+    fn poll_reconnect(&mut self, ctx: &egui::Context) {
+        let Some(PendingReconnect { attempt, due: Some(due) }) = self.reconnect else {
+            return;
+        };
+        let now = std::time::Instant::now();
+        if now < due {
+            ctx.request_repaint_after(due - now);
+            return;
+        }
+        self.console_output.push_str(&format!(
+            "Reconnecting to {} (attempt {attempt}/{})...\n", self.current_host_port, self.reconnect_attempts
+        ));
+        self.reconnect = Some(PendingReconnect { attempt, due: None });
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::Reconnect(self.current_host_port.clone())) {
+            error!("poll_reconnect: Failed to send Reconnect command: {e}");
+            self.console_output.push_error(&format!("Failed to send reconnect command: {e}\n"));
+            self.reconnect = None;
+        }
+    }
+    
+    /// A reconnect attempt failed: schedule the next one, or give up after the last
+    fn reconnect_failed(&mut self, error: &str) {
+        let Some(PendingReconnect { attempt, .. }) = self.reconnect else {
+            return;
+        };
+        self.console_output.push_str(&format!("Reconnect attempt {attempt} failed: {error}\n"));
+        if attempt < self.reconnect_attempts {
+            self.reconnect = Some(PendingReconnect::after_backoff(attempt + 1));
+            return;
+        }
+        self.reconnect = None;
+        let error = format!("Gave up reconnecting to {} after {attempt} attempts", self.current_host_port);
+        self.console_output.push_error(&format!("{error}\n"));
+        self.error_message = error;
+    }
+    
+    fn select_frame(&mut self, level: u32) {
+        info!("select_frame: Selecting frame {level}");
+        self.selected_frame = level;
+        self.assembly_follow_pc = true;
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::SelectFrame(level)) {
+            error!("select_frame: Failed to send SelectFrame command: {e}");
+            self.console_output.push_error(&format!("Failed to send select frame command: {e}\n"));
+            return;
+        }
+        // Queued behind the selection, so watches are evaluated in the new frame
+        self.evaluate_watches();
+    }
+    
+    fn run_palette(&mut self, target: GoToTarget) {
+        let expression = self.palette_input.trim().to_string();
+        if expression.is_empty() {
+            return;
+        }
+        self.show_palette = false;
+        
+        info!("run_palette: Going to {expression} in {target:?}");
+        if target == GoToTarget::Memory {
+            self.show_memory = true;
+        }
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::GoTo(expression, target)) {
+            error!("run_palette: Failed to send GoTo command: {e}");
+            self.console_output.push_error(&format!("Failed to send go to command: {e}\n"));
+        }
+    }
+    
+    /// Search registers, stack frames, the memory view and any scanned range for
+    /// the reference target, as a pointer of the target's width
+    fn find_references(&mut self) {
+        let Some(target) = self.reference_target else {
+            self.reference_results.clear();
+            return;
+        };
+        let pointer_size = pointer_size(self.target_arch.as_deref());
+        
+        let mut results = find_in_registers(target, pointer_size, &self.registers, self.register_format);
+        results.extend(find_in_frames(target, &self.stack_frames));
+        // The scanned range is read with the first view's byte order
+        let scan_endian = self.memory_views.first().map_or(Endian::Little, |view| view.layout.endian);
+        let blocks = self.memory_views.iter()
+            .filter_map(|view| view.data.as_ref().map(|data| (data, view.layout.endian)))
+            .chain(self.scanned_memory.as_ref().map(|data| (data, scan_endian)));
+        for (block, endian) in blocks {
+            if let Some(base) = parse_address(&block.begin) {
+                let bytes = decode_hex(&block.contents);
+                results.extend(find_in_memory(target, pointer_size, endian, base, &bytes));
+            }
+        }
+        // The memory views and the scanned range may overlap
+        results.sort();
+        results.dedup();
+        self.reference_results = results;
+    }
+    
+    fn scan_memory_for_references(&mut self) {
+        let Ok(size) = self.reference_scan_size.trim().parse::<u32>() else {
+            self.error_message = "Invalid scan size".to_string();
+            return;
+        };
+        let address = self.reference_scan_address.trim().to_string();
+        info!("scan_memory_for_references: Reading {size} bytes at {address}");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::ScanMemory(address, size)) {
+            error!("scan_memory_for_references: Failed to send ScanMemory command: {e}");
+            self.console_output.push_error(&format!("Failed to send scan memory command: {e}\n"));
+        }
+        // The result will come back via the event system
+    }
+    
+    /// Automatically fetch debug information when GDB is stopped
+    fn auto_refresh_debug_info(&mut self) {
+        if !self.is_debugging || !self.is_attached {
+            return;
+        }
+        
+        info!("auto_refresh_debug_info: Starting auto refresh");
+        self.console_output.push_str("Refreshing debug information...\n");
+        
+        info!("auto_refresh_debug_info: Sending RefreshDebugInfo command via channel");
+        
+        // Send command via channel - non-blocking
+        if let Err(e) = self.command_sender.send(GdbCommand::RefreshDebugInfo) {
+            error!("auto_refresh_debug_info: Failed to send RefreshDebugInfo command: {e}");
+            self.console_output.push_error(&format!("Failed to send refresh command: {e}\n"));
+        } else {
+            info!("auto_refresh_debug_info: RefreshDebugInfo command sent successfully");
+            // The result will come back via the event system
+        }
+    }
+}
+
+impl eframe::App for KatoriApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, PINNED_REGISTERS_KEY, &self.pinned_registers);
+        eframe::set_value(storage, CODE_THEME_KEY, &self.code_theme);
+    }
+    
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        info!("on_exit: Stopping GDB session before exit");
+        // A continue waiting on GDB would otherwise hold the adapter until the timeout
+        self.command_canceller.cancel_pending();
+        let gdb_adapter = self.gdb_adapter.clone();
+        let exit_action = self.exit_action;
+        let stopped = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(Self::shutdown_session(gdb_adapter, exit_action, SHUTDOWN_TIMEOUT))
+        });
+        // If the session could not be stopped, the adapter's Drop kills GDB
+        // once the runtime shuts down and releases the last reference.
+        info!("on_exit: GDB session stopped cleanly: {stopped}");
+    }
+    
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Drain the MI tap; records are only kept in developer mode
+        while let Ok(event) = self.mi_tap_receiver.try_recv() {
+            if self.developer_mode {
+                if self.mi_log.len() == MI_LOG_LIMIT {
+                    self.mi_log.pop_front();
+                }
+                self.mi_log.push_back(MiLogEntry::from_event(&event));
+            }
+        }
+        
+        // Process events from async operations
+        while let Ok(event) = self.event_receiver.try_recv() {
+            match event {
+                DebugEvent::RegistersUpdated(registers, reported) => {
+                    self.changed_registers = changed_registers(&self.register_baseline, &registers, reported);
+                    // Registers missing from this refresh (e.g. the full set was hidden) keep their old value
+                    self.register_baseline.extend(registers.iter().map(|reg| (reg.name.clone(), reg.value.clone())));
+                    self.registers = registers;
+                    info!("Event: Updated registers: {} items", self.registers.len());
+                }
+                DebugEvent::RegisterValuesUpdated(values, reported) => {
+                    let numbers: HashSet<u32> = values.iter().map(|reg| reg.number).collect();
+                    merge_register_values(&mut self.registers, values);
+                    let updated: Vec<Register> = self.registers.iter().filter(|reg| numbers.contains(&reg.number)).cloned().collect();
+                    // Highlights of registers not read this time still hold
+                    self.changed_registers.retain(|number| !numbers.contains(number));
+                    self.changed_registers.extend(changed_registers(&self.register_baseline, &updated, reported));
+                    self.register_baseline.extend(updated.iter().map(|reg| (reg.name.clone(), reg.value.clone())));
+                    info!("Event: Updated register values: {} items", updated.len());
+                }
+                DebugEvent::StackFramesUpdated(stack_frames) => {
+                    if let Some(arch) = stack_frames.iter().find_map(|frame| frame.arch.clone()) {
+                        self.target_arch = Some(arch);
+                    }
+                    self.stack_frames = stack_frames;
+                    if !self.stack_frames.iter().any(|frame| frame.level == self.selected_frame) {
+                        self.selected_frame = 0;
+                    }
+                    info!("Event: Updated stack frames: {} items", self.stack_frames.len());
+                }
+                DebugEvent::ThreadsUpdated(threads) => {
+                    self.threads = threads;
+                    info!("Event: Updated threads: {} items", self.threads.threads.len());
+                }
+                DebugEvent::AssemblyUpdated(assembly_lines) => {
+                    self.assembly_lines = assembly_lines;
+                    // Scroll to the PC, which sits mid-window, unless a jump is waiting;
+                    // listings asked for by name or address keep their own position
+                    if std::mem::take(&mut self.assembly_follow_pc) && self.assembly_target.is_none() {
+                        self.assembly_target = self.stack_frames.iter()
+                            .find(|frame| frame.level == self.selected_frame)
+                            .and_then(|frame| parse_address(&frame.address));
+                    }
+                    info!("Event: Updated assembly: {} items", self.assembly_lines.len());
+                }
+                DebugEvent::BreakpointInserted(breakpoint) => {
+                    if breakpoint.is_pending() {
+                        self.console_output.push_str(&format!("Breakpoint {} pending at {}: it is placed once the location is loaded\n", breakpoint.number, breakpoint_label(&breakpoint)));
+                    } else {
+                        self.console_output.push_str(&format!("Breakpoint {} at {}{}\n", breakpoint.number, breakpoint_label(&breakpoint), breakpoint_options(&breakpoint)));
+                    }
+                    self.breakpoints.push(breakpoint);
+                }
+                DebugEvent::WatchesUpdated(values) => {
+                    self.changed_watches = changed_watch_values(&self.watch_values, &values);
+                    self.watch_values = values;
+                }
+                DebugEvent::VarObjCreated(expression, varobj) => {
+                    // Removed while GDB was creating it, or expanded twice
+                    if !self.watch_expressions.contains(&expression) || self.watch_tree.root(&expression).is_some() {
+                        self.delete_varobj(varobj.name);
+                    } else {
+                        self.watch_tree.set_root(&expression, varobj);
+                    }
+                }
+                DebugEvent::VarObjChildren(name, from, children) => {
+                    self.watch_tree.add_children(&name, from, children);
+                }
+                DebugEvent::VarObjsUpdated(changes) => {
+                    for name in self.watch_tree.apply_changes(&changes) {
+                        self.list_varobj_children(name);
+                    }
+                }
+                DebugEvent::BreakpointsUpdated(breakpoints) => {
+                    self.breakpoints = breakpoints;
+                    info!("Event: Updated breakpoints: {} items", self.breakpoints.len());
+                }
+                DebugEvent::BreakpointModified(breakpoint) => {
+                    if let Some(bp) = self.breakpoints.iter_mut().find(|bp| bp.number == breakpoint.number) {
+                        if let Some(message) = breakpoint_resolution(bp, &breakpoint) {
+                            self.console_output.push_str(&format!("{message}\n"));
+                        }
+                        *bp = breakpoint;
+                    }
+                }
+                DebugEvent::BreakpointDeleted(number) => {
+                    self.breakpoints.retain(|bp| bp.number != number);
+                }
+                DebugEvent::RunToAddressStarted(number) => {
+                    self.run_to_breakpoint = Some(number);
+                }
+                DebugEvent::BreakpointHit(number) => {
+                    // GDB deletes a temporary breakpoint once it is hit
+                    if self.run_to_breakpoint.as_ref() == Some(&number) {
+                        self.run_to_breakpoint = None;
+                    }
+                    if let Some((target, hits)) = self.pending_hit_target {
+                        if number == target.to_string() {
+                            self.console_output.push_str(&format!("Breakpoint {target} reached after {hits} hits\n"));
+                            self.pending_hit_target = None;
+                        }
+                    }
+                }
+                DebugEvent::SignalReceived(alert) => {
+                    self.console_output.push_error(&format!("{alert}\n"));
+                    self.signal_alert = Some(alert);
+                }
+                DebugEvent::WatchpointInserted(watchpoint) => {
+                    self.console_output.push_str(&format!("Watchpoint {} ({}) on {}\n", watchpoint.number, watchpoint.kind, watchpoint.expression));
+                    self.watchpoints.push(watchpoint);
+                }
+                DebugEvent::ConsoleMessage(message) => {
+                    self.console_output.push_str(&message);
+                }
+                DebugEvent::StreamOutput(kind, text) => {
+                    self.console_output.push(kind, &text);
+                }
+                DebugEvent::ProgramOutput(text) => {
+                    // Bring the panel up the first time the program prints anything
+                    if self.program_output.entries().next().is_none() {
+                        self.show_program_output = true;
+                    }
+                    self.program_output.push(ConsoleKind::Target, &text);
+                }
+                DebugEvent::ArchitectureDetected(arch) => {
+                    info!("Event: Target architecture is {arch}");
+                    self.target_arch = Some(arch);
+                }
+                DebugEvent::ProgramExited(code) => {
+                    // The state change that follows carries the code to the banner
+                    info!("Event: Program exited with code {code}");
+                }
+                DebugEvent::AttachSuccess(pid) => {
+                    self.is_attached = true;
+                    self.launched = false;
+                    self.reconnect = None;
+                    self.is_debugging = true;
+                    self.target_state = TargetState::Stopped;
+                    self.assembly_follow_pc = true;
+                    // The first refresh of a new target has nothing to highlight
+                    self.register_baseline.clear();
+                    self.changed_registers.clear();
+                    if let Some(pid) = pid {
+                        self.current_pid = Some(pid);
+                        self.console_output.push_str(&format!("Successfully attached to process {pid}\n"));
+                    } else {
+                        self.console_output.push_str("Successfully attached to GDB server\n");
                     }
                     // Auto-refresh debug info after successful attach
                     self.auto_refresh_debug_info();
                 }
                 DebugEvent::AttachFailed(error) => {
-                    self.console_output.push_str(&format!("Attach failed: {error}\n"));
+                    self.console_output.push_error(&format!("Attach failed: {error}\n"));
                     self.error_message = format!("Attach failed: {error}");
                 }
+                DebugEvent::RemoteConnectionLost => {
+                    self.remote_connection_lost();
+                }
+                DebugEvent::ReconnectFailed(error) => {
+                    self.reconnect_failed(&error);
+                }
+                DebugEvent::ExecutableLoaded(path) => {
+                    self.console_output.push_str(&format!("Loaded {path}\n"));
+                    self.is_debugging = true;
+                    self.executable = Some(path);
+                }
                 DebugEvent::DetachSuccess => {
                     self.console_output.push_str("Successfully detached\n");
                 }
-                DebugEvent::MemoryRead(data) => {
-                    self.memory_data = Some(data);
+                DebugEvent::MemoryRead(id, data) => {
+                    // The view may have been closed while the read was in flight
+                    if let Some(view) = self.memory_views.iter_mut().find(|view| view.id == id) {
+                        view.data = Some(data);
+                        view.read_error = None;
+                    }
                     // self.console_output.push_str(&format!("Memory read successfully: {} bytes\n", data.contents.len()));
                     info!("Event: Memory read completed");
                 }
-                DebugEvent::MemoryReadFailed(error) => {
-                    self.console_output.push_str(&format!("Memory read failed: {error}\n"));
-                    self.error_message = format!("Memory read failed: {error}");
+                DebugEvent::MemoryChunk(id, generation, offset, chunk) => {
+                    // Chunks for a closed view, or of a read since cancelled or replaced, are dropped
+                    let next = self.memory_views.iter_mut()
+                        .find(|view| view.id == id)
+                        .and_then(|view| view.append_chunk(generation, offset, chunk));
+                    if let Some((address, offset, len)) = next {
+                        self.read_memory_chunk(id, generation, address, offset, len);
+                    }
+                }
+                DebugEvent::FindReferences(address) => {
+                    self.reference_target = parse_address(&address);
+                    self.scanned_memory = None;
+                    self.show_references = true;
+                    self.find_references();
+                }
+                DebugEvent::ReferenceScanRead(memory) => {
+                    self.scanned_memory = Some(memory);
+                    self.find_references();
+                }
+                DebugEvent::MemoryGoTo(address) => {
+                    self.show_in_memory(address);
+                }
+                DebugEvent::MemoryReadFailed(id, error) => {
+                    // Pinned views re-read at every stop, so the error stays in the view rather than the console
+                    if let Some(view) = self.memory_views.iter_mut().find(|view| view.id == id) {
+                        view.read_error = Some(error.clone());
+                    }
                     info!("Event: Memory read failed: {error}");
                 }
                 DebugEvent::CommandCompleted(command) => {
                     info!("Event: Command completed: {command:?}");
                     // Update target state if needed
                     match command {
-                        GdbCommand::Continue => {
+                        GdbCommand::Continue | GdbCommand::ContinueUntilHit(_, _) | GdbCommand::RunToAddress(_)
+                        | GdbCommand::ReverseContinue | GdbCommand::Restart => {
+                            self.target_state = TargetState::Running;
+                            self.console_output.push_str("Target is now running\n");
+                        }
+                        GdbCommand::Run(_, _) => {
+                            // The program is now the debug target, as if attached
+                            self.is_attached = true;
+                            self.launched = true;
                             self.target_state = TargetState::Running;
                             self.console_output.push_str("Target is now running\n");
                         }
-                        GdbCommand::StepOver | GdbCommand::StepInto | GdbCommand::StepOut => {
+                        GdbCommand::StepOver | GdbCommand::StepInto | GdbCommand::StepOut | GdbCommand::StepInstructions(_)
+                        | GdbCommand::ReverseStepInto | GdbCommand::ReverseStepOver => {
                             // self.target_state = TargetState::Stopped;
                             self.console_output.push_str("Step completed\n");
                         }
                         GdbCommand::Interrupt => {
                             self.console_output.push_str("Target interrupted\n");
                         }
+                        GdbCommand::WriteMemory(_, _) => {
+                            // Re-read the displayed regions to confirm the write; views may overlap
+                            self.refresh_memory_views(false);
+                        }
                         _ => {}
                     }
                 }
                 DebugEvent::CommandFailed(command, error) => {
                     error!("Event: Command failed: {command:?} - {error}");
-                    self.console_output.push_str(&format!("Command failed: {command:?} - {error}\n"));
+                    self.console_output.push_error(&format!("Command failed: {command:?} - {error}\n"));
+                    if let GdbCommand::LoadExecutable(path) = &command {
+                        self.error_message = format!("Could not load {path}: {error}");
+                    }
+                    if let GdbCommand::SetMiLog(Some(_)) = &command {
+                        self.mi_log_enabled = false;
+                    }
+                    if let GdbCommand::RunToAddress(_) = &command {
+                        // The command processor already removed the temporary breakpoint
+                        self.run_to_breakpoint = None;
+                    }
+                    if let GdbCommand::ReadMemoryChunk(id, generation, _, _, _) = &command {
+                        // Keep what was read; the rest of the range is abandoned
+                        if let Some(view) = self.memory_views.iter_mut().find(|view| view.id == *id) {
+                            if view.chunked_read.as_ref().is_some_and(|read| read.generation == *generation) {
+                                view.chunked_read = None;
+                                view.read_error = Some(error.clone());
+                            }
+                        }
+                    }
+                    if is_remote_disconnect(&error) {
+                        self.remote_connection_lost();
+                    }
+                }
+                DebugEvent::Notification(text) => {
+                    if self.show_notifications {
+                        self.console_output.push_str(&format!("{text}\n"));
+                    }
+                }
+                DebugEvent::ThreadRunning(id) => {
+                    // In non-stop mode the others may still be stopped; the next refresh has their frames
+                    for thread in &mut self.threads.threads {
+                        if id.is_none_or(|id| id == thread.id) {
+                            thread.state = "running".to_string();
+                            thread.frame = None;
+                        }
+                    }
+                }
+                DebugEvent::MemoryChanged(address, len) => {
+                    let ids: Vec<usize> = self.memory_views.iter()
+                        .filter(|view| view.pinned && view.overlaps(address, len))
+                        .map(|view| view.id)
+                        .collect();
+                    for id in ids {
+                        self.read_memory(id);
+                    }
+                }
+                DebugEvent::RecordingChanged(recording) => {
+                    self.is_recording = recording;
+                    self.console_output.push_str(if recording { "Recording execution\n" } else { "Recording stopped\n" });
                 }
                 DebugEvent::GdbConnectionLost => {
                     error!("Event: GDB connection lost");
-                    self.console_output.push_str("GDB connection lost!\n");
+                    self.console_output.push_error("GDB connection lost!\n");
                     self.is_debugging = false;
                     self.is_attached = false;
+                    self.is_recording = false;
                     self.target_state = TargetState::Detached;
+                    self.watch_tree.clear();
                 }
                 DebugEvent::TargetStateChanged(new_state) => {
                     info!("Event: Target state changed to: {new_state:?}");
@@ -978,83 +3465,422 @@ impl eframe::App for KatoriApp {
                     match new_state {
                         TargetState::Running => {
                             self.console_output.push_str("Target is running\n");
+                            self.signal_alert = None;
+                            self.exit_banner = None;
                         }
                         TargetState::Stopped => {
                             self.console_output.push_str("Target stopped\n");
+                            // GDB selects the innermost frame on every stop
+                            self.selected_frame = 0;
+                            self.assembly_follow_pc = true;
+                            // BreakpointHit arrives first and clears the target when it was reached
+                            self.abandon_hit_target("Stopped");
+                            self.abandon_run_to_address();
                             // Auto-refresh debug info when stopped
                             if let Err(e) = self.command_sender.send(GdbCommand::RefreshDebugInfo) {
                                 error!("Failed to send RefreshDebugInfo command: {e}");
                             }
+                            self.evaluate_watches();
+                            self.refresh_memory_views(true);
                         }
                         TargetState::Detached => {
                             self.console_output.push_str("Target detached\n");
                             self.clear_debug_info();
                         }
+                        TargetState::Exited(status) => {
+                            self.console_output.push_str(&format!("{status}\n"));
+                            self.abandon_hit_target("Program exited");
+                            self.abandon_run_to_address();
+                            self.clear_debug_info();
+                            self.exit_banner = Some(status);
+                        }
+                        TargetState::CoreDump => {
+                            self.console_output.push_str("Core file loaded (read-only)\n");
+                            if let Err(e) = self.command_sender.send(GdbCommand::RefreshDebugInfo) {
+                                error!("Failed to send RefreshDebugInfo command: {e}");
+                            }
+                        }
                     }
                 }
             }
         }
         
-        // Menu bar
-        egui::TopBottomPanel::top("menubar").show(ctx, |ui| {
-            egui::menu::bar(ui, |ui| {
-                ui.menu_button("File", |ui| {
-                    if ui.button("Exit").clicked() {
-                        std::process::exit(0);
-                    }
+        // Developer overlay listing every GDB/MI record
+        if self.developer_mode && self.show_mi_overlay {
+            egui::Window::new("GDB/MI Events")
+                .open(&mut self.show_mi_overlay)
+                .default_size([520.0, 320.0])
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} records", self.mi_log.len()));
+                        if ui.button("Clear").clicked() {
+                            self.mi_log.clear();
+                        }
+                    });
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .id_salt("mi_log_scroll")
+                        .auto_shrink([false, false])
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for entry in &self.mi_log {
+                                ui.label(egui::RichText::new(entry.to_string()).monospace().color(entry.color()));
+                            }
+                        });
                 });
-                
-                ui.menu_button("Debug", |ui| {
-                    if ui.button("Start Session").clicked() {
-                        self.start_gdb_session();
-                        ui.close_menu();
+        }
+        
+        // Closing while attached to a process asks what to do with it first
+        if ctx.input(|i| i.viewport().close_requested()) && self.is_attached && self.current_pid.is_some() && self.exit_action.is_none() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.show_exit_confirmation = true;
+        }
+        if self.show_exit_confirmation {
+            egui::Window::new("Close Katori")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    if let Some(pid) = self.current_pid {
+                        ui.label(format!("Katori is attached to process {pid}."));
                     }
-                    if ui.button("Stop Session").clicked() {
-                        self.stop_gdb_session();
-                        ui.close_menu();
+                    ui.horizontal(|ui| {
+                        if ui.button("Detach (leave running)").clicked() {
+                            self.exit_action = Some(ExitAction::Detach);
+                        }
+                        if ui.button("Kill").clicked() {
+                            self.exit_action = Some(ExitAction::Kill);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_exit_confirmation = false;
+                        }
+                    });
+                });
+            if self.exit_action.is_some() {
+                self.show_exit_confirmation = false;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+        
+        // Open executable dialog
+        if self.show_open_executable {
+            let mut open = true;
+            egui::Window::new("Open Executable")
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.executable_input)
+                            .hint_text("Path to executable")
+                            .desired_width(360.0),
+                    );
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Load").clicked() || submitted {
+                        self.load_executable();
                     }
-                    ui.separator();
-                    if ui.button("Attach").clicked() {
-                        self.attach_to_target();
-                        ui.close_menu();
+                });
+            if !open {
+                self.show_open_executable = false;
+            }
+        }
+        
+        // Run settings dialog
+        if self.show_run_settings {
+            egui::Window::new("Run Settings")
+                .open(&mut self.show_run_settings)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("Arguments (one per line)");
+                    ui.add(egui::TextEdit::multiline(&mut self.run_arguments)
+                        .desired_rows(4)
+                        .desired_width(360.0));
+                    ui.label("Working directory");
+                    ui.add(egui::TextEdit::singleline(&mut self.run_directory)
+                        .hint_text("GDB's current directory")
+                        .desired_width(360.0));
+                    ui.label("Environment");
+                    let mut remove = None;
+                    egui::Grid::new("run_environment").num_columns(3).show(ui, |ui| {
+                        for (index, (name, value)) in self.run_environment.iter_mut().enumerate() {
+                            ui.add(egui::TextEdit::singleline(name).hint_text("NAME").desired_width(120.0));
+                            ui.add(egui::TextEdit::singleline(value).hint_text("value").desired_width(200.0));
+                            if ui.small_button("✕").clicked() {
+                                remove = Some(index);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if let Some(index) = remove {
+                        let (name, _) = self.run_environment.remove(index);
+                        if !name.trim().is_empty() {
+                            self.removed_environment.push(name.trim().to_string());
+                        }
                     }
-                    if ui.button("Detach").clicked() {
-                        self.detach_from_target();
-                        ui.close_menu();
+                    if ui.button("Add variable").clicked() {
+                        self.run_environment.push((String::new(), String::new()));
                     }
+                    ui.weak("Applied the next time the program is run");
                 });
-                
-                ui.menu_button("View", |ui| {
-                    ui.checkbox(&mut self.show_registers, "Registers");
-                    ui.checkbox(&mut self.show_assembly, "Assembly");
-                    ui.checkbox(&mut self.show_stack, "Stack");
-                    ui.checkbox(&mut self.show_memory, "Memory");
-                    ui.checkbox(&mut self.show_console, "Console");
-                });
-            });
-        });
+        }
         
-        // Toolbar
-        egui::TopBottomPanel::top("toolbar").show(ctx, |ui| {
-            ui.horizontal(|ui| {
+        // Preferences
+        if self.show_preferences {
+            let mut open = true;
+            let mut mi_log_changed = false;
+            egui::Window::new("Preferences")
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    mi_log_changed |= ui.checkbox(&mut self.mi_log_enabled, "Log MI traffic").changed();
+                    ui.add_enabled_ui(!self.mi_log_enabled, |ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.mi_log_path)
+                            .hint_text("Log file")
+                            .desired_width(360.0));
+                    });
+                    ui.weak("Every line sent to and received from GDB is appended, with a timestamp");
+                });
+            if mi_log_changed {
+                self.send_mi_log();
+            }
+            if !open {
+                self.show_preferences = false;
+            }
+        }
+        
+        // References to an address found through the palette
+        if self.show_references {
+            let mut open = true;
+            egui::Window::new("References")
+                .open(&mut open)
+                .default_width(320.0)
+                .show(ctx, |ui| {
+                    match self.reference_target {
+                        Some(target) => ui.monospace(format!("Value 0x{target:x}")),
+                        None => ui.label("Address could not be parsed"),
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label("Scan");
+                        ui.add(egui::TextEdit::singleline(&mut self.reference_scan_address).hint_text("Start").desired_width(120.0));
+                        ui.add(egui::TextEdit::singleline(&mut self.reference_scan_size).hint_text("Bytes").desired_width(60.0));
+                        if ui.button("Read").clicked() {
+                            self.scan_memory_for_references();
+                        }
+                        if ui.button("Refresh").clicked() {
+                            self.find_references();
+                        }
+                    });
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .id_salt("references_scroll")
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            if self.reference_results.is_empty() {
+                                ui.label("No references in loaded data");
+                            }
+                            for location in &self.reference_results {
+                                ui.monospace(location.to_string());
+                            }
+                        });
+                });
+            if !open {
+                self.show_references = false;
+            }
+        }
+        
+        // Command palette
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::P)) {
+            self.show_palette = true;
+        }
+        if self.show_palette {
+            let mut open = true;
+            egui::Window::new("Go to")
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+                .show(ctx, |ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.palette_input)
+                            .hint_text("Symbol, address or expression")
+                            .desired_width(320.0),
+                    );
+                    response.request_focus();
+                    
+                    ui.horizontal(|ui| {
+                        if ui.button("Disassembly").clicked() || ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            self.run_palette(GoToTarget::Disassembly);
+                        }
+                        if ui.button("Memory").clicked() {
+                            self.run_palette(GoToTarget::Memory);
+                        }
+                        if ui.button("Breakpoint").clicked() {
+                            self.run_palette(GoToTarget::Breakpoint);
+                        }
+                        if ui.button("References").clicked() {
+                            self.run_palette(GoToTarget::References);
+                        }
+                    });
+                });
+            if !open || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.show_palette = false;
+            }
+        }
+        
+        // Menu bar
+        egui::TopBottomPanel::top("menubar").show(ctx, |ui| {
+            egui::MenuBar::new().ui(ui, |ui| {
+                ui.menu_button("File", |ui| {
+                    if ui.button("Open Executable...").clicked() {
+                        self.show_open_executable = true;
+                        ui.close();
+                    }
+                    if ui.button("Run Settings...").clicked() {
+                        self.show_run_settings = true;
+                        ui.close();
+                    }
+                    if ui.button("Preferences...").clicked() {
+                        self.show_preferences = true;
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("Exit").clicked() {
+                        // Close through eframe so on_exit can stop the GDB session
+                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
+                });
+                
+                ui.menu_button("Debug", |ui| {
+                    if ui.button("Start Session").clicked() {
+                        self.start_gdb_session();
+                        ui.close();
+                    }
+                    if ui.button("Stop Session").clicked() {
+                        self.stop_gdb_session();
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("Attach").clicked() {
+                        self.attach_to_target();
+                        ui.close();
+                    }
+                    if ui.button("Detach").clicked() {
+                        self.detach_from_target();
+                        ui.close();
+                    }
+                });
+                
+                ui.menu_button("Go", |ui| {
+                    if ui.button("Go to... (Ctrl+P)").clicked() {
+                        self.show_palette = true;
+                        ui.close();
+                    }
+                });
+                
+                ui.menu_button("View", |ui| {
+                    ui.checkbox(&mut self.show_registers, "Registers");
+                    ui.checkbox(&mut self.show_assembly, "Assembly");
+                    ui.checkbox(&mut self.show_source, "Source");
+                    ui.checkbox(&mut self.show_stack, "Stack");
+                    ui.checkbox(&mut self.show_threads, "Threads");
+                    ui.checkbox(&mut self.show_watch, "Watch");
+                    ui.add_enabled(
+                        has_vector_registers(self.target_arch.as_deref()),
+                        egui::Checkbox::new(&mut self.show_vector_lanes, "Vector register lanes"),
+                    );
+                    ui.checkbox(&mut self.collapse_wide_registers, "Fold float/vector registers");
+                    ui.checkbox(&mut self.show_memory, "Memory");
+                    ui.checkbox(&mut self.show_console, "Console");
+                    ui.checkbox(&mut self.show_program_output, "Program Output");
+                    ui.checkbox(&mut self.show_notifications, "GDB notifications in console")
+                        .on_hover_text("Libraries loaded, threads and processes started or exited");
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Repaint while running (ms):");
+                        ui.add(egui::DragValue::new(&mut self.running_repaint_ms).range(16..=2000));
+                    });
+                    ui.menu_button("Disassembly Theme", |ui| {
+                        self.code_theme.ui(ui);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("x86 disassembly:");
+                        let previous_flavor = self.disassembly_flavor.clone();
+                        for (flavor, label) in DISASSEMBLY_FLAVORS {
+                            ui.radio_value(&mut self.disassembly_flavor, flavor.to_string(), *label);
+                        }
+                        // The adapter keeps the flavor for sessions started later
+                        if self.disassembly_flavor != previous_flavor && self.send_disassembly_flavor() && self.is_attached {
+                            // Re-disassemble in the new syntax
+                            self.refresh_debug_info();
+                        }
+                    });
+                    ui.separator();
+                    if ui.checkbox(&mut self.developer_mode, "Developer mode").changed() && !self.developer_mode {
+                        self.show_mi_overlay = false;
+                        self.mi_log.clear();
+                    }
+                    ui.add_enabled(self.developer_mode, egui::Checkbox::new(&mut self.show_mi_overlay, "GDB/MI event overlay"));
+                });
+            });
+        });
+        
+        // Toolbar
+        egui::TopBottomPanel::top("toolbar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
                 // Debug control buttons
+                let can_execute = self.target_state.can_execute();
                 ui.separator();
-                if ui.button("▶ Continue").clicked() {
+                if ui.add_enabled(can_execute, egui::Button::new("▶ Continue")).clicked() {
                     self.continue_execution();
                 }
-                if ui.button("⏸ Break").clicked() {
+                if ui.add_enabled(can_execute, egui::Button::new("⏸ Break")).clicked() {
                     self.interrupt_execution();
                 }
+                let can_restart = self.target_state.can_restart()
+                    || (self.launched && self.is_attached && self.target_state.can_kill());
+                if ui.add_enabled(can_restart, egui::Button::new("⟲ Restart")).clicked() {
+                    self.restart_program();
+                }
+                if ui.add_enabled(self.executable.is_some() && !self.is_attached, egui::Button::new("🚀 Run")).clicked() {
+                    self.run_program();
+                }
+                if ui.add_enabled(self.is_attached && self.target_state.can_kill(), egui::Button::new("⏹ Kill")).clicked() {
+                    self.kill_program();
+                }
                 ui.separator();
-                if ui.button("⬇ Step Into").clicked() {
+                if ui.add_enabled(can_execute, egui::Button::new("⬇ Step Into")).clicked() {
                     self.step_into();
                 }
-                if ui.button("➡ Step Over").clicked() {
+                if ui.add_enabled(can_execute, egui::Button::new("➡ Step Over")).clicked() {
                     self.step_over();
                 }
-                if ui.button("⬆ Step Out").clicked() {
+                if ui.add_enabled(can_execute, egui::Button::new("⬆ Step Out")).clicked() {
                     self.step_out();
                 }
+                ui.add(egui::DragValue::new(&mut self.step_count).range(1..=100_000));
+                if ui.add_enabled(can_execute, egui::Button::new("⏩ Step N")).on_hover_text("Step this many instructions").clicked() {
+                    self.step_instructions(self.step_count);
+                }
+                ui.separator();
+                let record_label = if self.is_recording { "⏹ Stop Recording" } else { "⏺ Record" };
+                let record = ui.add_enabled(can_execute, egui::Button::new(record_label))
+                    .on_hover_text("Record execution so it can be stepped backwards");
+                if record.clicked() {
+                    self.set_recording(!self.is_recording);
+                }
+                if self.is_recording {
+                    if ui.add_enabled(can_execute, egui::Button::new("◀ Reverse Continue")).clicked() {
+                        self.reverse_execution(GdbCommand::ReverseContinue);
+                    }
+                    if ui.add_enabled(can_execute, egui::Button::new("⬅ Reverse Step Over")).clicked() {
+                        self.reverse_execution(GdbCommand::ReverseStepOver);
+                    }
+                    if ui.add_enabled(can_execute, egui::Button::new("⬆ Reverse Step Into")).clicked() {
+                        self.reverse_execution(GdbCommand::ReverseStepInto);
+                    }
+                }
                 ui.separator();
                 if ui.button("🔄 Refresh").clicked() {
                     self.refresh_debug_info();
@@ -1080,19 +3906,86 @@ impl eframe::App for KatoriApp {
                 ui.label("Attach to:");
                 ui.selectable_value(&mut self.attach_mode, AttachMode::GdbServer, "GDB Server");
                 ui.selectable_value(&mut self.attach_mode, AttachMode::Process, "Process");
+                ui.selectable_value(&mut self.attach_mode, AttachMode::CoreDump, "Core Dump");
                 
                 match self.attach_mode {
                     AttachMode::GdbServer => {
                         ui.label("Host:Port:");
                         ui.text_edit_singleline(&mut self.current_host_port);
+                        if ui.checkbox(&mut self.auto_reconnect, "Auto-reconnect")
+                            .on_hover_text("Re-attach with increasing delays when the connection drops")
+                            .changed() && !self.auto_reconnect
+                        {
+                            self.reconnect = None;
+                        }
+                        ui.add_enabled(
+                            self.auto_reconnect,
+                            egui::DragValue::new(&mut self.reconnect_attempts).range(1..=20).suffix(" tries"),
+                        );
                     }
                     AttachMode::Process => {
                         ui.label("PID:");
                         ui.text_edit_singleline(&mut self.pid_input);
                     }
+                    AttachMode::CoreDump => {
+                        ui.label("Executable:");
+                        ui.add(egui::TextEdit::singleline(&mut self.core_executable_input)
+                            .hint_text("Path to executable")
+                            .desired_width(200.0));
+                        ui.label("Core:");
+                        ui.add(egui::TextEdit::singleline(&mut self.core_file_input)
+                            .hint_text("Path to core file")
+                            .desired_width(200.0));
+                    }
+                }
+                
+                ui.label("Arch:");
+                let previous_arch = self.architecture.clone();
+                egui::ComboBox::from_id_salt("architecture")
+                    .selected_text(&self.architecture)
+                    .show_ui(ui, |ui| {
+                        for arch in SUPPORTED_ARCHITECTURES {
+                            ui.selectable_value(&mut self.architecture, arch.to_string(), *arch);
+                        }
+                    });
+                // Sent even before attaching: the adapter applies it when GDB starts,
+                // so raw stubs that don't report their ISA disassemble correctly
+                if self.architecture != previous_arch {
+                    self.send_architecture();
+                    if self.is_attached {
+                        // Re-disassemble with the new ISA
+                        self.refresh_debug_info();
+                    }
+                }
+                
+                ui.label("Width:");
+                let previous_width = self.instruction_width;
+                let width_label = |width: Option<u32>| width.map_or("Auto".to_string(), |width| format!("{width} bytes"));
+                egui::ComboBox::from_id_salt("instruction_width")
+                    .selected_text(width_label(self.instruction_width))
+                    .show_ui(ui, |ui| {
+                        for width in [None, Some(2), Some(4)] {
+                            ui.selectable_value(&mut self.instruction_width, width, width_label(width));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Bytes per instruction when disassembling; Auto uses 2 for Thumb code");
+                if self.instruction_width != previous_width {
+                    self.send_instruction_width();
+                    if self.is_attached {
+                        self.refresh_debug_info();
+                    }
                 }
                 
-                if ui.button("Attach").clicked() {
+                if ui.add_enabled(!self.is_attached, egui::Checkbox::new(&mut self.non_stop, "Non-stop"))
+                    .on_hover_text("Threads stop and resume individually; Break and Continue apply to them all")
+                    .changed()
+                {
+                    self.send_non_stop();
+                }
+                
+                let attach_label = if self.attach_mode == AttachMode::CoreDump { "Load" } else { "Attach" };
+                if ui.button(attach_label).clicked() {
                     self.attach_to_target();
                 }
                 
@@ -1106,15 +3999,158 @@ impl eframe::App for KatoriApp {
         egui::TopBottomPanel::top("breakpoint_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.label("Breakpoint:");
-                ui.text_edit_singleline(&mut self.breakpoint_input);
+                egui::ComboBox::from_id_salt("breakpoint_kind")
+                    .selected_text(self.breakpoint_kind.label())
+                    .show_ui(ui, |ui| {
+                        for kind in [LocationKind::Function, LocationKind::FileLine, LocationKind::Address] {
+                            ui.selectable_value(&mut self.breakpoint_kind, kind, kind.label());
+                        }
+                    });
+                let hint = match self.breakpoint_kind {
+                    LocationKind::Function => "function",
+                    LocationKind::FileLine => "source file",
+                    LocationKind::Address => "0x08000100",
+                };
+                ui.add(egui::TextEdit::singleline(&mut self.breakpoint_input).hint_text(hint));
+                if self.breakpoint_kind == LocationKind::FileLine {
+                    ui.label("line");
+                    ui.add(egui::DragValue::new(&mut self.breakpoint_line_input).range(1..=u32::MAX));
+                }
+                ui.add(egui::TextEdit::singleline(&mut self.breakpoint_condition_input)
+                    .hint_text("condition")
+                    .desired_width(120.0));
+                ui.label("ignore");
+                ui.add(egui::DragValue::new(&mut self.breakpoint_ignore_input))
+                    .on_hover_text("Hits to skip before stopping");
+                ui.checkbox(&mut self.breakpoint_temporary, "Temp").on_hover_text("Delete after the first stop");
+                ui.checkbox(&mut self.breakpoint_hardware, "HW").on_hover_text("Hardware breakpoint");
+                ui.checkbox(&mut self.breakpoint_pending, "Pending")
+                    .on_hover_text("Keep the breakpoint if the location isn't loaded yet, e.g. in a shared library");
                 if ui.button("Add").clicked() {
                     self.set_breakpoint();
                 }
+            });
+            
+            if !self.breakpoints.is_empty() {
+                let mut remove = None;
+                let mut toggle = None;
+                let mut reset_hits = None;
+                let mut continue_until = None;
+                let can_continue = self.target_state == TargetState::Stopped;
+                let hits: Vec<u32> = self.breakpoints.iter().map(|bp| self.hits_since_reset(bp)).collect();
+                let breakpoints = &self.breakpoints;
+                let continue_hits_input = &mut self.continue_hits_input;
+                egui::CollapsingHeader::new(format!("Breakpoints ({})", breakpoints.len()))
+                    .id_salt("breakpoint_table_header")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui_extras::TableBuilder::new(ui)
+                            .striped(true)
+                            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                            .id_salt("breakpoint_table")
+                            .max_scroll_height(140.0)
+                            .column(Column::auto())
+                            .column(Column::auto())
+                            .column(Column::auto())
+                            .column(Column::auto().at_least(180.0))
+                            .column(Column::auto())
+                            .column(Column::auto())
+                            .column(Column::remainder())
+                            .header(18.0, |mut header| {
+                                for title in ["On", "#", "Type", "Location", "Address", "Hits", ""] {
+                                    header.col(|ui| { ui.strong(title); });
+                                }
+                            })
+                            .body(|mut body| {
+                                for (bp, &hits) in breakpoints.iter().zip(&hits) {
+                                    let number = bp.number.parse::<u32>().ok();
+                                    body.row(18.0, |mut row| {
+                                        row.col(|ui| {
+                                            let mut enabled = bp.enabled == "y";
+                                            if ui.checkbox(&mut enabled, "").on_hover_text("Enabled").changed() {
+                                                toggle = number.map(|number| (number, enabled));
+                                            }
+                                        });
+                                        row.col(|ui| { ui.label(&bp.number); });
+                                        row.col(|ui| { ui.label(&bp.breakpoint_type); });
+                                        row.col(|ui| {
+                                            let mut text = format!("{}{}", breakpoint_label(bp), breakpoint_options(bp));
+                                            if bp.has_multiple_locations() {
+                                                text.push_str(&format!(" ({} locations)", bp.locations.len()));
+                                            }
+                                            let response = ui.label(text);
+                                            let response = if bp.has_multiple_locations() {
+                                                let locations = bp.locations.iter()
+                                                    .map(|loc| format!("#{} {} @ {}{}", loc.number, loc.function.as_deref().unwrap_or("??"), loc.address.as_deref().unwrap_or("?"), if loc.enabled == "y" { "" } else { " (disabled)" }))
+                                                    .collect::<Vec<_>>()
+                                                    .join("\n");
+                                                response.on_hover_text(locations)
+                                            } else {
+                                                response.on_hover_text("Right-click for hit count options")
+                                            };
+                                            // Breakpoint details: hit count and continuing to a later hit
+                                            response.context_menu(|ui| {
+                                                ui.label(format!("Hit {} times ({hits} since reset)", bp.times));
+                                                if ui.button("Reset hit count").clicked() {
+                                                    reset_hits = Some(bp.number.clone());
+                                                    ui.close();
+                                                }
+                                                ui.separator();
+                                                ui.horizontal(|ui| {
+                                                    ui.add(egui::DragValue::new(continue_hits_input).range(1..=u32::MAX));
+                                                    let button = ui.add_enabled(can_continue && number.is_some(), egui::Button::new("Continue until hit"));
+                                                    if button.on_hover_text("Continue until this breakpoint has been hit this many more times").clicked() {
+                                                        continue_until = number.map(|number| (number, *continue_hits_input));
+                                                        ui.close();
+                                                    }
+                                                });
+                                            });
+                                        });
+                                        row.col(|ui| { ui.monospace(bp.address.as_deref().unwrap_or("")); });
+                                        row.col(|ui| {
+                                            ui.label(hits.to_string()).on_hover_text(format!("{} in total", bp.times));
+                                        });
+                                        row.col(|ui| {
+                                            if ui.small_button("✕").on_hover_text("Delete").clicked() {
+                                                remove = number;
+                                            }
+                                        });
+                                    });
+                                }
+                            });
+                    });
+                if let Some((number, enabled)) = toggle {
+                    self.toggle_breakpoint(number, enabled);
+                }
+                if let Some(number) = reset_hits {
+                    self.reset_hit_count(&number);
+                }
+                if let Some((number, hits)) = continue_until {
+                    self.continue_until_hit(number, hits);
+                }
+                if let Some(number) = remove {
+                    self.remove_breakpoint(number);
+                }
+            }
+            
+            ui.horizontal(|ui| {
+                ui.label("Watchpoint:");
+                ui.text_edit_singleline(&mut self.watchpoint_input);
+                egui::ComboBox::from_id_salt("watch_kind")
+                    .selected_text(self.watch_kind.to_string())
+                    .show_ui(ui, |ui| {
+                        for kind in [WatchKind::Write, WatchKind::Read, WatchKind::Access] {
+                            ui.selectable_value(&mut self.watch_kind, kind, kind.to_string());
+                        }
+                    });
+                if ui.button("Add").clicked() {
+                    self.set_watchpoint();
+                }
                 
                 ui.separator();
-                ui.label("Breakpoints:");
-                for (i, bp) in self.breakpoints.iter().enumerate() {
-                    ui.label(format!("#{} {}", i + 1, bp));
+                ui.label("Watchpoints:");
+                for wp in &self.watchpoints {
+                    ui.label(format!("#{} {} ({})", wp.number, wp.expression, wp.kind));
                 }
             });
         });
@@ -1131,21 +4167,116 @@ impl eframe::App for KatoriApp {
             });
         }
         
+        // Status bar
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(format_latency(self.latency_monitor.last(), self.latency_monitor.average()))
+                    .on_hover_text("Round-trip time of GDB commands: the last one, and the average of recent ones");
+                if let Some(arch) = &self.target_arch {
+                    ui.separator();
+                    ui.label(arch).on_hover_text("Target architecture");
+                }
+            });
+        });
+        
+        // Program output, above the console
+        if self.show_program_output {
+            egui::TopBottomPanel::bottom("program_output").min_height(100.0).resizable(true).show(ctx, |ui| {
+                ui.label("Program Output:");
+                egui::ScrollArea::vertical()
+                    .id_salt("program_output_scroll")
+                    .stick_to_bottom(true)
+                    .max_height((ui.available_height() - 30.0).max(0.0))
+                    .show(ui, |ui| {
+                        let text: String = self.program_output.entries().map(|entry| entry.text.as_str()).collect();
+                        ui.monospace(text);
+                    });
+                
+                ui.horizontal(|ui| {
+                    ui.monospace("stdin>");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.program_input)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY)
+                            .hint_text("Input for the program, sent with Enter"),
+                    );
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        self.send_program_input();
+                        response.request_focus();
+                    }
+                });
+            });
+        }
+        
         // Console at bottom
         if self.show_console {
             egui::TopBottomPanel::bottom("console").min_height(150.0).show(ctx, |ui| {
-                ui.label("Console Output:");
+                ui.horizontal(|ui| {
+                    ui.label("Console Output:");
+                    ui.separator();
+                    for kind in ConsoleKind::ALL {
+                        let mut shown = !self.hidden_console_kinds.contains(&kind);
+                        if ui.checkbox(&mut shown, kind.label()).changed() {
+                            if shown {
+                                self.hidden_console_kinds.remove(&kind);
+                            } else {
+                                self.hidden_console_kinds.insert(kind);
+                            }
+                        }
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.console_timestamps, "Timestamps");
+                });
                 egui::ScrollArea::vertical()
                     .id_salt("console_scroll")
                     .stick_to_bottom(true)
+                    .max_height((ui.available_height() - 30.0).max(0.0))
                     .show(ui, |ui| {
-                        ui.monospace(&self.console_output);
+                        let font = egui::TextStyle::Monospace.resolve(ui.style());
+                        let mut job = egui::text::LayoutJob::default();
+                        for entry in self.console_output.entries().filter(|entry| !self.hidden_console_kinds.contains(&entry.kind)) {
+                            let color = match entry.kind {
+                                ConsoleKind::Info => ui.visuals().text_color(),
+                                ConsoleKind::Gdb => egui::Color32::LIGHT_BLUE,
+                                ConsoleKind::Target => egui::Color32::LIGHT_GREEN,
+                                ConsoleKind::Log => egui::Color32::DARK_GRAY,
+                                ConsoleKind::Error => egui::Color32::LIGHT_RED,
+                            };
+                            if self.console_timestamps {
+                                let stamp = format!("[{:9.3}] ", self.console_output.elapsed(entry));
+                                job.append(&stamp, 0.0, egui::TextFormat::simple(font.clone(), egui::Color32::GRAY));
+                            }
+                            job.append(&entry.text, 0.0, egui::TextFormat::simple(font.clone(), color));
+                        }
+                        ui.label(job);
                     });
+                
+                ui.horizontal(|ui| {
+                    ui.monospace("(gdb)");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.raw_command_input)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY)
+                            .hint_text("MI (-exec-next) or CLI (info registers) command"),
+                    );
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        self.send_raw_command();
+                        response.request_focus();
+                    } else if response.has_focus() {
+                        if ui.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
+                            if let Some(previous) = self.command_history.previous() {
+                                self.raw_command_input = previous.to_string();
+                            }
+                        } else if ui.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
+                            self.raw_command_input = self.command_history.next().unwrap_or_default().to_string();
+                        }
+                    }
+                });
             });
         }
 
-        // Right sidebar for registers and stack
-        if self.show_registers || self.show_stack {
+        // Right sidebar for registers, threads and stack
+        if self.show_registers || self.show_threads || self.show_watch || self.show_stack {
             egui::SidePanel::right("debug_sidebar")
                 .min_width(250.0)
                 .default_width(300.0)
@@ -1154,14 +4285,42 @@ impl eframe::App for KatoriApp {
                 .show(ctx, |ui| {
                     // Registers panel (top half of sidebar)
                     if self.show_registers {
-                        ui.heading("Registers");
+                        ui.horizontal(|ui| {
+                            ui.heading("Registers");
+                            let previous_format = self.register_format;
+                            egui::ComboBox::from_id_salt("register_format")
+                                .selected_text(self.register_format.to_string())
+                                .show_ui(ui, |ui| {
+                                    for format in RegisterFormat::ALL {
+                                        ui.selectable_value(&mut self.register_format, format, format.to_string());
+                                    }
+                                });
+                            if self.register_format != previous_format {
+                                self.send_register_format();
+                            }
+                            let mut show_all = self.register_set == RegisterSet::All;
+                            let toggle = ui.checkbox(&mut show_all, "Show all")
+                                .on_hover_text("Include system and debug registers (slower to refresh)");
+                            if toggle.changed() {
+                                self.register_set = if show_all { RegisterSet::All } else { RegisterSet::Core };
+                                self.send_register_set();
+                            }
+                            if ui.add_enabled(!self.changed_registers.is_empty(), egui::Button::new("Clear diff"))
+                                .on_hover_text("Stop highlighting changes; later stops compare against the current values")
+                                .clicked()
+                            {
+                                self.reset_register_baseline();
+                            }
+                        });
                         
-                        let available_height = if self.show_stack {
+                        let available_height = if self.show_stack || self.show_threads || self.show_watch {
                             ui.available_height() * 0.5
                         } else {
                             ui.available_height()
                         };
                         
+                        let mut register_actions = Vec::new();
+                        let mut visible_registers = None;
                         ui.allocate_ui_with_layout(
                             egui::Vec2::new(ui.available_width(), available_height),
                             egui::Layout::top_down(egui::Align::LEFT),
@@ -1173,16 +4332,205 @@ impl eframe::App for KatoriApp {
                                         if self.registers.is_empty() {
                                             ui.label("No register data");
                                         } else {
-                                            for reg in &self.registers {
-                                                ui.horizontal(|ui| {
-                                                    ui.monospace(format!("{:8}", reg.name));
-                                                    ui.monospace(&reg.value);
-                                                });
+                                            let show_lanes = self.show_vector_lanes && has_vector_registers(self.target_arch.as_deref());
+                                            let pinned = pinned_registers(&self.registers, &self.pinned_registers);
+                                            if !pinned.is_empty() {
+                                                ui.label(egui::RichText::new("Pinned").strong());
+                                                for reg in &pinned {
+                                                    let views = if show_lanes { reg.vector_views() } else { None };
+                                                    let changed = self.changed_registers.contains(&reg.number);
+                                                    show_register_row(ui, reg, views, changed, true, self.register_format, &mut register_actions);
+                                                }
+                                                ui.separator();
+                                            }
+                                            let is_pinned = |reg: &Register| pinned.iter().any(|pin| pin.number == reg.number);
+                                            
+                                            let groups = group_registers(self.target_arch.as_deref(), &self.registers);
+                                            let show_headings = groups.len() > 1;
+                                            let mut folded = Vec::new();
+                                            for (group, mut registers) in groups {
+                                                // Pinned registers are only shown at the top
+                                                registers.retain(|reg| !is_pinned(reg));
+                                                if registers.is_empty() {
+                                                    continue;
+                                                }
+                                                let show_group = |ui: &mut egui::Ui| {
+                                                    for reg in registers {
+                                                        let views = if show_lanes && group == RegisterGroup::Vector { reg.vector_views() } else { None };
+                                                        let changed = self.changed_registers.contains(&reg.number);
+                                                        show_register_row(ui, reg, views, changed, false, self.register_format, &mut register_actions);
+                                                    }
+                                                };
+                                                if show_headings {
+                                                    // Float and vector registers are numerous and rarely needed, so start folded
+                                                    let folded_by_default = self.collapse_wide_registers && matches!(group, RegisterGroup::Float | RegisterGroup::Vector);
+                                                    let open = egui::CollapsingHeader::new(egui::RichText::new(group.label()).strong())
+                                                        .id_salt(("register_group", group.label(), self.collapse_wide_registers))
+                                                        .default_open(!folded_by_default)
+                                                        .show(ui, show_group)
+                                                        .body_returned
+                                                        .is_some();
+                                                    if !open {
+                                                        folded.push(group);
+                                                    }
+                                                } else {
+                                                    show_group(ui);
+                                                }
                                             }
+                                            // Folded groups aren't read at stops, pinned registers always are
+                                            let pinned: Vec<u32> = pinned.iter().map(|reg| reg.number).collect();
+                                            let groups = group_registers(self.target_arch.as_deref(), &self.registers);
+                                            visible_registers = visible_register_numbers(&groups, &folded, &pinned);
                                         }
                                     });
                             }
                         );
+                        for action in register_actions {
+                            match action {
+                                RegisterAction::Follow(address) => self.show_in_memory(format!("0x{address:x}")),
+                                RegisterAction::CopyAll => {
+                                    ui.ctx().copy_text(format_registers(self.target_arch.as_deref(), &self.registers));
+                                }
+                                RegisterAction::TogglePin(name) => self.toggle_register_pin(&name),
+                            }
+                        }
+                        // With no registers (e.g. after an exit) there is no frame to read them from
+                        if visible_registers != self.register_subset && !self.registers.is_empty() {
+                            self.register_subset = visible_registers;
+                            self.send_register_subset();
+                        }
+                        
+                        if self.show_stack || self.show_threads || self.show_watch {
+                            ui.separator();
+                        }
+                    }
+                    
+                    // Threads panel (between registers and stack)
+                    if self.show_threads {
+                        ui.heading("Threads");
+                        let mut selected_thread = None;
+                        let mut interrupted_thread = None;
+                        egui::ScrollArea::vertical()
+                            .id_salt("threads_scroll")
+                            .max_height(120.0)
+                            .auto_shrink([false, true])
+                            .show(ui, |ui| {
+                                if self.threads.threads.is_empty() {
+                                    ui.label("No thread data");
+                                }
+                                for thread in &self.threads.threads {
+                                    let is_current = self.threads.current_thread_id == Some(thread.id);
+                                    let location = thread.frame.as_ref()
+                                        .map(|frame| frame.function.clone().unwrap_or_else(|| format_address(&frame.address)))
+                                        .unwrap_or_else(|| thread.state.clone());
+                                    let label = format!(
+                                        "{} {} {} ({})",
+                                        if is_current { "*" } else { " " },
+                                        thread.id,
+                                        thread.name.as_deref().unwrap_or(&thread.target_id),
+                                        location,
+                                    );
+                                    let response = ui.selectable_label(is_current, egui::RichText::new(label).monospace());
+                                    if response.clicked() && !is_current {
+                                        selected_thread = Some(thread.id);
+                                    }
+                                    if self.non_stop && thread.state == "running" {
+                                        response.context_menu(|ui| {
+                                            if ui.button("Interrupt thread").clicked() {
+                                                interrupted_thread = Some(thread.id);
+                                                ui.close();
+                                            }
+                                        });
+                                    }
+                                }
+                            });
+                        if let Some(id) = selected_thread {
+                            self.select_thread(id);
+                        }
+                        if let Some(id) = interrupted_thread {
+                            self.interrupt_thread(id);
+                        }
+                        
+                        if self.show_stack || self.show_watch {
+                            ui.separator();
+                        }
+                    }
+                    
+                    // Watch expressions, re-evaluated on every stop
+                    if self.show_watch {
+                        ui.heading("Watch");
+                        ui.horizontal(|ui| {
+                            let response = ui.add(
+                                egui::TextEdit::singleline(&mut self.watch_expression_input)
+                                    .hint_text("Expression")
+                                    .desired_width(ui.available_width() - 40.0),
+                            );
+                            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                            if ui.button("Add").clicked() || submitted {
+                                self.add_watch_expression();
+                            }
+                        });
+                        let mut removed = None;
+                        let mut expand = None;
+                        let mut actions = Vec::new();
+                        egui::ScrollArea::vertical()
+                            .id_salt("watch_scroll")
+                            .max_height(240.0)
+                            .auto_shrink([false, true])
+                            .show(ui, |ui| {
+                                for (index, expression) in self.watch_expressions.iter().enumerate() {
+                                    let value = self.watch_values.iter()
+                                        .find(|(watched, _)| watched == expression)
+                                        .map(|(_, value)| value);
+                                    let root = self.watch_tree.root(expression);
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("✕").clicked() {
+                                            removed = Some(index);
+                                        }
+                                        if let Some(root) = root {
+                                            show_varobj_toggle(ui, &self.watch_tree, root, &mut actions);
+                                        } else if matches!(value, Some(Ok(_))) && ui.small_button("▸").on_hover_text("Show members").clicked() {
+                                            expand = Some(expression.clone());
+                                        }
+                                        ui.monospace(format!("{expression} ="));
+                                        match value {
+                                            Some(Ok(value)) if self.changed_watches.contains(expression) => {
+                                                ui.label(egui::RichText::new(value).monospace().color(egui::Color32::YELLOW))
+                                                    .on_hover_text("Changed since the last stop");
+                                            }
+                                            Some(Ok(value)) => { ui.monospace(value); }
+                                            Some(Err(error)) => { ui.colored_label(egui::Color32::LIGHT_RED, format!("<error: {error}>")); }
+                                            None => { ui.weak("<not evaluated>"); }
+                                        }
+                                    });
+                                    if let Some(root) = root.filter(|root| self.watch_tree.is_expanded(&root.name)) {
+                                        show_varobj_children(ui, &self.watch_tree, root, &mut actions);
+                                    }
+                                }
+                            });
+                        if let Some(index) = removed {
+                            let expression = self.watch_expressions.remove(index);
+                            if let Some(name) = self.watch_tree.remove_root(&expression) {
+                                self.delete_varobj(name);
+                            }
+                        }
+                        if let Some(expression) = expand {
+                            self.expand_watch(expression);
+                        }
+                        for action in actions {
+                            match action {
+                                VarObjAction::Toggle(name) if self.watch_tree.is_expanded(&name) => {
+                                    self.watch_tree.set_expanded(&name, false);
+                                }
+                                VarObjAction::Toggle(name) => {
+                                    self.watch_tree.set_expanded(&name, true);
+                                    if !self.watch_tree.is_listed(&name) {
+                                        self.list_varobj_children(name);
+                                    }
+                                }
+                                VarObjAction::More(name) => self.list_varobj_children(name),
+                            }
+                        }
                         
                         if self.show_stack {
                             ui.separator();
@@ -1192,6 +4540,7 @@ impl eframe::App for KatoriApp {
                     // Stack frames panel (bottom half of sidebar)
                     if self.show_stack {
                         ui.heading("Stack Frames");
+                        let mut selected_frame = None;
                         egui::ScrollArea::both()
                             .id_salt("stack_scroll")
                             .auto_shrink([false, false])
@@ -1201,14 +4550,20 @@ impl eframe::App for KatoriApp {
                                 } else {
                                     for frame in &self.stack_frames {
                                         let display = if let Some(func) = &frame.function {
-                                            format!("#{} {} @ 0x{}", frame.level, func, frame.address)
+                                            format!("#{} {} @ {}", frame.level, format_call(func, &frame.args), format_address(&frame.address))
                                         } else {
-                                            format!("#{} @ 0x{}", frame.level, frame.address)
+                                            format!("#{} @ {}", frame.level, format_address(&frame.address))
                                         };
-                                        ui.monospace(display);
+                                        let is_selected = frame.level == self.selected_frame;
+                                        if ui.selectable_label(is_selected, egui::RichText::new(display).monospace()).clicked() && !is_selected {
+                                            selected_frame = Some(frame.level);
+                                        }
                                     }
                                 }
                             });
+                        if let Some(level) = selected_frame {
+                            self.select_frame(level);
+                        }
                     }
                 });
         }
@@ -1220,153 +4575,1789 @@ impl eframe::App for KatoriApp {
                 .default_height(250.0)
                 .resizable(true)
                 .show(ctx, |ui| {
-                    ui.heading("Memory Viewer");
                     ui.horizontal(|ui| {
-                        ui.label("Address:");
-                        ui.text_edit_singleline(&mut self.memory_address);
-                        ui.label("Size:");
-                        ui.add(egui::DragValue::new(&mut self.memory_size).speed(1.0));
-                        if ui.button("Read").clicked() {
-                            self.read_memory();
+                        ui.heading("Memory Viewer");
+                        if ui.button("Add view").clicked() {
+                            self.add_memory_view();
                         }
                     });
                     
-                    ui.separator();
-                    
-                    // Memory display
+                    // Memory views, stacked; double-click a byte to edit it (single-byte view only)
+                    let mut read = None;
+                    let mut remove = None;
+                    let mut commit = None;
                     egui::ScrollArea::vertical()
                         .id_salt("memory_scroll")
                         .show(ui, |ui| {
-                            if self.memory_data.is_none() {
-                                ui.label("No memory data");
-                            } else {
-                                let data = self.memory_data.as_ref().unwrap();
-                                egui_extras::TableBuilder::new(ui)
-                                    .striped(true)
-                                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                                    .id_salt("memory_table")
-                                    .column(Column::auto())
-                                    .column(Column::remainder())
-                                    .header(20.0, |mut header| {
-                                        header.col(|ui| { ui.label("Offset");});
-                                        header.col(|ui| { ui.label("Contents");});
-                                    })
-                                    .body(|mut body|{
-                                        let first_data_offset = u32::from_str_radix(&data.begin.trim_start_matches("0x"), 16).unwrap();
-                                        let contents: Vec<String> = data.contents
-                                            .chars()
-                                            .collect::<Vec<_>>()
-                                            .chunks(16 * 2)
-                                            .map(|chunk| chunk.iter().collect::<String>())
-                                            .collect();
-
-                                        body.rows(20.0, contents.len(), |mut row| {
-                                            let i = row.index();
-
-                                            row.col(|ui| {ui.label(format!("{:08X}", first_data_offset + (i as u32) * 16)); });
-                                            row.col(|ui| {
-                                                ui.monospace(&contents[i]);
+                            for view in &mut self.memory_views {
+                                let id = view.id;
+                                ui.separator();
+                                ui.horizontal(|ui| {
+                                    ui.label("Address:");
+                                    ui.text_edit_singleline(&mut view.address);
+                                    ui.label("Size:");
+                                    ui.add(egui::DragValue::new(&mut view.size).speed(1.0));
+                                    if ui.button("Read").clicked() {
+                                        read = Some(id);
+                                    }
+                                    if ui.add_enabled(view.data.is_some(), egui::Button::new("Copy"))
+                                        .on_hover_text("Copy as a hex dump in the current layout")
+                                        .clicked()
+                                    {
+                                        if let Some(dump) = view.hex_dump() {
+                                            ui.ctx().copy_text(dump);
+                                        }
+                                    }
+                                    ui.checkbox(&mut view.pinned, "Pin")
+                                        .on_hover_text("Re-read this range whenever the target stops");
+                                    if let Some(chunked_read) = &view.chunked_read {
+                                        ui.add(egui::ProgressBar::new(chunked_read.progress())
+                                            .desired_width(120.0)
+                                            .text(format!("{} / {} bytes", chunked_read.offset, chunked_read.size)));
+                                        if ui.small_button("Cancel").on_hover_text("Stop reading; keep what has arrived").clicked() {
+                                            view.chunked_read = None;
+                                        }
+                                    }
+                                    
+                                    ui.separator();
+                                    egui::ComboBox::from_id_salt(("memory_bytes_per_row", id))
+                                        .selected_text(format!("{} per row", view.layout.bytes_per_row))
+                                        .show_ui(ui, |ui| {
+                                            for n in BYTES_PER_ROW_OPTIONS {
+                                                ui.selectable_value(&mut view.layout.bytes_per_row, n, format!("{n} per row"));
+                                            }
+                                        });
+                                    egui::ComboBox::from_id_salt(("memory_group_size", id))
+                                        .selected_text(format!("{}-byte words", view.layout.group_size))
+                                        .show_ui(ui, |ui| {
+                                            for n in GROUP_SIZE_OPTIONS {
+                                                ui.selectable_value(&mut view.layout.group_size, n, format!("{n}-byte words"));
+                                            }
+                                        });
+                                    ui.add_enabled_ui(view.layout.group_size > 1, |ui| {
+                                        egui::ComboBox::from_id_salt(("memory_endian", id))
+                                            .selected_text(view.layout.endian.label())
+                                            .show_ui(ui, |ui| {
+                                                for endian in [Endian::Little, Endian::Big] {
+                                                    ui.selectable_value(&mut view.layout.endian, endian, endian.label());
+                                                }
+                                            });
+                                    });
+                                    if ui.small_button("✖").on_hover_text("Close this view").clicked() {
+                                        remove = Some(id);
+                                    }
+                                });
+                                
+                                if let Some(error) = &view.read_error {
+                                    ui.colored_label(egui::Color32::LIGHT_RED, format!("Read failed: {error}"));
+                                }
+                                let Some(data) = &view.data else {
+                                    ui.label("No memory data");
+                                    continue;
+                                };
+                                let memory_edit = &mut view.edit;
+                                let layout = view.layout;
+                                ui.push_id(("memory_view", id), |ui| {
+                                    egui_extras::TableBuilder::new(ui)
+                                        .striped(true)
+                                        .vscroll(false)
+                                        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                                        .id_salt("memory_table")
+                                        .column(Column::auto())
+                                        .column(Column::auto())
+                                        .column(Column::remainder())
+                                        .header(20.0, |mut header| {
+                                            header.col(|ui| { ui.label("Offset");});
+                                            header.col(|ui| { ui.label("Contents");});
+                                            header.col(|ui| { ui.label("ASCII");});
+                                        })
+                                        .body(|body|{
+                                            let first_data_offset = parse_address(&data.begin).unwrap_or(0);
+                                            let bytes = decode_hex(&data.contents);
+                                            let per_row = layout.bytes_per_row;
+
+                                            body.rows(20.0, bytes.len().div_ceil(per_row), |mut row| {
+                                                let i = row.index();
+                                                let row_address = first_data_offset + (i * per_row) as u64;
+                                                let row_bytes = &bytes[i * per_row..bytes.len().min((i + 1) * per_row)];
+
+                                                row.col(|ui| {
+                                                    ui.add(egui::Label::new(format!("{row_address:08X}")).sense(egui::Sense::click()))
+                                                        .context_menu(|ui| {
+                                                            if ui.button("Copy row").clicked() {
+                                                                ui.ctx().copy_text(format_hex_dump(row_address, row_bytes, &layout));
+                                                                ui.close();
+                                                            }
+                                                        });
+                                                });
+                                                row.col(|ui| {
+                                                    if layout.group_size > 1 {
+                                                        ui.monospace(format_hex_row(row_bytes, &layout));
+                                                        return;
+                                                    }
+                                                    ui.spacing_mut().item_spacing.x = 4.0;
+                                                    for (j, value) in row_bytes.iter().enumerate() {
+                                                        let address = row_address + j as u64;
+                                                        let byte = format!("{value:02x}");
+                                                        match memory_edit {
+                                                            Some((edit_address, text)) if *edit_address == address => {
+                                                                let response = ui.add(
+                                                                    egui::TextEdit::singleline(text)
+                                                                        .font(egui::TextStyle::Monospace)
+                                                                        .char_limit(2)
+                                                                        .desired_width(18.0),
+                                                                );
+                                                                response.request_focus();
+                                                                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                                                                    *memory_edit = None;
+                                                                } else if response.lost_focus() {
+                                                                    commit = memory_edit.take();
+                                                                }
+                                                            }
+                                                            _ => {
+                                                                let label = egui::Label::new(egui::RichText::new(&byte).monospace())
+                                                                    .sense(egui::Sense::click());
+                                                                if ui.add(label).double_clicked() {
+                                                                    *memory_edit = Some((address, byte));
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                });
+                                                row.col(|ui| {
+                                                    ui.monospace(format_ascii(row_bytes, &layout));
+                                                });
                                             });
                                         });
-                                    })
-                                    ;
+                                });
                             }
                         });
+                    if let Some(id) = read {
+                        self.read_memory(id);
+                    }
+                    if let Some(id) = remove {
+                        self.memory_views.retain(|view| view.id != id);
+                    }
+                    if let Some((address, text)) = commit {
+                        self.write_memory_byte(address, &text);
+                    }
                 });
         }
         
-        // Main content area - Assembly takes the remaining space
+        // Main content area - Source and assembly share the remaining space
         egui::CentralPanel::default().show(ctx, |ui| {
-            if self.show_assembly {
-                ui.heading("Assembly");
-                egui::ScrollArea::vertical()
-                    .id_salt("assembly_scroll")
-                    .auto_shrink([false, false])
+            if let Some(alert) = &self.signal_alert {
+                let text = alert.to_string();
+                let mut view = false;
+                let mut dismiss = false;
+                egui::Frame::new()
+                    .fill(egui::Color32::from_rgb(150, 30, 30))
+                    .inner_margin(6.0)
+                    .corner_radius(4.0)
                     .show(ui, |ui| {
-                        if self.assembly_lines.is_empty() {
-                            ui.centered_and_justified(|ui| {
-                                ui.label("No assembly data available");
+                        ui.set_width(ui.available_width());
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(text).strong().color(egui::Color32::WHITE));
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                dismiss = ui.button("Dismiss").clicked();
+                                view = ui.button("View faulting frame").clicked();
                             });
-                        } else {
-                            let text = self.assembly_lines.iter()
-                                .map(|line| format!("{}: {}",line.address, line.instruction))
-                                .collect::<Vec<_>>()
-                                .join("\n");
+                        });
+                    });
+                if view {
+                    self.view_faulting_frame();
+                }
+                if dismiss {
+                    self.signal_alert = None;
+                }
+            }
+            
+            if let Some(status) = &self.exit_banner {
+                let text = status.to_string();
+                let mut restart = false;
+                let mut dismiss = false;
+                egui::Frame::new()
+                    .fill(egui::Color32::from_rgb(45, 60, 90))
+                    .inner_margin(6.0)
+                    .corner_radius(4.0)
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(text).strong().color(egui::Color32::WHITE));
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                dismiss = ui.button("Dismiss").clicked();
+                                restart = ui.button("Restart").clicked();
+                            });
+                        });
+                    });
+                if restart {
+                    self.restart_program();
+                }
+                if dismiss {
+                    self.exit_banner = None;
+                }
+            }
+            
+            if self.show_source {
+                ui.heading("Source");
+                let height = if self.show_assembly { ui.available_height() * 0.5 } else { ui.available_height() };
+                ui.allocate_ui(egui::Vec2::new(ui.available_width(), height), |ui| {
+                    self.show_source_view(ui);
+                });
+                if self.show_assembly {
+                    ui.separator();
+                }
+            }
+            
+            if self.show_assembly {
+                let mut text = Vec::new();
+                // Instruction address and, in analysis mode, target of each displayed line;
+                // source lines have neither
+                let mut line_addresses = Vec::new();
+                let mut line_targets = Vec::new();
+                let symbols = self.assembly_analysis.then(|| SymbolMap::new(&self.assembly_lines));
+                let mut previous_source = None;
+                for line in &self.assembly_lines {
+                    // Source lines go above their first instruction, as comments
+                    if line.source.is_some() && line.source != previous_source {
+                        if let Some(source) = &line.source {
+                            text.push(format!("{ASSEMBLY_GUTTER}{}", format_source_line(&mut self.source_files, source)));
+                            line_addresses.push(None);
+                            line_targets.push(None);
+                        }
+                    }
+                    previous_source = line.source.clone();
+                    let instruction = match &symbols {
+                        Some(symbols) => annotate(&line.instruction, symbols),
+                        None => line.instruction.clone(),
+                    };
+                    text.push(format!("{ASSEMBLY_GUTTER}{}: {instruction}", format_address(&line.address)));
+                    line_addresses.push(Some(line.address.clone()));
+                    line_targets.push(symbols.as_ref().and_then(|_| target_address(&line.instruction)));
+                }
+                
+                ui.horizontal(|ui| {
+                    ui.heading("Assembly");
+                    ui.separator();
+                    ui.label("Function:");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.disassemble_input)
+                            .hint_text("main or 0x8000124")
+                            .desired_width(160.0),
+                    );
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.add_enabled(self.is_debugging, egui::Button::new("Show")).clicked() || submitted {
+                        self.disassemble_function();
+                    }
+                    ui.checkbox(&mut self.assembly_analysis, "Analysis")
+                        .on_hover_text("Name branch targets; click an instruction to follow its target");
+                    
+                    ui.separator();
+                    ui.label("Find:");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.assembly_search)
+                            .hint_text("mnemonic, register or address")
+                            .desired_width(160.0),
+                    );
+                    let match_count = find_matches(&text, &self.assembly_search).len();
+                    let mut step = 0;
+                    if response.changed() {
+                        self.assembly_match = 0;
+                        self.assembly_jump = true;
+                    }
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        step = 1;
+                        response.request_focus();
+                    }
+                    if ui.add_enabled(match_count > 0, egui::Button::new("◀")).on_hover_text("Previous match").clicked() {
+                        step = match_count - 1;
+                    }
+                    if ui.add_enabled(match_count > 0, egui::Button::new("▶")).on_hover_text("Next match").clicked() {
+                        step = 1;
+                    }
+                    if match_count > 0 && step > 0 {
+                        self.assembly_match = (self.assembly_match + step) % match_count;
+                        self.assembly_jump = true;
+                    }
+                    if match_count > 0 {
+                        ui.label(format!("{}/{match_count}", self.assembly_match + 1));
+                    } else if !self.assembly_search.trim().is_empty() {
+                        ui.weak("No matches");
+                    }
+                });
+                
+                let matches = find_matches(&text, &self.assembly_search);
+                self.assembly_match = self.assembly_match.min(matches.len().saturating_sub(1));
+                let current_match = matches.get(self.assembly_match).copied();
+                let target_line = self.assembly_target.and_then(|target| {
+                    line_addresses.iter().position(|address| address.as_deref().and_then(parse_address) == Some(target))
+                });
+                if target_line.is_some() {
+                    self.assembly_target = None;
+                }
+                let jump_line = if std::mem::take(&mut self.assembly_jump) { current_match } else { None };
+                let mut scroll = egui::ScrollArea::vertical()
+                    .id_salt("assembly_scroll")
+                    .auto_shrink([false, false]);
+                if let Some(line) = jump_line.or(target_line) {
+                    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                    scroll = scroll.vertical_scroll_offset((line as f32 * row_height - ui.available_height() / 3.0).max(0.0));
+                }
+                scroll.show(ui, |ui| {
+                    if self.assembly_lines.is_empty() {
+                        ui.centered_and_justified(|ui| {
+                            ui.label("No assembly data available");
+                        });
+                    } else {
+                        let text = text.join("\n");
+                        
+                        // Search matches, the selected one stronger, then the current PC on top
+                        let match_color = ui.visuals().warn_fg_color.linear_multiply(0.15);
+                        let current_match_color = ui.visuals().warn_fg_color.linear_multiply(0.4);
+                        let mut highlights: Vec<_> = matches.iter()
+                            .map(|&line| (line, if Some(line) == current_match { current_match_color } else { match_color }))
+                            .collect();
+                        let pc = self.stack_frames.iter()
+                            .find(|frame| frame.level == self.selected_frame)
+                            .and_then(|frame| parse_address(&frame.address));
+                        let pc_color = ui.visuals().selection.bg_fill.linear_multiply(0.4);
+                        highlights.extend(line_addresses.iter().enumerate()
+                            .filter(|(_, address)| pc.is_some() && address.as_deref().and_then(parse_address) == pc)
+                            .map(|(line, _)| (line, pc_color)));
 
-                            self.show_code(ui, text);
+                        let (response, clicked_line, galley) = self.show_code(ui, text.clone(), &highlights);
+                        
+                        // Breakpoint dots in the gutter; clicking there sets or removes one
+                        let font = egui::TextStyle::Monospace.resolve(ui.style());
+                        let gutter_width = ui.fonts(|fonts| fonts.glyph_width(&font, ' ')) * ASSEMBLY_GUTTER.len() as f32;
+                        // A wrapped line spans several rows; the dot goes on its first
+                        let line_rows: Vec<egui::Rect> = std::iter::once(0)
+                            .chain(galley.rows.iter().enumerate().filter(|(_, row)| row.ends_with_newline).map(|(index, _)| index + 1))
+                            .filter_map(|index| galley.rows.get(index).map(|row| row.rect()))
+                            .collect();
+                        for (line, address) in line_addresses.iter().enumerate() {
+                            let Some((_, enabled)) = address.as_deref().and_then(parse_address).and_then(|address| breakpoint_at_address(&self.breakpoints, address)) else {
+                                continue;
+                            };
+                            let Some(row) = line_rows.get(line) else {
+                                continue;
+                            };
+                            let center = egui::pos2(response.rect.min.x + gutter_width / 2.0, response.rect.min.y + row.center().y);
+                            let color = if enabled { egui::Color32::RED } else { egui::Color32::GRAY };
+                            ui.painter().circle_filled(center, row.height() * 0.3, color);
                         }
-                    });
-            } else {
+                        let in_gutter = response.interact_pointer_pos().is_some_and(|pos| pos.x - response.rect.min.x < gutter_width);
+                        if response.clicked() {
+                            if in_gutter {
+                                if let Some(address) = clicked_line.and_then(|line| line_addresses.get(line).cloned().flatten()) {
+                                    self.toggle_breakpoint_at_address(&address);
+                                }
+                            } else if let Some(target) = clicked_line.and_then(|line| line_targets.get(line).copied().flatten()) {
+                                self.follow_branch(target);
+                            }
+                        }
+                        if response.secondary_clicked() {
+                            self.assembly_context_address = clicked_line.and_then(|line| line_addresses.get(line).cloned().flatten());
+                        }
+                        let can_execute = self.target_state.can_execute();
+                        let mut run_to = None;
+                        response.context_menu(|ui| {
+                            if ui.button("Copy assembly").clicked() {
+                                ui.ctx().copy_text(text.clone());
+                                ui.close();
+                            }
+                            let Some(address) = &self.assembly_context_address else {
+                                ui.label("No instruction here");
+                                return;
+                            };
+                            if ui.add_enabled(can_execute, egui::Button::new(format!("Run to here ({})", format_address(address)))).clicked() {
+                                run_to = Some(address.clone());
+                                ui.close();
+                            }
+                        });
+                        if let Some(address) = run_to {
+                            self.run_to_address(address);
+                        }
+                    }
+                });
+            } else if !self.show_source {
                 ui.centered_and_justified(|ui| {
                     ui.label("Assembly view disabled");
                 });
             }
         });
+        
+        self.poll_reconnect(ctx);
+        
+        // Idle frames are event-driven: the command processor requests a repaint
+        // whenever something arrives, so only poll while the target is running.
+        if self.target_state == TargetState::Running {
+            ctx.request_repaint_after(std::time::Duration::from_millis(self.running_repaint_ms));
+        }
     }
 }
 
 impl KatoriApp {
-    fn show_code(&mut self, ui: &mut egui::Ui, text: String) {
-        let ps = self.syntax_set.clone();
-        let ts = syntect::highlighting::ThemeSet::load_defaults();
-        let syntax =
-            egui_extras::syntax_highlighting::SyntectSettings { ps, ts };
-
-        // ...elsewhere
-        let theme =egui_extras::syntax_highlighting::CodeTheme::default();
-        let layout = egui_extras::syntax_highlighting::highlight_with(
+    /// Syntax highlight `text`; `language` is a syntax name or file extension
+    fn highlight_code(&self, ui: &egui::Ui, text: &str, language: &str) -> egui::text::LayoutJob {
+        egui_extras::syntax_highlighting::highlight_with(
             ui.ctx(),
             ui.style(),
-            &theme,
-            &text,
-            "ARM",
-            &syntax,
-        );
+            &self.code_theme,
+            text,
+            language,
+            &self.syntax,
+        )
+    }
+
+    /// Source of the current frame with line numbers, the current line highlighted
+    fn show_source_view(&mut self, ui: &mut egui::Ui) {
+        let frame = self.stack_frames.iter().find(|frame| frame.level == self.selected_frame).or(self.stack_frames.first());
+        let Some(frame) = frame.cloned() else {
+            ui.label("No source location");
+            return;
+        };
+        let location = frame.fullname.clone().or(frame.file.clone()).zip(frame.line);
+        let lines = location.as_ref().and_then(|(path, _)| source_lines(&mut self.source_files, path).cloned());
+        let (Some((path, line)), Some(lines)) = (location, lines) else {
+            // No debug info, or the file isn't available on this machine
+            let function = frame.function.as_deref().unwrap_or("??");
+            ui.label(format!("Source not available for {function} at {}", format_address(&frame.address)));
+            return;
+        };
+        
+        let text = lines.join("\n");
+        let mut job = self.highlight_code(ui, &text, source_language(&path));
+        let current = (line as usize).saturating_sub(1);
+        let start: usize = lines.iter().take(current).map(|l| l.len() + 1).sum();
+        let end = start + lines.get(current).map_or(0, |l| l.len());
+        let highlight = ui.visuals().selection.bg_fill.linear_multiply(0.4);
+        for section in &mut job.sections {
+            if (start..=end).contains(&section.byte_range.start) {
+                section.format.background = highlight;
+            }
+        }
+        let numbers = (1..=lines.len())
+            .map(|n| if n == current + 1 { format!(">{n:>5}") } else { format!("{n:>6}") })
+            .collect::<Vec<_>>()
+            .join("\n");
+        
+        let mut scroll = egui::ScrollArea::both()
+            .id_salt("source_scroll")
+            .auto_shrink([false, false]);
+        // Jump to the current line once per new location, leaving the user free to scroll
+        let location = Some((path, line));
+        if self.source_scroll_location != location {
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            scroll = scroll.vertical_scroll_offset((current as f32 * row_height - ui.available_height() / 3.0).max(0.0));
+            self.source_scroll_location = location;
+        }
+        scroll.show(ui, |ui| {
+            ui.horizontal_top(|ui| {
+                ui.add(egui::Label::new(egui::RichText::new(numbers).monospace().weak()).extend());
+                ui.add(egui::Label::new(job).extend());
+            });
+        });
+    }
+
+    /// Show highlighted assembly; also returns the line under the pointer when it was clicked
+    fn show_code(&mut self, ui: &mut egui::Ui, text: String, highlights: &[(usize, egui::Color32)]) -> (egui::Response, Option<usize>, Arc<egui::Galley>) {
+        let mut layout = self.highlight_code(ui, &text, "ARM");
+        // Line backgrounds, later entries winning
+        if !highlights.is_empty() {
+            let line_starts: Vec<usize> = std::iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1)).collect();
+            for section in &mut layout.sections {
+                let line = line_starts.partition_point(|&start| start <= section.byte_range.start) - 1;
+                for (highlighted, color) in highlights {
+                    if *highlighted == line {
+                        section.format.background = *color;
+                    }
+                }
+            }
+        }
+        let galley = ui.fonts(|fonts| fonts.layout_job(layout));
 
         // let layout = egui_extras::syntax_highlighting::highlight(ui.ctx(), ui.style(), &egui_extras::syntax_highlighting::CodeTheme::default(), &text, "arm");
-        ui.add(egui::Label::new(layout));
+        let response = ui.add(egui::Label::new(galley.clone()).sense(egui::Sense::click()));
+        let clicked_line = response.interact_pointer_pos().map(|pos| {
+            let index = galley.cursor_from_pos(pos - response.rect.min).index;
+            text.chars().take(index).filter(|c| *c == '\n').count()
+        });
         // egui_extras::syntax_highlighting::code_view_ui(ui, &egui_extras::syntax_highlighting::CodeTheme::default(), &text, "arm");
 
         // let language = "C"; 
         // let theme =egui_extras::syntax_highlighting::CodeTheme::default();
         // egui_extras::syntax_highlighting::code_view_ui(ui, &theme, &text, language);
 
+        (response, clicked_line, galley)
     }
 
 }
 
-#[derive(Debug)]
-struct MemoryReadResult {
-    offset: String,
-    begin: String,
-    end: String,
-    contents: String,
-}
-
-fn parse_memory(value: &Value) -> Option<MemoryReadResult> {
-
-    let mut offset: Option<String> = None;
-    let mut begin: Option<String> = None;
-    let mut end: Option<String> = None;
-    let mut contents: Option<String> = None;
-
-    let memory_list = value.as_list()?;
-    let first_item = memory_list.get(0)?;
-    let memory_tuple = first_item.as_tuple()?;
-    for (key, val) in memory_tuple.iter() {
-        match key.as_str() {
-            "offset" => offset = val.as_string().map(|s| s.to_string()),
-            "begin" => begin = val.as_string().map(|s| s.to_string()),
-            "end" => end = val.as_string().map(|s| s.to_string()),
-            "contents" => contents = val.as_string().map(|s| s.to_string()),
-            _ => {}
-        }
-    }
-
-    Some(MemoryReadResult {
-        offset: offset?,
-        begin: begin?,
-        end: end?,
-        contents: contents?,
+/// Expressions whose value differs from the previous evaluation; newly added
+/// or previously failing expressions don't count as changed
+fn changed_watch_values(previous: &[(String, Result<String, String>)], current: &[(String, Result<String, String>)]) -> HashSet<String> {
+    current.iter()
+        .filter_map(|(expression, value)| {
+            let value = value.as_ref().ok()?;
+            let (_, old) = previous.iter().find(|(old_expression, _)| old_expression == expression)?;
+            (old.as_ref().ok()? != value).then(|| expression.clone())
+        })
+        .collect()
+}
+
+/// What a click in the registers panel asks for
+enum RegisterAction {
+    /// Show the memory a register points at
+    Follow(u64),
+    CopyAll,
+    /// Pin or unpin the register with this name
+    TogglePin(String),
+}
+
+/// One register: a pin toggle, its name and value. Vector registers fold out
+/// into their lanes when `views` are given.
+fn show_register_row(
+    ui: &mut egui::Ui,
+    reg: &Register,
+    views: Option<Vec<VectorView>>,
+    changed: bool,
+    pinned: bool,
+    format: RegisterFormat,
+    actions: &mut Vec<RegisterAction>,
+) {
+    let show_pin = |ui: &mut egui::Ui, actions: &mut Vec<RegisterAction>| {
+        let hint = if pinned { "Unpin" } else { "Pin to the top" };
+        if ui.selectable_label(pinned, "📌").on_hover_text(hint).clicked() {
+            actions.push(RegisterAction::TogglePin(reg.name.clone()));
+        }
+    };
+    match views {
+        Some(views) => {
+            let mut name = egui::RichText::new(&reg.name).monospace();
+            if changed {
+                name = name.color(egui::Color32::YELLOW);
+            }
+            let id = ui.make_persistent_id(("vector_register", reg.number));
+            egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                .show_header(ui, |ui| {
+                    show_pin(ui, actions);
+                    ui.label(name);
+                })
+                .body(|ui| {
+                    for view in views {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.monospace(format!("{:12}", view.name));
+                            ui.monospace(view.lanes.join(" "));
+                        });
+                    }
+                });
+        }
+        None => {
+            ui.horizontal(|ui| {
+                show_pin(ui, actions);
+                ui.monospace(format!("{:8}", reg.name));
+                let mut text = egui::RichText::new(&reg.value).monospace();
+                if changed {
+                    text = text.color(egui::Color32::YELLOW);
+                }
+                let value = egui::Label::new(text).sense(egui::Sense::click());
+                let address = parse_register_value(&reg.value, format);
+                let mut response = ui.add(value);
+                if changed {
+                    response = response.on_hover_text("Changed since the last stop");
+                }
+                response.context_menu(|ui| {
+                    if ui.add_enabled(address.is_some(), egui::Button::new("Follow in memory")).clicked() {
+                        if let Some(address) = address {
+                            actions.push(RegisterAction::Follow(address));
+                        }
+                        ui.close();
+                    }
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(format!("{} = {}", reg.name, reg.value));
+                        ui.close();
+                    }
+                    if ui.button("Copy all registers").clicked() {
+                        actions.push(RegisterAction::CopyAll);
+                        ui.close();
+                    }
+                });
+            });
+        }
+    }
+}
+
+/// What a click in the watch tree asks for
+enum VarObjAction {
+    /// Open or close a node; opening one whose children aren't listed yet lists them
+    Toggle(String),
+    /// List the next page of a node's children
+    More(String),
+}
+
+/// Open/close button for a variable object that has children
+fn show_varobj_toggle(ui: &mut egui::Ui, tree: &VarObjTree, varobj: &VarObj, actions: &mut Vec<VarObjAction>) {
+    if varobj.has_children() {
+        let icon = if tree.is_expanded(&varobj.name) { "▾" } else { "▸" };
+        if ui.small_button(icon).clicked() {
+            actions.push(VarObjAction::Toggle(varobj.name.clone()));
+        }
+    }
+}
+
+/// Rows for the listed children of `parent` and, below each open one, its own children
+fn show_varobj_children(ui: &mut egui::Ui, tree: &VarObjTree, parent: &VarObj, actions: &mut Vec<VarObjAction>) {
+    ui.indent(&parent.name, |ui| {
+        for child in tree.children(&parent.name) {
+            ui.horizontal(|ui| {
+                show_varobj_toggle(ui, tree, child, actions);
+                let label = ui.monospace(format!("{} =", child.expression));
+                if let Some(type_name) = &child.type_name {
+                    label.on_hover_text(type_name);
+                }
+                let value = child.value.as_deref().unwrap_or_default();
+                if tree.is_out_of_scope(&child.name) {
+                    ui.weak("<out of scope>");
+                } else if tree.is_changed(&child.name) {
+                    ui.label(egui::RichText::new(value).monospace().color(egui::Color32::YELLOW))
+                        .on_hover_text("Changed since the last stop");
+                } else {
+                    ui.monospace(value);
+                }
+            });
+            if tree.is_expanded(&child.name) {
+                show_varobj_children(ui, tree, child, actions);
+            }
+        }
+        if tree.has_more(parent) && ui.small_button("Show more").clicked() {
+            actions.push(VarObjAction::More(parent.name.clone()));
+        }
+    });
+}
+
+/// The breakpoint placed at `address`, itself or through one of its locations, and
+/// whether it is enabled there. Addresses are compared as numbers, since the
+/// disassembly pads them (`0x08000124`) and breakpoints don't (`0x8000124`).
+fn breakpoint_at_address(breakpoints: &[Breakpoint], address: u64) -> Option<(&Breakpoint, bool)> {
+    breakpoints.iter().find_map(|breakpoint| {
+        let enabled = breakpoint.enabled == "y";
+        if breakpoint.address.as_deref().and_then(parse_address) == Some(address) {
+            return Some((breakpoint, enabled));
+        }
+        breakpoint.locations.iter()
+            .find(|location| location.address.as_deref().and_then(parse_address) == Some(address))
+            .map(|location| (breakpoint, enabled && location.enabled == "y"))
+    })
+}
+
+/// Registers to highlight after a refresh: the ones GDB reported as changed, or
+/// if it couldn't say, the ones whose value differs from the baseline. Nothing is
+/// highlighted without a baseline, e.g. on the first refresh after attaching,
+/// when GDB reports every register as changed.
+fn changed_registers(baseline: &HashMap<String, String>, current: &[Register], reported: Option<Vec<u32>>) -> HashSet<u32> {
+    match reported {
+        _ if baseline.is_empty() => HashSet::new(),
+        Some(reported) => current.iter()
+            .map(|reg| reg.number)
+            .filter(|number| reported.contains(number))
+            .collect(),
+        None => changed_register_values(baseline, current),
+    }
+}
+
+/// Registers whose value differs from the baseline; registers not in it don't count
+fn changed_register_values(baseline: &HashMap<String, String>, current: &[Register]) -> HashSet<u32> {
+    current.iter()
+        .filter(|reg| baseline.get(&reg.name).is_some_and(|old| *old != reg.value))
+        .map(|reg| reg.number)
+        .collect()
+}
+
+/// Lines of a source file, read once and cached by path (`None` if unreadable)
+fn source_lines<'a>(source_files: &'a mut HashMap<String, Option<Vec<String>>>, path: &str) -> Option<&'a Vec<String>> {
+    source_files.entry(path.to_string()).or_insert_with(|| {
+        std::fs::read_to_string(path)
+            .map(|contents| contents.lines().map(str::to_string).collect())
+            .map_err(|e| debug!("source_lines: Cannot read {path}: {e}"))
+            .ok()
+    }).as_ref()
+}
+
+/// Syntax for a source file: assembly uses the ARM syntax, anything else is
+/// looked up by extension
+fn source_language(path: &str) -> &str {
+    match std::path::Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("s" | "S" | "asm") => "ARM",
+        Some(ext) => ext,
+        None => "txt",
+    }
+}
+
+/// Render a source location as an assembly comment, including the line's
+/// text when the file can be read locally
+fn format_source_line(source_files: &mut HashMap<String, Option<Vec<String>>>, source: &SourceLine) -> String {
+    let path = source.fullname.clone().unwrap_or_else(|| source.file.clone());
+    let text = source_lines(source_files, &path)
+        .and_then(|lines| lines.get((source.line as usize).checked_sub(1)?))
+        .map(|text| text.trim())
+        .unwrap_or_default();
+    format!("// {}:{}: {}", source.file, source.line, text)
+}
+
+/// Strip a leading `0x`/`0X` from a hex address
+fn strip_hex_prefix(address: &str) -> &str {
+    let address = address.trim();
+    address.strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .unwrap_or(address)
+}
+
+/// Format an address for display with exactly one `0x` prefix, whether or
+/// not GDB already included one
+fn format_address(address: &str) -> String {
+    format!("0x{}", strip_hex_prefix(address))
+}
+
+/// Longest argument value shown in the stack panel before it is cut short
+const MAX_ARGUMENT_LENGTH: usize = 32;
+
+/// A frame as a call, e.g. `main(argc=1, argv=0x7fffffffe0b8)`; long values
+/// (nested structs, strings) are truncated
+fn format_call(function: &str, args: &[gdbadapter::Argument]) -> String {
+    let args: Vec<String> = args.iter()
+        .map(|arg| {
+            let value = arg.value.split_whitespace().collect::<Vec<_>>().join(" ");
+            if value.chars().count() > MAX_ARGUMENT_LENGTH {
+                format!("{}={}…", arg.name, value.chars().take(MAX_ARGUMENT_LENGTH).collect::<String>())
+            } else {
+                format!("{}={value}", arg.name)
+            }
+        })
+        .collect();
+    format!("{function}({})", args.join(", "))
+}
+
+/// Advice for errors the user can fix, appended to the failure message
+fn error_hint(error: &GdbError) -> &'static str {
+    match error {
+        GdbError::NoSymbols(_) => " (open the executable with File > Open Executable to load its symbols)",
+        GdbError::NotRunning(_) => " (run or attach to the program first)",
+        _ => "",
+    }
+}
+
+/// Lines containing `query`, ignoring case; an empty query matches nothing
+fn find_matches(lines: &[String], query: &str) -> Vec<usize> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    lines.iter()
+        .enumerate()
+        .filter(|(_, line)| line.to_lowercase().contains(&query))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Parse a memory viewer edit: exactly two hex digits
+fn parse_hex_byte(text: &str) -> Option<u8> {
+    let text = text.trim();
+    if text.len() == 2 && text.chars().all(|c| c.is_ascii_hexdigit()) {
+        u8::from_str_radix(text, 16).ok()
+    } else {
+        None
+    }
+}
+
+/// Whether input is a plain, possibly namespace-qualified, identifier such as `ns::init`
+fn is_symbol_name(input: &str) -> bool {
+    let input = input.trim();
+    input.split("::").all(|part| {
+        part.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
     })
 }
+
+/// Parse input that is exactly a hex literal such as `0x8000124`
+fn hex_literal(input: &str) -> Option<String> {
+    let input = input.trim();
+    let digits = input.strip_prefix("0x").or_else(|| input.strip_prefix("0X"))?;
+    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(format_address(digits))
+    } else {
+        None
+    }
+}
+
+/// Whether a GDB value is a function, e.g. `{int (int, char **)} 0x401136 <main>`
+fn is_function_value(value: &str) -> bool {
+    value.starts_with('{') && value.ends_with('>')
+}
+
+/// Find the first hex address in a GDB value such as `{int (int)} 0x401136 <main>`
+/// or `(int *) 0x20000000 <buffer>`
+fn extract_address(value: &str) -> Option<String> {
+    value.split(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == '{' || c == '}')
+        .find_map(hex_literal)
+}
+
+/// Maximum number of entries kept in the console command history
+const COMMAND_HISTORY_LIMIT: usize = 100;
+
+/// In-memory history of console commands, navigated with up/down
+#[derive(Debug, Default)]
+struct CommandHistory {
+    entries: Vec<String>,
+    /// Index into `entries` while browsing, `None` when editing a fresh line
+    position: Option<usize>,
+}
+
+impl CommandHistory {
+    fn push(&mut self, command: String) {
+        if self.entries.last() != Some(&command) {
+            self.entries.push(command);
+            if self.entries.len() > COMMAND_HISTORY_LIMIT {
+                self.entries.remove(0);
+            }
+        }
+        self.position = None;
+    }
+    
+    /// Step back to an older command (up arrow)
+    fn previous(&mut self) -> Option<&str> {
+        let position = match self.position {
+            Some(0) => 0,
+            Some(position) => position - 1,
+            None => self.entries.len().checked_sub(1)?,
+        };
+        self.position = Some(position);
+        self.entries.get(position).map(|s| s.as_str())
+    }
+    
+    /// Step forward to a newer command (down arrow); `None` once past the newest
+    fn next(&mut self) -> Option<&str> {
+        let position = self.position? + 1;
+        if position < self.entries.len() {
+            self.position = Some(position);
+            self.entries.get(position).map(|s| s.as_str())
+        } else {
+            self.position = None;
+            None
+        }
+    }
+}
+
+/// Maximum number of records kept by the developer event overlay
+const MI_LOG_LIMIT: usize = 1000;
+
+/// Kind of record shown in the developer event overlay
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MiRecordKind {
+    Result,
+    ErrorResult,
+    Async,
+    Stream,
+    ConnectionLost,
+}
+
+/// One line of the developer event overlay
+#[derive(Debug, Clone, PartialEq)]
+struct MiLogEntry {
+    kind: MiRecordKind,
+    token: Option<u32>,
+    class: String,
+    detail: String,
+}
+
+impl MiLogEntry {
+    fn from_event(event: &GdbEvent) -> Self {
+        let fields = |results: &std::collections::HashMap<String, Value>| {
+            let mut keys: Vec<_> = results.keys().collect();
+            keys.sort();
+            keys.into_iter()
+                .map(|key| format!("{}={}", key, format_mi_value(&results[key])))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        
+        match event {
+            GdbEvent::Result(result) => MiLogEntry {
+                kind: if result.class == ResultClass::Error { MiRecordKind::ErrorResult } else { MiRecordKind::Result },
+                token: result.token,
+                class: format!("^{:?}", result.class).to_lowercase(),
+                detail: fields(&result.results),
+            },
+            GdbEvent::Async(record) => MiLogEntry {
+                kind: MiRecordKind::Async,
+                token: record.token,
+                class: format!("{:?}", record.class),
+                detail: fields(&record.results),
+            },
+            GdbEvent::Stream(stream) => MiLogEntry {
+                kind: MiRecordKind::Stream,
+                token: None,
+                class: format!("{:?}", stream.stream_type),
+                detail: format!("{:?}", stream.content),
+            },
+            GdbEvent::Prompt => MiLogEntry {
+                kind: MiRecordKind::Stream,
+                token: None,
+                class: "Prompt".to_string(),
+                detail: "(gdb)".to_string(),
+            },
+            GdbEvent::ConnectionLost => MiLogEntry {
+                kind: MiRecordKind::ConnectionLost,
+                token: None,
+                class: "ConnectionLost".to_string(),
+                detail: String::new(),
+            },
+        }
+    }
+    
+    fn color(&self) -> egui::Color32 {
+        match self.kind {
+            MiRecordKind::Result => egui::Color32::LIGHT_GREEN,
+            MiRecordKind::ErrorResult | MiRecordKind::ConnectionLost => egui::Color32::LIGHT_RED,
+            MiRecordKind::Async => egui::Color32::LIGHT_BLUE,
+            MiRecordKind::Stream => egui::Color32::GRAY,
+        }
+    }
+}
+
+impl std::fmt::Display for MiLogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let token = self.token.map(|t| t.to_string()).unwrap_or_else(|| "-".to_string());
+        write!(f, "[{token:>4}] {} {}", self.class, self.detail)
+    }
+}
+
+/// Turn console input into the command passed to `send_command`: MI commands
+/// lose their leading `-`, anything else runs through the CLI interpreter
+fn raw_to_mi(input: &str) -> String {
+    let input = input.trim();
+    match input.strip_prefix('-') {
+        Some(mi_command) => mi_command.to_string(),
+        None => format!("interpreter-exec console {}", gdbadapter::quote_mi_string(input)),
+    }
+}
+
+/// Render a result record back into MI-like text, e.g. `^done,bkpt={number="1"}`
+fn format_mi_result(result: &GdbResult) -> String {
+    let class = format!("{:?}", result.class).to_lowercase();
+    let mut keys: Vec<_> = result.results.keys().collect();
+    keys.sort();
+    let mut text = format!("^{class}");
+    for key in keys {
+        text.push_str(&format!(",{}={}", key, format_mi_value(&result.results[key])));
+    }
+    text
+}
+
+/// Render a value in MI syntax with tuple keys sorted for stable output
+fn format_mi_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{s:?}"),
+        Value::List(items) => {
+            let items: Vec<_> = items.iter().map(format_mi_value).collect();
+            format!("[{}]", items.join(","))
+        }
+        Value::Tuple(tuple) => {
+            let mut keys: Vec<_> = tuple.keys().collect();
+            keys.sort();
+            let fields: Vec<_> = keys.into_iter()
+                .map(|key| format!("{}={}", key, format_mi_value(&tuple[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+    }
+}
+
+/// Human-readable summary of a stop, e.g. "Stopped (breakpoint-hit) at breakpoint 1 in main (main.c:12)"
+fn describe_stop(info: &StopInfo) -> String {
+    let mut text = match &info.reason {
+        Some(reason) => format!("Stopped ({reason})"),
+        None => "Stopped".to_string(),
+    };
+    if let Some(signal) = &info.signal_name {
+        text.push_str(&format!(" with {signal}"));
+    }
+    if let Some(number) = &info.breakpoint_number {
+        text.push_str(&format!(" at breakpoint {number}"));
+    }
+    if let Some(frame) = &info.frame {
+        match (&frame.function, &frame.file, frame.line) {
+            (Some(func), Some(file), Some(line)) => text.push_str(&format!(" in {func} ({file}:{line})")),
+            (Some(func), _, _) => text.push_str(&format!(" in {func} @ {}", format_address(&frame.address))),
+            _ => text.push_str(&format!(" @ {}", format_address(&frame.address))),
+        }
+    }
+    text
+}
+
+/// Console line for a notify record worth showing, e.g. "Loaded library /lib/libc.so.6 at 0x7ffff7fc5000".
+///
+/// Breakpoint modifications are left out: they arrive on every hit, which the
+/// stop message already reports.
+fn describe_notification(record: &AsyncRecord) -> Option<String> {
+    let field = |name: &str| record.results.get(name).and_then(|value| value.as_string());
+    match record.class {
+        AsyncClass::LibraryLoaded => {
+            let name = field("target-name").or(field("id"))?;
+            // Newer GDBs report where the library was mapped
+            let from = record.results.get("ranges")
+                .and_then(|ranges| ranges.as_list())
+                .and_then(|ranges| ranges.first())
+                .and_then(|range| range.as_tuple())
+                .and_then(|range| range.get("from"))
+                .and_then(|from| from.as_string());
+            Some(match from {
+                Some(from) => format!("Loaded library {name} at {from}"),
+                None => format!("Loaded library {name}"),
+            })
+        }
+        AsyncClass::LibraryUnloaded => Some(format!("Unloaded library {}", field("target-name").or(field("id"))?)),
+        AsyncClass::ThreadCreated => Some(format!("Thread {} created", field("id")?)),
+        AsyncClass::ThreadExited => Some(format!("Thread {} exited", field("id")?)),
+        AsyncClass::ThreadGroupStarted => Some(format!("Process {} started", field("pid")?)),
+        AsyncClass::ThreadGroupExited => Some(match field("exit-code") {
+            Some(code) => format!("Process group {} exited with code {code}", field("id")?),
+            None => format!("Process group {} exited", field("id")?),
+        }),
+        AsyncClass::BreakpointCreated => {
+            let bp = record.results.get("bkpt").and_then(|bkpt| bkpt.as_tuple()).and_then(Breakpoint::from_tuple)?;
+            Some(format!("Breakpoint {} created at {}", bp.number, breakpoint_label(&bp)))
+        }
+        AsyncClass::BreakpointDeleted => Some(format!("Breakpoint {} deleted", field("id")?)),
+        _ => None,
+    }
+}
+
+/// Console note for a pending breakpoint that GDB has now placed, e.g. after a library loaded
+fn breakpoint_resolution(previous: &Breakpoint, current: &Breakpoint) -> Option<String> {
+    if !previous.is_pending() || current.is_pending() {
+        return None;
+    }
+    let address = current.address.as_deref().map(|address| format!(" ({address})")).unwrap_or_default();
+    Some(format!("Pending breakpoint {} resolved at {}{address}", current.number, breakpoint_label(current)))
+}
+
+/// Whether a console command loads a core file (`core-file`, `target core`, `-target-select core`)
+fn loads_core_file(command: &str) -> bool {
+    let words: Vec<_> = command.trim().trim_start_matches('-').split_whitespace().collect();
+    matches!(words.as_slice(), ["core-file" | "core", _, ..] | ["target" | "target-select", "core", _, ..])
+}
+
+/// Condition, ignore count and temporary marker, e.g. ` if n > 3 (ignore 2, temporary)`
+fn breakpoint_options(bp: &Breakpoint) -> String {
+    let mut text = bp.condition.as_ref().map(|c| format!(" if {c}")).unwrap_or_default();
+    let mut flags = Vec::new();
+    if bp.is_pending() {
+        flags.push("pending".to_string());
+    }
+    if bp.ignore_count > 0 {
+        flags.push(format!("ignore {}", bp.ignore_count));
+    }
+    if bp.is_temporary() {
+        flags.push("temporary".to_string());
+    }
+    if !flags.is_empty() {
+        text.push_str(&format!(" ({})", flags.join(", ")));
+    }
+    text
+}
+
+/// Short human-readable description of where a breakpoint is set
+fn breakpoint_label(bp: &Breakpoint) -> String {
+    match (&bp.function, &bp.file, bp.line) {
+        (Some(func), Some(file), Some(line)) => format!("{func} ({file}:{line})"),
+        (Some(func), _, _) => func.clone(),
+        (None, Some(file), Some(line)) => format!("{file}:{line}"),
+        _ => bp.original_location.clone()
+            .or_else(|| bp.address.clone())
+            .unwrap_or_else(|| "?".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_breakpoint_input_picks_the_location_kind() {
+        let mut app = KatoriApp::headless();
+        
+        app.set_breakpoint_input("main.c:42").unwrap();
+        assert_eq!(app.breakpoint_location(), Ok(LocationSpec::FileLine("main.c".into(), 42)));
+        
+        app.set_breakpoint_input("*0x08000100").unwrap();
+        assert_eq!(app.breakpoint_location(), Ok(LocationSpec::Address("0x08000100".into())));
+        
+        app.set_breakpoint_input("main").unwrap();
+        assert_eq!(app.breakpoint_location(), Ok(LocationSpec::Function("main".into())));
+        
+        assert!(app.set_breakpoint_input("main.c:0").is_err());
+        assert_eq!(app.breakpoint_location(), Ok(LocationSpec::Function("main".into())));
+    }
+
+    #[test]
+    fn test_breakpoint_hit_maps_to_stopped_state() {
+        let input = r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="1",frame={addr="0x08000100",func="main",args=[],file="main.c",fullname="/src/main.c",line="12",arch="armv7"},thread-id="1",stopped-threads="all""#;
+        let record = match gdbadapter::parse_gdb_output(input).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => record,
+            _ => panic!("Expected async record"),
+        };
+        
+        let events = KatoriApp::stop_events(&record);
+        
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            DebugEvent::ConsoleMessage(message) => {
+                assert_eq!(message, "Stopped (breakpoint-hit) at breakpoint 1 in main (main.c:12)\n");
+            }
+            other => panic!("Expected console message, got {other:?}"),
+        }
+        // The hit is reported before the state change so a pending "continue until hit" sees it first
+        assert!(matches!(&events[1], DebugEvent::BreakpointHit(number) if number == "1"));
+        assert!(matches!(events[2], DebugEvent::TargetStateChanged(TargetState::Stopped)));
+    }
+    
+    #[test]
+    fn test_breakpoint_modified_carries_hit_count() {
+        let input = r#"=breakpoint-modified,bkpt={number="2",type="breakpoint",disp="keep",enabled="y",addr="0x08000200",func="loop",file="main.c",fullname="/src/main.c",line="30",thread-groups=["i1"],times="4",ignore="2",original-location="loop"}"#;
+        let record = match gdbadapter::parse_gdb_output(input).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => record,
+            _ => panic!("Expected async record"),
+        };
+        
+        let events = KatoriApp::gdb_event_to_debug_events(GdbEvent::Async(record));
+        
+        match events.as_slice() {
+            [DebugEvent::BreakpointModified(bp)] => {
+                assert_eq!(bp.number, "2");
+                assert_eq!(bp.times, 4);
+            }
+            other => panic!("Expected breakpoint modification, got {other:?}"),
+        }
+    }
+    
+    #[test]
+    fn test_pending_breakpoint_resolution_is_announced() {
+        let breakpoint = |input: &str| match gdbadapter::parse_gdb_output(input).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => record.results.get("bkpt").and_then(|bkpt| bkpt.as_tuple()).and_then(Breakpoint::from_tuple).unwrap(),
+            _ => panic!("Expected async record"),
+        };
+        let pending = breakpoint(r#"=breakpoint-created,bkpt={number="3",type="breakpoint",disp="keep",enabled="y",addr="<PENDING>",pending="plugin.c:30",times="0",original-location="plugin.c:30"}"#);
+        let resolved = breakpoint(r#"=breakpoint-modified,bkpt={number="3",type="breakpoint",disp="keep",enabled="y",addr="0x7ffff7fc1139",func="plugin_init",file="plugin.c",fullname="/src/plugin.c",line="30",thread-groups=["i1"],times="0",original-location="plugin.c:30"}"#);
+        
+        assert_eq!(breakpoint_label(&pending), "plugin.c:30");
+        assert_eq!(
+            breakpoint_resolution(&pending, &resolved).as_deref(),
+            Some("Pending breakpoint 3 resolved at plugin_init (plugin.c:30) (0x7ffff7fc1139)")
+        );
+        assert_eq!(breakpoint_resolution(&resolved, &resolved), None);
+        assert_eq!(breakpoint_resolution(&pending, &pending), None);
+    }
+    
+    #[test]
+    fn test_breakpoints_found_by_instruction_address() {
+        let breakpoint = |input: &str| match gdbadapter::parse_gdb_output(input).unwrap() {
+            gdbadapter::GdbOutput::Result(result) => result.results.get("bkpt").and_then(|bkpt| bkpt.as_tuple()).and_then(Breakpoint::from_tuple).unwrap(),
+            _ => panic!("Expected result record"),
+        };
+        let breakpoints = vec![
+            breakpoint(r#"^done,bkpt={number="1",type="breakpoint",disp="keep",enabled="n",addr="0x8000124",func="main",times="0"}"#),
+            breakpoint(r#"^done,bkpt={number="2",type="breakpoint",disp="keep",enabled="y",addr="<MULTIPLE>",times="0",locations=[{number="2.1",enabled="y",addr="0x08000200"},{number="2.2",enabled="n",addr="0x08000340"}]}"#),
+            breakpoint(r#"^done,bkpt={number="3",type="breakpoint",disp="keep",enabled="y",addr="<PENDING>",pending="plugin.c:30",times="0"}"#),
+        ];
+        let found = |address: &str| breakpoint_at_address(&breakpoints, parse_address(address).unwrap())
+            .map(|(breakpoint, enabled)| (breakpoint.number.as_str(), enabled));
+        
+        // The disassembly pads addresses that breakpoints report unpadded
+        assert_eq!(found("0x08000124"), Some(("1", false)));
+        assert_eq!(found("0x08000200"), Some(("2", true)));
+        assert_eq!(found("0x8000340"), Some(("2", false)));
+        assert_eq!(found("0x08000126"), None);
+    }
+    
+    #[test]
+    fn test_running_records_name_the_thread() {
+        let running = |input: &str| match gdbadapter::parse_gdb_output(input).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => KatoriApp::gdb_event_to_debug_events(GdbEvent::Async(record)),
+            _ => panic!("Expected async record"),
+        };
+        
+        assert!(matches!(running(r#"*running,thread-id="2""#).as_slice(), [DebugEvent::ThreadRunning(Some(2))]));
+        assert!(matches!(running(r#"*running,thread-id="all""#).as_slice(), [DebugEvent::ThreadRunning(None)]));
+    }
+    
+    #[test]
+    fn test_memory_changed_carries_range() {
+        let record = match gdbadapter::parse_gdb_output(r#"=memory-changed,thread-group="i1",addr="0x20000004",len="0x4""#).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => record,
+            _ => panic!("Expected async record"),
+        };
+        
+        let events = KatoriApp::gdb_event_to_debug_events(GdbEvent::Async(record));
+        
+        assert!(matches!(events.as_slice(), [DebugEvent::MemoryChanged(0x2000_0004, 4)]));
+    }
+    
+    #[test]
+    fn test_notify_records_are_described() {
+        let describe = |input: &str| match gdbadapter::parse_gdb_output(input).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => describe_notification(&record),
+            _ => panic!("Expected async record"),
+        };
+        
+        assert_eq!(
+            describe(r#"=library-loaded,id="/lib/x86_64-linux-gnu/libc.so.6",target-name="/lib/x86_64-linux-gnu/libc.so.6",host-name="/lib/x86_64-linux-gnu/libc.so.6",symbols-loaded="0",thread-group="i1",ranges=[{from="0x00007ffff7c28700",to="0x00007ffff7dbd93d"}]"#).as_deref(),
+            Some("Loaded library /lib/x86_64-linux-gnu/libc.so.6 at 0x00007ffff7c28700")
+        );
+        assert_eq!(describe(r#"=thread-created,id="2",group-id="i1""#).as_deref(), Some("Thread 2 created"));
+        assert_eq!(describe(r#"=thread-group-started,id="i1",pid="4242""#).as_deref(), Some("Process 4242 started"));
+        assert_eq!(describe(r#"=thread-group-exited,id="i1",exit-code="0""#).as_deref(), Some("Process group i1 exited with code 0"));
+        assert_eq!(describe(r#"=breakpoint-modified,bkpt={number="1",type="breakpoint",times="1"}"#), None);
+    }
+    
+    #[test]
+    fn test_signal_stop_raises_alert() {
+        let record = match gdbadapter::parse_gdb_output(r#"*stopped,reason="signal-received",signal-name="SIGSEGV",signal-meaning="Segmentation fault",frame={addr="0x08000124",func="fault",args=[]},thread-id="1""#).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => record,
+            _ => panic!("Expected async record"),
+        };
+        
+        let events = KatoriApp::stop_events(&record);
+        let alert = events.iter().find_map(|event| match event {
+            DebugEvent::SignalReceived(alert) => Some(alert),
+            _ => None,
+        }).expect("signal stop should raise an alert");
+        
+        assert_eq!(alert.to_string(), "Program received SIGSEGV: Segmentation fault");
+        assert_eq!(alert.address.as_deref(), Some("0x08000124"));
+        assert!(matches!(events.last(), Some(DebugEvent::TargetStateChanged(TargetState::Stopped))));
+    }
+    
+    #[tokio::test]
+    async fn test_reconnect_retries_until_the_attempts_run_out() {
+        let mut app = KatoriApp::headless();
+        app.reconnect_attempts = 2;
+        app.reconnect = Some(PendingReconnect { attempt: 1, due: None });
+        
+        app.reconnect_failed("Connection refused");
+        assert!(matches!(app.reconnect, Some(PendingReconnect { attempt: 2, due: Some(_) })));
+        
+        app.reconnect = Some(PendingReconnect { attempt: 2, due: None });
+        app.reconnect_failed("Connection refused");
+        assert_eq!(app.reconnect, None);
+        assert!(app.error_message.starts_with("Gave up reconnecting"));
+    }
+    
+    #[test]
+    fn test_reconnect_delay_backs_off() {
+        let delays: Vec<u64> = (1..=8).map(|attempt| reconnect_delay(attempt).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30, 30]);
+    }
+    
+    #[test]
+    fn test_format_latency() {
+        let ms = std::time::Duration::from_millis;
+        
+        assert_eq!(format_latency(Some(ms(12)), Some(ms(40))), "GDB latency: 12 ms (avg 40 ms)");
+        assert_eq!(format_latency(None, None), "GDB latency: -");
+    }
+    
+    #[test]
+    fn test_disassembly_flavor_only_for_x86() {
+        assert!(is_x86(Some("i386:x86-64")));
+        assert!(is_x86(Some("i8086")));
+        assert!(is_x86(None));
+        assert!(!is_x86(Some("armv7e-m")));
+    }
+    
+    #[test]
+    fn test_remote_disconnect_is_detected_in_gdb_output() {
+        let stream = gdbadapter::StreamRecord { stream_type: StreamType::Console, content: "Remote connection closed\n".to_string() };
+        let events = KatoriApp::gdb_event_to_debug_events(GdbEvent::Stream(stream));
+        assert!(matches!(events.as_slice(), [DebugEvent::StreamOutput(ConsoleKind::Gdb, _), DebugEvent::RemoteConnectionLost]));
+        
+        assert!(is_remote_disconnect("Remote communication error.  Target disconnected.: Connection reset by peer."));
+        assert!(!is_remote_disconnect("Remote debugging using localhost:1337"));
+    }
+    
+    #[test]
+    fn test_record_notifications_track_recording() {
+        for (input, recording) in [
+            (r#"=record-started,thread-group="i1",method="full""#, true),
+            (r#"=record-stopped,thread-group="i1""#, false),
+        ] {
+            let record = match gdbadapter::parse_gdb_output(input).unwrap() {
+                gdbadapter::GdbOutput::Async(record) => record,
+                _ => panic!("Expected async record"),
+            };
+            let events = KatoriApp::gdb_event_to_debug_events(GdbEvent::Async(record));
+            assert!(matches!(events.as_slice(), [DebugEvent::RecordingChanged(r)] if *r == recording));
+        }
+    }
+    
+    #[test]
+    fn test_breakpoint_options_describe_condition_and_flags() {
+        let mut bp = Breakpoint::from_tuple(&HashMap::from([
+            ("number".to_string(), Value::String("4".to_string())),
+            ("disp".to_string(), Value::String("keep".to_string())),
+        ])).unwrap();
+        assert_eq!(breakpoint_options(&bp), "");
+        
+        bp.condition = Some("n > 3".to_string());
+        bp.ignore_count = 2;
+        bp.disposition = "del".to_string();
+        assert_eq!(breakpoint_options(&bp), " if n > 3 (ignore 2, temporary)");
+        
+        bp.pending = Some("plugin.c:30".to_string());
+        assert_eq!(breakpoint_options(&bp), " if n > 3 (pending, ignore 2, temporary)");
+    }
+    
+    #[test]
+    fn test_changed_register_values() {
+        let register = |number: u32, value: &str| Register { number, name: format!("r{number}"), value: value.to_string() };
+        let baseline = HashMap::from([("r0".to_string(), "0x1".to_string()), ("r1".to_string(), "0x2".to_string())]);
+        let current = [register(0, "0x1"), register(1, "0x3"), register(2, "0x4")];
+        
+        assert_eq!(changed_register_values(&baseline, &current), HashSet::from([1]));
+        // The first refresh after attaching has nothing to compare against
+        assert!(changed_register_values(&HashMap::new(), &current).is_empty());
+    }
+    
+    #[test]
+    fn test_changed_registers_prefer_gdbs_list() {
+        let register = |number: u32, value: &str| Register { number, name: format!("r{number}"), value: value.to_string() };
+        let baseline = HashMap::from([("r0".to_string(), "0x1".to_string()), ("r1".to_string(), "0x2".to_string())]);
+        let current = [register(0, "0x1"), register(1, "0x3")];
+        
+        // GDB's list wins, and numbers not on screen are dropped
+        assert_eq!(changed_registers(&baseline, &current, Some(vec![0, 13])), HashSet::from([0]));
+        assert_eq!(changed_registers(&baseline, &current, None), HashSet::from([1]));
+        assert!(changed_registers(&HashMap::new(), &current, Some(vec![0, 1])).is_empty());
+    }
+    
+    #[test]
+    fn test_changed_watch_values() {
+        let previous = vec![
+            ("count".to_string(), Ok("1".to_string())),
+            ("state".to_string(), Ok("IDLE".to_string())),
+            ("ptr->next".to_string(), Err("Cannot access memory at address 0x0".to_string())),
+        ];
+        let current = vec![
+            ("count".to_string(), Ok("2".to_string())),
+            ("state".to_string(), Ok("IDLE".to_string())),
+            ("ptr->next".to_string(), Ok("0x20000010".to_string())),
+            ("new".to_string(), Ok("5".to_string())),
+        ];
+        
+        assert_eq!(changed_watch_values(&previous, &current), HashSet::from(["count".to_string()]));
+    }
+    
+    #[test]
+    fn test_source_lines_cache_missing_files() {
+        let mut source_files = HashMap::new();
+        
+        assert!(source_lines(&mut source_files, "/nonexistent/main.c").is_none());
+        assert_eq!(source_files.get("/nonexistent/main.c"), Some(&None));
+        
+        assert_eq!(source_language("/src/main.c"), "c");
+        assert_eq!(source_language("startup.S"), "ARM");
+        assert_eq!(source_language("Makefile"), "txt");
+    }
+    
+    #[test]
+    fn test_exit_maps_to_exited_state() {
+        let record = match gdbadapter::parse_gdb_output(r#"*stopped,reason="exited",exit-code="03""#).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => record,
+            _ => panic!("Expected async record"),
+        };
+        
+        let events = KatoriApp::stop_events(&record);
+        
+        assert!(matches!(events.last(), Some(DebugEvent::TargetStateChanged(TargetState::Exited(ExitStatus::Code(3))))));
+    }
+    
+    #[test]
+    fn test_exit_records_map_to_program_exited() {
+        for (input, code) in [
+            (r#"*stopped,reason="exited-normally""#, 0),
+            (r#"*stopped,reason="exited",exit-code="01""#, 1),
+            // GDB prints exit codes in octal
+            (r#"*stopped,reason="exited",exit-code="012""#, 10),
+        ] {
+            let record = match gdbadapter::parse_gdb_output(input).unwrap() {
+                gdbadapter::GdbOutput::Async(record) => record,
+                _ => panic!("Expected async record"),
+            };
+            let events = KatoriApp::stop_events(&record);
+            assert!(events.iter().any(|event| matches!(event, DebugEvent::ProgramExited(exited) if *exited == code)), "{input}");
+        }
+        assert_eq!(ExitStatus::Code(0).to_string(), "Program exited normally");
+        assert_eq!(ExitStatus::Code(10).to_string(), "Program exited with code 10");
+    }
+    
+    #[test]
+    fn test_exited_signalled_maps_to_signal_exit() {
+        let record = match gdbadapter::parse_gdb_output(r#"*stopped,reason="exited-signalled",signal-name="SIGKILL",signal-meaning="Killed""#).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => record,
+            _ => panic!("Expected async record"),
+        };
+        
+        let events = KatoriApp::stop_events(&record);
+        
+        match events.last() {
+            Some(DebugEvent::TargetStateChanged(TargetState::Exited(status))) => {
+                assert_eq!(*status, ExitStatus::Signal("SIGKILL".to_string()));
+                assert_eq!(status.to_string(), "Program terminated by SIGKILL");
+            }
+            other => panic!("Expected exited state, got {other:?}"),
+        }
+        assert!(TargetState::Exited(ExitStatus::Signal("SIGKILL".to_string())).can_restart());
+    }
+    
+    #[test]
+    fn test_target_state_controls() {
+        assert!(TargetState::Stopped.can_execute());
+        assert!(!TargetState::Stopped.can_restart());
+        assert!(!TargetState::Exited(ExitStatus::Code(0)).can_execute());
+        assert!(TargetState::Exited(ExitStatus::Code(0)).can_restart());
+        assert!(!TargetState::CoreDump.can_execute());
+        assert!(!TargetState::CoreDump.can_restart());
+        assert!(TargetState::Running.can_kill());
+        assert!(!TargetState::Exited(ExitStatus::Killed).can_kill());
+        assert!(TargetState::Exited(ExitStatus::Killed).can_restart());
+        assert_eq!(ExitStatus::Killed.to_string(), "Program killed");
+    }
+    
+    #[test]
+    fn test_loads_core_file() {
+        assert!(loads_core_file("core-file /tmp/core.1234"));
+        assert!(loads_core_file("target core core.1234"));
+        assert!(loads_core_file("-target-select core core.1234"));
+        assert!(!loads_core_file("core-file"));
+        assert!(!loads_core_file("target remote :1234"));
+    }
+    
+    #[test]
+    fn test_mi_log_entry_shows_token_and_class() {
+        let result = match gdbadapter::parse_gdb_output(r#"12^error,msg="No symbol \"foo\" in current context.""#).unwrap() {
+            gdbadapter::GdbOutput::Result(result) => result,
+            _ => panic!("Expected result record"),
+        };
+        
+        let entry = MiLogEntry::from_event(&GdbEvent::Result(result));
+        
+        assert_eq!(entry.kind, MiRecordKind::ErrorResult);
+        assert_eq!(entry.to_string(), r#"[  12] ^error msg="No symbol \"foo\" in current context.""#);
+        
+        let lost = MiLogEntry::from_event(&GdbEvent::ConnectionLost);
+        assert_eq!(lost.kind, MiRecordKind::ConnectionLost);
+        assert_eq!(lost.token, None);
+    }
+    
+    #[test]
+    fn test_format_address() {
+        assert_eq!(format_address("0xdead"), "0xdead");
+        assert_eq!(format_address("dead"), "0xdead");
+        assert_eq!(format_address("0Xdead"), "0xdead");
+        assert_eq!(strip_hex_prefix(" 0x08048564 "), "08048564");
+    }
+    
+    #[test]
+    fn test_format_call_truncates_long_values() {
+        let arg = |name: &str, value: &str| gdbadapter::Argument { name: name.to_string(), value: value.to_string() };
+        let args = [
+            arg("argc", "1"),
+            arg("config", "{name = 0x8000400 \"device\", limits = {low = 0, high = 4096}, flags = 3}"),
+        ];
+        
+        assert_eq!(format_call("main", &[]), "main()");
+        assert_eq!(
+            format_call("main", &args),
+            "main(argc=1, config={name = 0x8000400 \"device\", limi…)"
+        );
+    }
+    
+    #[test]
+    fn test_find_matches_ignores_case() {
+        let lines = ["0x08000100: push {r7, lr}", "// main.c:12", "0x08000102: MOV r7, sp"].map(String::from);
+
+        assert_eq!(find_matches(&lines, "mov"), [2]);
+        assert_eq!(find_matches(&lines, "R7"), [0, 2]);
+        assert_eq!(find_matches(&lines, "8000102"), [2]);
+        assert!(find_matches(&lines, "  ").is_empty());
+    }
+    
+    #[test]
+    fn test_parse_hex_byte() {
+        assert_eq!(parse_hex_byte("ff"), Some(0xff));
+        assert_eq!(parse_hex_byte("0A"), Some(0x0a));
+        assert_eq!(parse_hex_byte("a"), None);
+        assert_eq!(parse_hex_byte("zz"), None);
+        assert_eq!(parse_hex_byte("+1"), None);
+    }
+    
+    #[test]
+    fn test_extract_address() {
+        assert_eq!(extract_address("{int (int, char **)} 0x401136 <main>"), Some("0x401136".to_string()));
+        assert_eq!(extract_address("(int *) 0x20000000 <buffer>"), Some("0x20000000".to_string()));
+        assert_eq!(extract_address("0X8000124"), Some("0x8000124".to_string()));
+        assert_eq!(extract_address("42"), None);
+        assert_eq!(hex_literal("0x100+4"), None);
+        assert_eq!(hex_literal("0x"), None);
+        
+        assert!(is_function_value("{int (int, char **)} 0x401136 <main>"));
+        assert!(!is_function_value("(char *) 0x20000000 <buffer>"));
+        assert!(!is_function_value("0x20000100"));
+    }
+    
+    #[test]
+    fn test_symbol_names() {
+        assert!(is_symbol_name("main"));
+        assert!(is_symbol_name(" _start "));
+        assert!(is_symbol_name("app::uart::init"));
+        assert!(!is_symbol_name("$sp"));
+        assert!(!is_symbol_name("buffer + 4"));
+        assert!(!is_symbol_name("*ptr"));
+        assert!(!is_symbol_name("0x100"));
+        assert!(!is_symbol_name("::init"));
+        assert!(!is_symbol_name(""));
+    }
+    
+    #[test]
+    fn test_command_history_navigation() {
+        let mut history = CommandHistory::default();
+        assert_eq!(history.previous(), None);
+        
+        history.push("info registers".to_string());
+        history.push("-exec-next".to_string());
+        history.push("-exec-next".to_string()); // consecutive duplicates collapse
+        
+        assert_eq!(history.previous(), Some("-exec-next"));
+        assert_eq!(history.previous(), Some("info registers"));
+        assert_eq!(history.previous(), Some("info registers")); // stays at the oldest
+        assert_eq!(history.next(), Some("-exec-next"));
+        assert_eq!(history.next(), None); // back to a fresh line
+        assert_eq!(history.previous(), Some("-exec-next"));
+    }
+    
+    #[test]
+    fn test_raw_to_mi() {
+        assert_eq!(raw_to_mi("-exec-next"), "exec-next");
+        assert_eq!(raw_to_mi("  -break-list  "), "break-list");
+        assert_eq!(raw_to_mi("info registers"), "interpreter-exec console \"info registers\"");
+        assert_eq!(raw_to_mi("print \"hi\""), "interpreter-exec console \"print \\\"hi\\\"\"");
+    }
+    
+    #[test]
+    fn test_format_mi_result() {
+        let result = match gdbadapter::parse_gdb_output(r#"^done,bkpt={number="1",type="breakpoint"},groups=["i1"]"#).unwrap() {
+            gdbadapter::GdbOutput::Result(result) => result,
+            _ => panic!("Expected result record"),
+        };
+        
+        assert_eq!(format_mi_result(&result), r#"^done,bkpt={number="1",type="breakpoint"},groups=["i1"]"#);
+    }
+    
+    async fn dispatch_without_session(command: GdbCommand) -> Vec<DebugEvent> {
+        let (adapter, _gdb_events) = GdbAdapter::new();
+        let (event_sender, mut event_receiver) = tokio::sync::mpsc::unbounded_channel();
+        
+        KatoriApp::dispatch_command(Arc::new(Mutex::new(adapter)), command, &event_sender).await;
+        
+        let mut events = Vec::new();
+        while let Ok(event) = event_receiver.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+    
+    #[tokio::test]
+    async fn test_commands_without_session_report_no_session() {
+        let commands = [
+            GdbCommand::Continue,
+            GdbCommand::StepOver,
+            GdbCommand::StepInto,
+            GdbCommand::StepOut,
+            GdbCommand::StepInstructions(5),
+            GdbCommand::RunToAddress("0x08000124".to_string()),
+            GdbCommand::ReverseContinue,
+            GdbCommand::ReverseStepInto,
+            GdbCommand::ReverseStepOver,
+            GdbCommand::SetRecording(true),
+            GdbCommand::Interrupt,
+            GdbCommand::SetBreakpoint(BreakpointSpec::new("main")),
+            GdbCommand::RemoveBreakpoint(1),
+            GdbCommand::RefreshBreakpoints,
+            GdbCommand::ToggleBreakpoint(1, false),
+            GdbCommand::ToggleBreakpointLocation("1.2".to_string(), false),
+            GdbCommand::ContinueUntilHit(1, 3),
+            GdbCommand::SetIgnoreCount(1, 0),
+            GdbCommand::EvaluateWatches(vec!["counter".to_string()]),
+            GdbCommand::ExpandWatch("origin".to_string()),
+            GdbCommand::ListVarObjChildren("var1".to_string(), 0, VAROBJ_PAGE_SIZE),
+            GdbCommand::UpdateVarObjs,
+            GdbCommand::DeleteVarObj("var1".to_string()),
+            GdbCommand::SetWatchpoint("counter".to_string(), WatchKind::Write),
+            GdbCommand::RefreshDebugInfo,
+            GdbCommand::ReadMemory(0, "0x20000000".to_string(), 64),
+            GdbCommand::ReadMemoryChunk(0, 1, "0x20000000".to_string(), 4096, 4096),
+            GdbCommand::ScanMemory("$sp".to_string(), 64),
+            GdbCommand::WriteMemory("0x20000000".to_string(), vec![0xff]),
+            GdbCommand::Detach,
+            GdbCommand::Raw("info registers".to_string()),
+            GdbCommand::GoTo("main".to_string(), GoToTarget::Disassembly),
+            GdbCommand::DisassembleFunction("main".to_string()),
+            GdbCommand::SelectThread(2),
+            GdbCommand::InterruptThread(2),
+            GdbCommand::SelectFrame(1),
+            GdbCommand::Reconnect("localhost:1337".to_string()),
+            GdbCommand::Restart,
+            GdbCommand::Kill,
+            GdbCommand::Run(Vec::new(), None),
+            GdbCommand::SendProgramInput("y\n".to_string()),
+            GdbCommand::SetEnvironment(vec![("LANG".to_string(), "C".to_string())], Vec::new()),
+        ];
+        
+        for command in commands {
+            let events = dispatch_without_session(command.clone()).await;
+            
+            assert_eq!(events.len(), 1, "{command:?}: {events:?}");
+            match &events[0] {
+                DebugEvent::ConsoleMessage(message) => assert_eq!(message, NO_SESSION_MESSAGE),
+                other => panic!("{command:?}: expected console message, got {other:?}"),
+            }
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_session_management_allowed_without_session() {
+        assert!(!GdbCommand::StartSession.requires_session());
+        assert!(!GdbCommand::SetRegisterFormat(RegisterFormat::Decimal).requires_session());
+        assert!(!GdbCommand::SetRegisterSet(RegisterSet::All).requires_session());
+        assert!(!GdbCommand::SetRegisterSubset(None).requires_session());
+        assert!(!GdbCommand::SetNonStop(true).requires_session());
+        assert!(!GdbCommand::SetMiLog(None).requires_session());
+        assert!(!GdbCommand::LoadExecutable("a.out".to_string()).requires_session());
+        assert!(!GdbCommand::Attach(AttachMode::GdbServer, "localhost:1337".to_string()).requires_session());
+        assert!(!GdbCommand::SetArchitecture("armv7e-m".to_string()).requires_session());
+        assert!(!GdbCommand::SetInstructionWidth(Some(2)).requires_session());
+        assert!(!GdbCommand::SetDisassemblyFlavor("intel".to_string()).requires_session());
+        assert!(!GdbCommand::LoadCore("./firmware.elf".to_string(), "core.1234".to_string()).requires_session());
+        
+        let events = dispatch_without_session(GdbCommand::StopSession).await;
+        
+        assert_eq!(events.len(), 1, "{events:?}");
+        assert!(matches!(events[0], DebugEvent::CommandCompleted(GdbCommand::StopSession)));
+    }
+    
+    #[tokio::test]
+    async fn test_gdb_event_task_forwards_events() {
+        let (gdb_event_sender, gdb_events) = tokio::sync::broadcast::channel(16);
+        let (event_sender, mut event_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::spawn(KatoriApp::gdb_event_task(egui::Context::default(), gdb_events, event_sender));
+        
+        let log = gdbadapter::StreamRecord { stream_type: StreamType::Log, content: "echo\n".to_string() };
+        let stderr = gdbadapter::StreamRecord { stream_type: StreamType::Log, content: "GDB stderr: oops".to_string() };
+        let console = gdbadapter::StreamRecord { stream_type: StreamType::Console, content: "hello\n".to_string() };
+        let target = gdbadapter::StreamRecord { stream_type: StreamType::Target, content: "Enter a number: ".to_string() };
+        gdb_event_sender.send(GdbEvent::Stream(log)).unwrap();
+        gdb_event_sender.send(GdbEvent::Stream(stderr)).unwrap();
+        gdb_event_sender.send(GdbEvent::Stream(console)).unwrap();
+        gdb_event_sender.send(GdbEvent::Stream(target)).unwrap();
+        gdb_event_sender.send(GdbEvent::ConnectionLost).unwrap();
+        drop(gdb_event_sender);
+        task.await.unwrap();
+        
+        assert!(matches!(event_receiver.try_recv(), Ok(DebugEvent::StreamOutput(ConsoleKind::Log, message)) if message == "echo\n"));
+        assert!(matches!(event_receiver.try_recv(), Ok(DebugEvent::StreamOutput(ConsoleKind::Error, message)) if message == "GDB stderr: oops\n"));
+        assert!(matches!(event_receiver.try_recv(), Ok(DebugEvent::StreamOutput(ConsoleKind::Gdb, message)) if message == "hello\n"));
+        assert!(matches!(event_receiver.try_recv(), Ok(DebugEvent::ProgramOutput(message)) if message == "Enter a number: "));
+        assert!(matches!(event_receiver.try_recv(), Ok(DebugEvent::GdbConnectionLost)));
+        assert!(event_receiver.try_recv().is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_unsolicited_stop_handled_while_processor_idle() {
+        let (adapter, _events) = GdbAdapter::new();
+        let (gdb_event_sender, gdb_events) = tokio::sync::broadcast::channel(16);
+        let (event_sender, mut event_receiver) = tokio::sync::mpsc::unbounded_channel();
+        // Keep the command channel open and empty, so the processor sits waiting for a command
+        let (_command_sender, command_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let ctx = egui::Context::default();
+        tokio::spawn(KatoriApp::command_processor_task(ctx.clone(), Arc::new(Mutex::new(adapter)), command_receiver, event_sender.clone()));
+        tokio::spawn(KatoriApp::gdb_event_task(ctx, gdb_events, event_sender));
+        
+        let record = match gdbadapter::parse_gdb_output(r#"*stopped,reason="signal-received",signal-name="SIGINT",thread-id="1""#).unwrap() {
+            gdbadapter::GdbOutput::Async(record) => record,
+            _ => panic!("Expected async record"),
+        };
+        gdb_event_sender.send(GdbEvent::Async(record)).unwrap();
+        
+        let state = tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            loop {
+                if let Some(DebugEvent::TargetStateChanged(state)) = event_receiver.recv().await {
+                    return state;
+                }
+            }
+        }).await.expect("stop was not forwarded without a command");
+        assert_eq!(state, TargetState::Stopped);
+    }
+    
+    #[tokio::test]
+    async fn test_shutdown_session_stops_idle_adapter() {
+        let (adapter, _events) = GdbAdapter::new();
+        let adapter = Arc::new(Mutex::new(adapter));
+        
+        let stopped = KatoriApp::shutdown_session(adapter.clone(), None, SHUTDOWN_TIMEOUT).await;
+        
+        assert!(stopped);
+        assert!(!adapter.lock().await.is_running());
+    }
+    
+    #[tokio::test]
+    async fn test_shutdown_session_still_stops_when_exit_action_fails() {
+        let (adapter, _events) = GdbAdapter::new();
+        let adapter = Arc::new(Mutex::new(adapter));
+        
+        // Nothing to detach from or kill, which must not prevent shutdown
+        for action in [ExitAction::Detach, ExitAction::Kill] {
+            assert!(KatoriApp::shutdown_session(adapter.clone(), Some(action), SHUTDOWN_TIMEOUT).await);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_shutdown_session_detaches_after_slow_stop() {
+        // The target takes longer to stop than detaching and exiting GDB do
+        let gdb = gdbadapter::mock::MockGdb::new()
+            .expect("mi-async", &[r#"*running,thread-id="all""#, "{token}^done"])
+            .expect_delayed("exec-interrupt", std::time::Duration::from_millis(3500), &["^done", r#"*stopped,reason="signal-received""#]);
+        let (mut adapter, _events) = GdbAdapter::new();
+        let (output, input) = gdb.serve();
+        adapter.start_session_with_io(output, input).await.unwrap();
+        adapter.set_mi_async(true).await.unwrap();
+        assert!(adapter.is_target_running());
+        let adapter = Arc::new(Mutex::new(adapter));
+        
+        assert!(KatoriApp::shutdown_session(adapter.clone(), Some(ExitAction::Detach), SHUTDOWN_TIMEOUT).await);
+        assert!(gdb.received().contains(&"target-detach".to_string()));
+        assert!(!adapter.lock().await.is_running());
+    }
+    
+    #[tokio::test]
+    async fn test_shutdown_session_gives_up_when_adapter_busy() {
+        let (adapter, _events) = GdbAdapter::new();
+        let adapter = Arc::new(Mutex::new(adapter));
+        
+        // Simulate a long-running command (e.g. Continue) holding the adapter
+        let _busy = adapter.clone().lock_owned().await;
+        
+        let stopped = KatoriApp::shutdown_session(adapter, None, std::time::Duration::from_millis(50)).await;
+        
+        assert!(!stopped);
+    }
+}
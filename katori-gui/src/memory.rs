@@ -0,0 +1,352 @@
+//! Memory viewer formatting
+//!
+//! Turns the raw bytes of a `-data-read-memory-bytes` block into rows of hex
+//! words and an ASCII gutter, with configurable row width, word size and
+//! endianness. Several views can be open at once, each with its own range and
+//! layout.
+
+use crate::mi::MemoryReadResult;
+use gdbadapter::parse_address;
+
+/// Largest single `-data-read-memory-bytes` request; bigger views are read in
+/// chunks of this size so each gets its own timeout and shows up as it arrives
+pub const MEMORY_CHUNK_SIZE: u32 = 4096;
+
+/// Byte order used when grouping bytes into words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Endian::Little => "Little endian",
+            Endian::Big => "Big endian",
+        }
+    }
+}
+
+/// Row width choices offered in the memory viewer
+pub const BYTES_PER_ROW_OPTIONS: [usize; 3] = [8, 16, 32];
+
+/// Word size choices offered in the memory viewer
+pub const GROUP_SIZE_OPTIONS: [usize; 4] = [1, 2, 4, 8];
+
+/// How the memory viewer lays out bytes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryLayout {
+    pub bytes_per_row: usize,
+    /// Bytes per displayed word
+    pub group_size: usize,
+    pub endian: Endian,
+}
+
+impl Default for MemoryLayout {
+    fn default() -> Self {
+        MemoryLayout {
+            bytes_per_row: 16,
+            group_size: 1,
+            endian: Endian::Little,
+        }
+    }
+}
+
+/// One pane of the memory viewer
+#[derive(Debug)]
+pub struct MemoryView {
+    /// Routes read results to this view; stays the same when other views are removed
+    pub id: usize,
+    pub address: String,
+    pub size: u32,
+    pub data: Option<MemoryReadResult>,
+    /// Why the last read failed, until one succeeds
+    pub read_error: Option<String>,
+    pub layout: MemoryLayout,
+    /// Byte being edited: absolute address and the text typed so far
+    pub edit: Option<(u64, String)>,
+    /// Re-read the range every time the target stops
+    pub pinned: bool,
+    /// Read still in progress when the view is larger than one chunk
+    pub chunked_read: Option<ChunkedRead>,
+    /// Generation of the latest chunked read, so chunks of a replaced read can be told apart
+    pub read_generation: u64,
+}
+
+/// A view's read split into `MEMORY_CHUNK_SIZE` requests
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkedRead {
+    /// Which of the view's reads this is; replies carry it back
+    pub generation: u64,
+    /// Address the chunks are offsets from: the view's expression until the
+    /// first chunk arrives, then the address it was read at
+    pub address: String,
+    /// Offset of the chunk currently requested
+    pub offset: u32,
+    pub size: u32,
+}
+
+impl ChunkedRead {
+    pub fn new(generation: u64, address: &str, size: u32) -> Self {
+        ChunkedRead { generation, address: address.to_string(), offset: 0, size }
+    }
+
+    /// Length of the chunk at the current offset
+    pub fn chunk_len(&self) -> u32 {
+        MEMORY_CHUNK_SIZE.min(self.size - self.offset)
+    }
+
+    /// Fraction of the range read so far
+    pub fn progress(&self) -> f32 {
+        self.offset as f32 / self.size.max(1) as f32
+    }
+}
+
+impl MemoryView {
+    pub fn new(id: usize, address: &str) -> Self {
+        MemoryView {
+            id,
+            address: address.to_string(),
+            size: 256,
+            data: None,
+            read_error: None,
+            layout: MemoryLayout::default(),
+            edit: None,
+            pinned: false,
+            chunked_read: None,
+            read_generation: 0,
+        }
+    }
+
+    /// Start a chunked read of the view's range, replacing one in progress
+    pub fn start_chunked_read(&mut self) -> &ChunkedRead {
+        self.read_generation += 1;
+        self.chunked_read.insert(ChunkedRead::new(self.read_generation, &self.address, self.size))
+    }
+
+    /// Add a chunk read for `chunked_read`, and return the next chunk's address,
+    /// offset and length. Chunks from an earlier read are dropped, and the read
+    /// ends early if GDB returns fewer bytes than asked for (the rest isn't readable)
+    pub fn append_chunk(&mut self, generation: u64, offset: u32, chunk: MemoryReadResult) -> Option<(String, u32, u32)> {
+        let read = self.chunked_read.as_mut()
+            .filter(|read| read.generation == generation && read.offset == offset)?;
+        if offset == 0 {
+            // An expression like `$sp` may change between chunks; the rest follow the first
+            read.address = chunk.begin.clone();
+        }
+        let requested = read.chunk_len();
+        let received = (chunk.contents.len() / 2) as u32;
+        self.read_error = None;
+
+        match &mut self.data {
+            Some(data) if offset > 0 => {
+                data.contents.push_str(&chunk.contents);
+                data.end = chunk.end;
+            }
+            _ => self.data = Some(chunk),
+        }
+
+        read.offset += received;
+        if received < requested || read.offset >= read.size {
+            self.chunked_read = None;
+            return None;
+        }
+        Some((read.address.clone(), read.offset, read.chunk_len()))
+    }
+
+    /// Range last read, or the requested one while the address is a plain number
+    fn range(&self) -> Option<(u64, u64)> {
+        if let Some(data) = &self.data {
+            return Some((parse_address(&data.begin)?, parse_address(&data.end)?));
+        }
+        let begin = parse_address(&self.address)?;
+        Some((begin, begin.saturating_add(u64::from(self.size))))
+    }
+
+    /// Whether `len` bytes at `address` fall within the view's `[begin, end)` range
+    pub fn overlaps(&self, address: u64, len: u64) -> bool {
+        self.range().is_some_and(|(begin, end)| address < end && begin < address.saturating_add(len))
+    }
+
+    /// The range last read, as a hex dump in the view's current layout
+    pub fn hex_dump(&self) -> Option<String> {
+        let data = self.data.as_ref()?;
+        let begin = parse_address(&data.begin)?;
+        Some(format_hex_dump(begin, &decode_hex(&data.contents), &self.layout))
+    }
+}
+
+/// Decode GDB's `contents` hex string; a trailing odd digit is ignored
+pub fn decode_hex(contents: &str) -> Vec<u8> {
+    contents.as_bytes()
+        .chunks_exact(2)
+        .filter_map(|pair| std::str::from_utf8(pair).ok())
+        .filter_map(|pair| u8::from_str_radix(pair, 16).ok())
+        .collect()
+}
+
+/// Format one row as space-separated words.
+///
+/// Words are shown most significant byte first, so little-endian words are
+/// byte-reversed. Missing bytes at the end of the data are padded with spaces
+/// so every row has the same width.
+pub fn format_hex_row(bytes: &[u8], layout: &MemoryLayout) -> String {
+    let group_size = layout.group_size.max(1);
+    let groups = layout.bytes_per_row.div_ceil(group_size);
+
+    (0..groups)
+        .map(|group| {
+            let start = group * group_size;
+            let mut cells: Vec<String> = (start..start + group_size)
+                .map(|i| bytes.get(i).map(|b| format!("{b:02x}")).unwrap_or_else(|| "  ".to_string()))
+                .collect();
+            if layout.endian == Endian::Little {
+                cells.reverse();
+            }
+            cells.concat()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Format the ASCII gutter for one row, padded to the full row width
+pub fn format_ascii(bytes: &[u8], layout: &MemoryLayout) -> String {
+    (0..layout.bytes_per_row)
+        .map(|i| match bytes.get(i) {
+            Some(b) if b.is_ascii_graphic() || *b == b' ' => *b as char,
+            Some(_) => '.',
+            None => ' ',
+        })
+        .collect()
+}
+
+/// Format bytes starting at `begin` as text, one row per line: address, words
+/// and ASCII, laid out as in the viewer
+pub fn format_hex_dump(begin: u64, bytes: &[u8], layout: &MemoryLayout) -> String {
+    bytes.chunks(layout.bytes_per_row.max(1))
+        .enumerate()
+        .map(|(i, row)| {
+            let address = begin + (i * layout.bytes_per_row) as u64;
+            let line = format!("{address:08X}  {}  {}", format_hex_row(row, layout), format_ascii(row, layout));
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(begin: u64, bytes: usize) -> MemoryReadResult {
+        MemoryReadResult {
+            offset: "0x0".to_string(),
+            begin: format!("{begin:#x}"),
+            end: format!("{:#x}", begin + bytes as u64),
+            contents: "ab".repeat(bytes),
+        }
+    }
+
+    #[test]
+    fn test_chunked_read_assembles_in_order() {
+        let mut view = MemoryView::new(0, "$sp");
+        view.size = MEMORY_CHUNK_SIZE * 2 + 16;
+        let stale = view.start_chunked_read().generation;
+        let generation = view.start_chunked_read().generation;
+
+        // The first chunk of a replaced read is dropped, even at the same offset
+        assert_eq!(view.append_chunk(stale, 0, chunk(0x5000, 4096)), None);
+        // Later chunks are read from where the first one was, not from `$sp` again
+        assert_eq!(view.append_chunk(generation, 0, chunk(0x1000, 4096)), Some(("0x1000".to_string(), 4096, 4096)));
+        assert_eq!(view.append_chunk(stale, 4096, chunk(0x5000, 4096)), None);
+        assert_eq!(view.chunked_read.as_ref().map(|read| read.progress()), Some(4096.0 / 8208.0));
+        assert_eq!(view.append_chunk(generation, 4096, chunk(0x2000, 4096)), Some(("0x1000".to_string(), 8192, 16)));
+        assert_eq!(view.append_chunk(generation, 8192, chunk(0x3000, 16)), None);
+
+        let data = view.data.as_ref().unwrap();
+        assert_eq!((data.begin.as_str(), data.end.as_str()), ("0x1000", "0x3010"));
+        assert_eq!(decode_hex(&data.contents).len(), 8208);
+        assert!(view.chunked_read.is_none());
+    }
+
+    #[test]
+    fn test_chunked_read_stops_at_short_chunk() {
+        let mut view = MemoryView::new(0, "0x1000");
+        view.size = MEMORY_CHUNK_SIZE * 4;
+        let generation = view.start_chunked_read().generation;
+
+        assert_eq!(view.append_chunk(generation, 0, chunk(0x1000, 100)), None);
+        assert!(view.chunked_read.is_none());
+        assert_eq!(decode_hex(&view.data.unwrap().contents).len(), 100);
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("deadBEEF0"), [0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        let layout = MemoryLayout { bytes_per_row: 8, ..Default::default() };
+        let bytes = [0x48, 0x69, 0x00, 0x7f, 0x20, 0x41, 0x42, 0x43];
+
+        assert_eq!(format_hex_row(&bytes, &layout), "48 69 00 7f 20 41 42 43");
+        assert_eq!(format_ascii(&bytes, &layout), "Hi.. ABC");
+    }
+
+    #[test]
+    fn test_format_words_by_endianness() {
+        let bytes = [0x78, 0x56, 0x34, 0x12, 0xef, 0xbe, 0xad, 0xde];
+        let little = MemoryLayout { bytes_per_row: 8, group_size: 4, endian: Endian::Little };
+        let big = MemoryLayout { endian: Endian::Big, ..little };
+
+        assert_eq!(format_hex_row(&bytes, &little), "12345678 deadbeef");
+        assert_eq!(format_hex_row(&bytes, &big), "78563412 efbeadde");
+    }
+
+    #[test]
+    fn test_partial_row_keeps_width() {
+        let layout = MemoryLayout { bytes_per_row: 8, group_size: 4, endian: Endian::Little };
+        let full = format_hex_row(&[0; 8], &layout);
+        let partial = format_hex_row(&[0x01, 0x02, 0x03, 0x04, 0x05], &layout);
+
+        assert_eq!(partial, "04030201       05");
+        assert_eq!(partial.len(), full.len());
+        assert_eq!(format_ascii(&[0x41], &layout), "A       ");
+    }
+
+    #[test]
+    fn test_overlap_with_changed_range() {
+        let mut view = MemoryView::new(0, "0x20000000");
+        view.size = 16;
+
+        assert!(view.overlaps(0x2000_0000, 4));
+        assert!(view.overlaps(0x1fff_fffe, 4));
+        assert!(view.overlaps(0x2000_000c, 8));
+        assert!(!view.overlaps(0x2000_0010, 4));
+        assert!(!view.overlaps(0x1fff_fffc, 4));
+
+        // Once read, the range GDB returned counts, even for a symbolic address
+        view.address = "buffer".to_string();
+        assert!(!view.overlaps(0x2000_0000, 4));
+        view.data = Some(MemoryReadResult {
+            offset: "0x0".to_string(),
+            begin: "0x30000000".to_string(),
+            end: "0x30000010".to_string(),
+            contents: String::new(),
+        });
+        assert!(view.overlaps(0x3000_0008, 1));
+    }
+
+    #[test]
+    fn test_hex_dump_follows_layout() {
+        let layout = MemoryLayout { bytes_per_row: 4, group_size: 2, endian: Endian::Big };
+        let bytes = [0x48, 0x69, 0x00, 0x7f, 0x41, 0x42];
+
+        assert_eq!(
+            format_hex_dump(0x2000_0000, &bytes, &layout),
+            "20000000  4869 007f  Hi..\n20000004  4142       AB"
+        );
+    }
+}
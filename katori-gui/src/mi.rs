@@ -0,0 +1,292 @@
+//! Parsers turning GDB/MI results into the data shown in the panels
+//!
+//! These are shared by every command that fetches debug information so the
+//! registers, stack, disassembly and memory views all read GDB's replies the
+//! same way.
+
+use gdbadapter::{AssemblyLine, GdbResult, Register, SourceLine, Value};
+use std::collections::HashMap;
+use log::debug;
+
+/// Parse `-data-list-register-names` into `(number, name)` pairs.
+///
+/// A name's position in the list is its register number. GDB reports numbers
+/// the target doesn't have as `""`, so those are left out.
+pub fn parse_register_names(result: &GdbResult) -> Vec<(usize, String)> {
+    let mut register_names = Vec::new();
+    if let Some(Value::List(names_list)) = result.results.get("register-names") {
+        for (i, name_value) in names_list.iter().enumerate() {
+            if let Some(name) = name_value.as_string().filter(|name| !name.is_empty()) {
+                register_names.push((i, name.to_string()));
+            }
+        }
+    }
+    register_names
+}
+
+/// Parse register values from GDB/MI result.
+///
+/// Values for numbers without a name are dropped. `rN` names are only made up
+/// when no names are known at all.
+pub fn parse_registers(result: &GdbResult, register_names: &[(usize, String)]) -> Option<Vec<Register>> {
+    // GDB/MI uses "register-values" field for -data-list-register-values
+    if let Some(Value::List(register_list)) = result.results.get("register-values") {
+        let mut registers = Vec::new();
+        
+        for reg_value in register_list {
+            if let Some(reg_tuple) = reg_value.as_tuple() {
+                // Skip malformed entries rather than dropping the whole list
+                let Some(number) = reg_tuple.get("number").and_then(Value::as_u32) else {
+                    continue;
+                };
+                // Vector registers may come back structured instead of as a string
+                let Some(value) = reg_tuple.get("value").map(|v| v.to_string()) else {
+                    continue;
+                };
+                
+                let name = match register_names.iter().find(|(i, _)| *i == number as usize) {
+                    Some((_, name)) => name.clone(),
+                    None if register_names.is_empty() => format!("r{number}"),
+                    None => continue,
+                };
+                
+                registers.push(Register {
+                    number,
+                    name,
+                    value,
+                });
+            }
+        }
+        
+        Some(registers)
+    } else {
+        // Check if there's a different structure
+        debug!("parse_registers: Available register result keys: {:?}", result.results.keys().collect::<Vec<_>>());
+        None
+    }
+}
+
+/// Parse a single `asm_insns`/`line_asm_insn` entry
+fn parse_instruction(asm_tuple: &HashMap<String, Value>, source: Option<&SourceLine>) -> Option<AssemblyLine> {
+    Some(AssemblyLine {
+        address: asm_tuple.get("address")?.as_string()?.to_string(),
+        function: asm_tuple.get("func-name").and_then(|v| v.as_string()).map(|s| s.to_string()),
+        offset: asm_tuple.get("offset").and_then(Value::as_u32),
+        instruction: asm_tuple.get("inst")?.as_string()?.to_string(),
+        opcodes: asm_tuple.get("opcodes").and_then(|v| v.as_string()).map(|s| s.to_string()),
+        source: source.cloned(),
+    })
+}
+
+/// Parse assembly instructions from GDB/MI result
+///
+/// Handles both plain disassembly and mixed source mode, where instructions
+/// are nested in `src_and_asm_line={line,file,line_asm_insn=[...]}` blocks.
+pub fn parse_assembly(result: &GdbResult) -> Option<Vec<AssemblyLine>> {
+    // GDB/MI uses "asm_insns" field for -data-disassemble
+    if let Some(Value::List(asm_list)) = result.results.get("asm_insns") {
+        let mut assembly = Vec::new();
+        
+        for asm_value in asm_list {
+            let Some(asm_tuple) = asm_value.as_tuple() else {
+                continue;
+            };
+            if let Some(block) = asm_tuple.get("src_and_asm_line").and_then(|v| v.as_tuple()) {
+                let source = SourceLine::from_tuple(block);
+                let instructions = block.get("line_asm_insn").and_then(|v| v.as_list()).map(Vec::as_slice).unwrap_or_default();
+                for insn in instructions.iter().filter_map(|v| v.as_tuple()) {
+                    assembly.push(parse_instruction(insn, source.as_ref())?);
+                }
+            } else {
+                assembly.push(parse_instruction(asm_tuple, None)?);
+            }
+        }
+        
+        Some(assembly)
+    } else {
+        // Check if there's a different structure
+        debug!("parse_assembly: Available assembly result keys: {:?}", result.results.keys().collect::<Vec<_>>());
+        None
+    }
+}
+
+/// Contents of a `-data-read-memory-bytes` block
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct MemoryReadResult {
+    pub offset: String,
+    pub begin: String,
+    pub end: String,
+    pub contents: String,
+}
+
+/// Parse the first block of a `memory` list
+pub fn parse_memory(value: &Value) -> Option<MemoryReadResult> {
+
+    let mut offset: Option<String> = None;
+    let mut begin: Option<String> = None;
+    let mut end: Option<String> = None;
+    let mut contents: Option<String> = None;
+
+    let memory_list = value.as_list()?;
+    let first_item = memory_list.first()?;
+    let memory_tuple = first_item.as_tuple()?;
+    for (key, val) in memory_tuple.iter() {
+        match key.as_str() {
+            "offset" => offset = val.as_string().map(|s| s.to_string()),
+            "begin" => begin = val.as_string().map(|s| s.to_string()),
+            "end" => end = val.as_string().map(|s| s.to_string()),
+            "contents" => contents = val.as_string().map(|s| s.to_string()),
+            _ => {}
+        }
+    }
+
+    Some(MemoryReadResult {
+        offset: offset?,
+        begin: begin?,
+        end: end?,
+        contents: contents?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(input: &str) -> GdbResult {
+        match gdbadapter::parse_gdb_output(input).unwrap() {
+            gdbadapter::GdbOutput::Result(result) => result,
+            _ => panic!("Expected result record"),
+        }
+    }
+
+    #[test]
+    fn test_parse_registers_uses_names() {
+        let names = parse_register_names(&result(r#"^done,register-names=["r0","r1","","sp"]"#));
+        let registers = parse_registers(
+            &result(r#"^done,register-values=[{number="0",value="0x1"},{number="3",value="0x20001000"},{number="7",value="0x0"}]"#),
+            &names,
+        ).unwrap();
+
+        // Number 7 has no name, so it isn't a register worth showing
+        assert_eq!(registers.len(), 2);
+        assert_eq!((registers[0].name.as_str(), registers[0].value.as_str()), ("r0", "0x1"));
+        assert_eq!(registers[1].name, "sp");
+        assert_eq!(registers[1].number, 3);
+
+        // Without any names, numbers are all there is to go on
+        let registers = parse_registers(&result(r#"^done,register-values=[{number="7",value="0x0"}]"#), &[]).unwrap();
+        assert_eq!(registers[0].name, "r7");
+    }
+
+    #[test]
+    fn test_parse_registers_skips_unnamed_numbers() {
+        // ARM targets leave gaps between the core, FPA and VFP registers
+        let names = parse_register_names(&result(
+            r#"^done,register-names=["r0","r1","sp","lr","pc","","","","","","","","","","","","cpsr","","d0"]"#,
+        ));
+        assert_eq!(names.len(), 7);
+        assert_eq!(names[5], (16, "cpsr".to_string()));
+        assert_eq!(names[6], (18, "d0".to_string()));
+
+        let registers = parse_registers(
+            &result(r#"^done,register-values=[{number="4",value="0x8000"},{number="5",value="<unavailable>"},{number="16",value="0x60000010"},{number="18",value="0"}]"#),
+            &names,
+        ).unwrap();
+        let named: Vec<_> = registers.iter().map(|reg| (reg.number, reg.name.as_str())).collect();
+        assert_eq!(named, [(4, "pc"), (16, "cpsr"), (18, "d0")]);
+    }
+
+    #[test]
+    fn test_parse_registers_subset() {
+        let names = parse_register_names(&result(r#"^done,register-names=["r0","r1","r2","sp","lr","pc","cpsr"]"#));
+        // Reply to `-data-list-register-values x 1 5`
+        let registers = parse_registers(
+            &result(r#"^done,register-values=[{number="1",value="0x2a"},{number="5",value="0x8000"}]"#),
+            &names,
+        ).unwrap();
+
+        let named: Vec<_> = registers.iter().map(|reg| (reg.number, reg.name.as_str())).collect();
+        assert_eq!(named, [(1, "r1"), (5, "pc")]);
+    }
+
+    #[test]
+    fn test_parse_registers_structured_values() {
+        let names = parse_register_names(&result(r#"^done,register-names=["pc","xmm0"]"#));
+        let registers = parse_registers(
+            &result(r#"^done,register-values=[{number="0",value="134218020"},{number="1",value={v2_int64=["0","1"]}},{value="0x0"}]"#),
+            &names,
+        ).unwrap();
+
+        assert_eq!(registers.len(), 2);
+        assert_eq!(registers[0].value, "134218020");
+        assert_eq!(registers[1].value, "{v2_int64 = {0, 1}}");
+        assert_eq!(registers[1].vector_views().unwrap()[0].lanes, ["0", "1"]);
+    }
+
+    #[test]
+    fn test_parse_registers_keeps_tuple_values() {
+        let value = Value::Tuple(HashMap::from([
+            ("u64".to_string(), Value::String("0x1".to_string())),
+            ("f64".to_string(), Value::String("4.9e-324".to_string())),
+        ]));
+        let register = Value::Tuple(HashMap::from([
+            ("number".to_string(), Value::String("0".to_string())),
+            ("value".to_string(), value),
+        ]));
+        let result = GdbResult {
+            token: None,
+            class: gdbadapter::ResultClass::Done,
+            results: HashMap::from([("register-values".to_string(), Value::List(vec![register]))]),
+        };
+
+        let registers = parse_registers(&result, &[(0, "d0".to_string())]).unwrap();
+
+        assert_eq!(registers.len(), 1);
+        assert_eq!(registers[0].value, "{f64 = 4.9e-324, u64 = 0x1}");
+    }
+
+    #[test]
+    fn test_parse_registers_missing_field() {
+        assert!(parse_registers(&result("^done"), &[]).is_none());
+    }
+
+    #[test]
+    fn test_parse_assembly() {
+        let lines = parse_assembly(&result(
+            r#"^done,asm_insns=[{address="0x08000124",func-name="delay",offset="4",opcodes="00 bf",inst="nop"},{address="0x08000126",inst="bx\tlr"}]"#,
+        )).unwrap();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].function.as_deref(), Some("delay"));
+        assert_eq!(lines[0].offset, Some(4));
+        assert_eq!(lines[0].opcodes.as_deref(), Some("00 bf"));
+        assert_eq!(lines[1].instruction, "bx\tlr");
+        assert_eq!(lines[1].function, None);
+    }
+
+    #[test]
+    fn test_parse_assembly_with_source() {
+        let lines = parse_assembly(&result(
+            r#"^done,asm_insns=[src_and_asm_line={line="7",file="main.c",fullname="/src/main.c",line_asm_insn=[{address="0x08000124",inst="nop"},{address="0x08000126",inst="nop"}]},src_and_asm_line={line="8",file="main.c",line_asm_insn=[{address="0x08000128",inst="bx	lr"}]}]"#,
+        )).unwrap();
+
+        assert_eq!(lines.len(), 3);
+        let source = lines[0].source.as_ref().unwrap();
+        assert_eq!((source.file.as_str(), source.line), ("main.c", 7));
+        assert_eq!(source.fullname.as_deref(), Some("/src/main.c"));
+        assert_eq!(lines[1].source, lines[0].source);
+        assert_eq!(lines[2].source.as_ref().unwrap().line, 8);
+        assert_eq!(lines[2].instruction, "bx\tlr");
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        let result = result(r#"^done,memory=[{begin="0x20000000",offset="0x00000000",end="0x20000004",contents="deadbeef"}]"#);
+        let memory = parse_memory(result.results.get("memory").unwrap()).unwrap();
+
+        assert_eq!(memory.begin, "0x20000000");
+        assert_eq!(memory.end, "0x20000004");
+        assert_eq!(memory.contents, "deadbeef");
+    }
+}
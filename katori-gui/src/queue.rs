@@ -0,0 +1,70 @@
+//! FIFO queue between the UI and the command processor
+//!
+//! Commands run one at a time in the order the UI sent them, each holding the
+//! adapter lock only while it runs. The one exception is `RefreshDebugInfo`:
+//! only a single refresh is ever waiting, and a new request moves it behind
+//! everything queued since, so a refresh never runs earlier than the latest
+//! request for one. Rapid stepping or repeated stops therefore cost one
+//! refresh instead of one per event.
+
+use std::collections::VecDeque;
+
+use crate::GdbCommand;
+
+#[derive(Debug, Default)]
+pub struct CommandQueue {
+    commands: VecDeque<GdbCommand>,
+}
+
+impl CommandQueue {
+    pub fn push(&mut self, command: GdbCommand) {
+        if matches!(command, GdbCommand::RefreshDebugInfo) {
+            self.commands.retain(|queued| !matches!(queued, GdbCommand::RefreshDebugInfo));
+        }
+        self.commands.push_back(command);
+    }
+
+    pub fn pop(&mut self) -> Option<GdbCommand> {
+        self.commands.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_requests_collapse_to_one() {
+        let mut queue = CommandQueue::default();
+        for _ in 0..5 {
+            queue.push(GdbCommand::RefreshDebugInfo);
+        }
+
+        assert_eq!(queue.len(), 1);
+        assert!(matches!(queue.pop(), Some(GdbCommand::RefreshDebugInfo)));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_refresh_moves_behind_later_commands() {
+        let mut queue = CommandQueue::default();
+        queue.push(GdbCommand::RefreshDebugInfo);
+        queue.push(GdbCommand::StepOver);
+        queue.push(GdbCommand::RefreshDebugInfo);
+        queue.push(GdbCommand::RemoveBreakpoint(1));
+
+        let order: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert!(matches!(
+            order.as_slice(),
+            [GdbCommand::StepOver, GdbCommand::RefreshDebugInfo, GdbCommand::RemoveBreakpoint(1)]
+        ));
+    }
+}
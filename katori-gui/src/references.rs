@@ -0,0 +1,151 @@
+//! Find references to an address in already-fetched data
+//!
+//! Scans the register set, stack frames and memory blocks the UI already holds
+//! for a pointer-sized value, so tracing where a pointer is kept doesn't need
+//! extra round-trips to GDB.
+
+use gdbadapter::{parse_address, Register, RegisterFormat, StackFrame};
+
+use crate::memory::Endian;
+
+/// Where a value was found
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReferenceLocation {
+    Register(String),
+    /// Program counter of a stack frame (e.g. a return address)
+    Frame(u32),
+    /// Start address of a matching pointer-sized word in memory
+    Memory(u64),
+}
+
+impl std::fmt::Display for ReferenceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceLocation::Register(name) => write!(f, "register {name}"),
+            ReferenceLocation::Frame(level) => write!(f, "stack frame #{level}"),
+            ReferenceLocation::Memory(address) => write!(f, "memory at 0x{address:x}"),
+        }
+    }
+}
+
+/// Pointer width in bytes for a GDB architecture name, 32-bit unless known to be 64-bit
+pub fn pointer_size(arch: Option<&str>) -> usize {
+    let arch = arch.unwrap_or_default().to_ascii_lowercase();
+    if arch.contains("x86-64") || arch.starts_with("aarch64") || arch.contains("rv64") {
+        8
+    } else {
+        4
+    }
+}
+
+
+/// Parse a register value shown in `format`; structured values (vector registers) yield `None`.
+///
+/// Pointer values may carry a symbol annotation (`0x20000000 <buffer>`), so
+/// only the leading number is read.
+pub fn parse_register_value(value: &str, format: RegisterFormat) -> Option<u64> {
+    let value = value.split_whitespace().next()?;
+    match format {
+        RegisterFormat::Hex => parse_address(value),
+        RegisterFormat::Binary => u64::from_str_radix(value, 2).ok(),
+        RegisterFormat::Octal => u64::from_str_radix(value, 8).ok(),
+        // Negative decimals are two's complement pointers
+        RegisterFormat::Decimal | RegisterFormat::Natural => value.strip_prefix("0x")
+            .map_or_else(
+                || value.parse::<u64>().ok().or_else(|| value.parse::<i64>().ok().map(|v| v as u64)),
+                |hex| u64::from_str_radix(hex, 16).ok(),
+            ),
+    }
+}
+
+fn mask(value: u64, pointer_size: usize) -> u64 {
+    if pointer_size >= 8 { value } else { value & ((1u64 << (pointer_size * 8)) - 1) }
+}
+
+/// Registers holding `target`
+pub fn find_in_registers(target: u64, pointer_size: usize, registers: &[Register], format: RegisterFormat) -> Vec<ReferenceLocation> {
+    registers.iter()
+        .filter(|reg| parse_register_value(&reg.value, format).is_some_and(|value| mask(value, pointer_size) == mask(target, pointer_size)))
+        .map(|reg| ReferenceLocation::Register(reg.name.clone()))
+        .collect()
+}
+
+/// Stack frames whose program counter is `target`
+pub fn find_in_frames(target: u64, frames: &[StackFrame]) -> Vec<ReferenceLocation> {
+    frames.iter()
+        .filter(|frame| parse_address(&frame.address) == Some(target))
+        .map(|frame| ReferenceLocation::Frame(frame.level))
+        .collect()
+}
+
+/// Every offset in `bytes` (loaded from `base`) where `target` is stored as a pointer.
+///
+/// All byte offsets are checked, not just aligned ones, since packed structures
+/// and unaligned stores are common in what this is used to inspect.
+pub fn find_in_memory(target: u64, pointer_size: usize, endian: Endian, base: u64, bytes: &[u8]) -> Vec<ReferenceLocation> {
+    let target = mask(target, pointer_size);
+    let needle: Vec<u8> = match endian {
+        Endian::Little => target.to_le_bytes()[..pointer_size].to_vec(),
+        Endian::Big => target.to_be_bytes()[8 - pointer_size..].to_vec(),
+    };
+    bytes.windows(pointer_size)
+        .enumerate()
+        .filter(|(_, window)| *window == needle.as_slice())
+        .map(|(offset, _)| ReferenceLocation::Memory(base + offset as u64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reg(name: &str, value: &str) -> Register {
+        Register { number: 0, name: name.to_string(), value: value.to_string() }
+    }
+
+    #[test]
+    fn test_find_in_registers_respects_format() {
+        let registers = [reg("rsp", "0x7ffe0010"), reg("rax", "0x1"), reg("xmm0", "{v4_int32 = {0x7ffe0010}}")];
+        assert_eq!(
+            find_in_registers(0x7ffe0010, 8, &registers, RegisterFormat::Hex),
+            [ReferenceLocation::Register("rsp".to_string())]
+        );
+
+        let registers = [reg("sp", "-16"), reg("r0", "536870912")];
+        assert_eq!(
+            find_in_registers(0xfffffff0, 4, &registers, RegisterFormat::Decimal),
+            [ReferenceLocation::Register("sp".to_string())]
+        );
+        assert_eq!(find_in_registers(0x20000000, 4, &registers, RegisterFormat::Decimal).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_register_value_ignores_symbol_annotation() {
+        assert_eq!(parse_register_value("0x20000000 <buffer>", RegisterFormat::Hex), Some(0x20000000));
+        assert_eq!(parse_register_value("0x8000124 <main+4>", RegisterFormat::Natural), Some(0x8000124));
+        assert_eq!(parse_register_value("", RegisterFormat::Hex), None);
+        assert_eq!(parse_register_value("{v4_int32 = {0x1}}", RegisterFormat::Hex), None);
+    }
+
+    #[test]
+    fn test_find_in_memory_is_endianness_aware() {
+        let bytes = [0xaa, 0x00, 0x10, 0x00, 0x20, 0x20, 0x00, 0x00, 0x10];
+        assert_eq!(
+            find_in_memory(0x20001000, 4, Endian::Little, 0x1000, &bytes),
+            [ReferenceLocation::Memory(0x1001)]
+        );
+        assert_eq!(
+            find_in_memory(0x20000010, 4, Endian::Big, 0x1000, &bytes),
+            [ReferenceLocation::Memory(0x1005)]
+        );
+        assert!(find_in_memory(0x20001000, 8, Endian::Little, 0x1000, &bytes).is_empty());
+    }
+
+    #[test]
+    fn test_pointer_size() {
+        assert_eq!(pointer_size(Some("i386:x86-64")), 8);
+        assert_eq!(pointer_size(Some("aarch64")), 8);
+        assert_eq!(pointer_size(Some("armv7e-m")), 4);
+        assert_eq!(pointer_size(None), 4);
+    }
+}
@@ -0,0 +1,411 @@
+//! Register presentation helpers
+//!
+//! GDB numbers registers in its own internal order, which mixes flags and
+//! segment registers in with the general-purpose ones. This module orders
+//! them for display using per-architecture layouts, and picks out the core set
+//! fetched by default; the full set adds floating-point, system and debug
+//! registers.
+
+use gdbadapter::{Register, RegisterFormat};
+
+use crate::references::parse_register_value;
+
+/// Display group a register is placed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterGroup {
+    /// Program counter, stack pointer, link register and friends
+    Core,
+    General,
+    Special,
+    /// Registers outside the core set (debug, system...), only present when
+    /// all registers are fetched
+    System,
+    /// Floating-point registers (x87 stack, VFP singles, RISC-V `f`)
+    Float,
+    /// SIMD registers (XMM/YMM/ZMM, NEON), shown with their lanes
+    Vector,
+    /// Every register in GDB order, for architectures without a layout
+    All,
+}
+
+impl RegisterGroup {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RegisterGroup::Core => "Core",
+            RegisterGroup::General => "General",
+            RegisterGroup::Special => "Special",
+            RegisterGroup::System => "System",
+            RegisterGroup::Float => "Float",
+            RegisterGroup::Vector => "Vector",
+            RegisterGroup::All => "All",
+        }
+    }
+}
+
+/// Preferred register ordering for an architecture family
+struct RegisterLayout {
+    /// Prefixes of GDB architecture names (`arch` field) this layout applies to
+    arch_prefixes: &'static [&'static str],
+    /// Registers shown first, in this order
+    core: &'static [&'static str],
+    /// General-purpose registers, by name or numbered prefix (`r` matches `r0`..`r12`)
+    general: &'static [&'static str],
+    /// Status/system registers shown after the general ones, in this order
+    special: &'static [&'static str],
+    /// Floating-point registers, by name or numbered prefix (`st` matches `st0`..`st7`)
+    float: &'static [&'static str],
+    /// Prefixes of numbered vector registers (`xmm` matches `xmm0`..`xmm15`).
+    /// ARM `d` registers are unions when NEON is present, plain doubles otherwise.
+    vector: &'static [&'static str],
+}
+
+/// Known layouts, checked in order so more specific prefixes come first
+const REGISTER_LAYOUTS: &[RegisterLayout] = &[
+    RegisterLayout {
+        arch_prefixes: &["aarch64"],
+        core: &["pc", "sp", "x30", "x29"],
+        general: &["x"],
+        special: &["cpsr", "fpsr", "fpcr"],
+        float: &["s", "h"],
+        vector: &["v", "d"],
+    },
+    RegisterLayout {
+        arch_prefixes: &["arm"],
+        core: &["pc", "sp", "lr"],
+        general: &["r"],
+        special: &["cpsr", "xpsr", "fpscr", "msp", "psp", "primask", "basepri", "faultmask", "control"],
+        float: &["s"],
+        vector: &["q", "d"],
+    },
+    RegisterLayout {
+        arch_prefixes: &["i386:x86-64"],
+        core: &["rip", "rsp", "rbp"],
+        general: &["rax", "rbx", "rcx", "rdx", "rsi", "rdi", "r"],
+        special: &["eflags", "cs", "ss", "ds", "es", "fs", "gs", "fs_base", "gs_base", "k0", "k1", "k2", "k3", "k4", "k5", "k6", "k7"],
+        float: &["st", "fctrl", "fstat", "ftag", "fiseg", "fioff", "foseg", "fooff", "fop"],
+        vector: &["xmm", "ymm", "zmm"],
+    },
+    RegisterLayout {
+        arch_prefixes: &["i386", "i8086"],
+        core: &["eip", "esp", "ebp"],
+        general: &["eax", "ebx", "ecx", "edx", "esi", "edi"],
+        special: &["eflags", "cs", "ss", "ds", "es", "fs", "gs"],
+        float: &["st", "fctrl", "fstat", "ftag", "fiseg", "fioff", "foseg", "fooff", "fop"],
+        vector: &["xmm", "ymm"],
+    },
+    RegisterLayout {
+        arch_prefixes: &["riscv"],
+        core: &["pc", "sp", "ra", "fp"],
+        general: &["zero", "gp", "tp", "t", "s", "a", "x"],
+        special: &["mstatus", "mepc", "mcause", "mtval", "priv"],
+        float: &["f", "ft", "fs", "fa", "fflags", "frm", "fcsr"],
+        vector: &[],
+    },
+];
+
+fn layout_for(arch: &str) -> Option<&'static RegisterLayout> {
+    let arch = arch.to_ascii_lowercase();
+    REGISTER_LAYOUTS
+        .iter()
+        .find(|layout| layout.arch_prefixes.iter().any(|prefix| arch.starts_with(prefix)))
+}
+
+/// Whether `name` is a numbered register with one of the given prefixes (`xmm3`, `v12`)
+fn is_numbered(prefixes: &[&str], name: &str) -> bool {
+    prefixes.iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// Group of a register under `layout`
+fn classify(layout: &RegisterLayout, name: &str) -> RegisterGroup {
+    let named = |names: &[&str]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+    if named(layout.core) {
+        RegisterGroup::Core
+    } else if named(layout.special) {
+        RegisterGroup::Special
+    } else if is_numbered(layout.vector, name) {
+        RegisterGroup::Vector
+    } else if named(layout.float) || is_numbered(layout.float, name) {
+        RegisterGroup::Float
+    } else if named(layout.general) || is_numbered(layout.general, name) {
+        RegisterGroup::General
+    } else {
+        RegisterGroup::System
+    }
+}
+
+/// GDB numbers of the core register set (core, general and special groups),
+/// given `(number, name)` pairs from `-data-list-register-names`.
+///
+/// `None` for architectures without a layout, where every register is fetched.
+pub fn core_register_numbers(arch: Option<&str>, names: &[(usize, String)]) -> Option<Vec<u32>> {
+    let layout = arch.and_then(layout_for)?;
+    Some(
+        names.iter()
+            .filter(|(_, name)| !name.is_empty())
+            .filter(|(_, name)| matches!(classify(layout, name), RegisterGroup::Core | RegisterGroup::General | RegisterGroup::Special))
+            .map(|(number, _)| *number as u32)
+            .collect()
+    )
+}
+
+/// Whether the architecture has SIMD registers worth showing lane by lane
+pub fn has_vector_registers(arch: Option<&str>) -> bool {
+    arch.and_then(layout_for).is_some_and(|layout| !layout.vector.is_empty())
+}
+
+/// Whether an ARM target is executing Thumb code.
+///
+/// M-profile cores (`armv7e-m`, `armv8-m.main`) only run Thumb; otherwise the
+/// T bit of CPSR (bit 5) or xPSR (bit 24) decides. Other architectures never are.
+pub fn is_thumb_state(arch: Option<&str>, registers: &[Register], format: RegisterFormat) -> bool {
+    let Some(arch) = arch.map(str::to_ascii_lowercase).filter(|arch| arch.starts_with("arm")) else {
+        return false;
+    };
+    if arch.contains("-m") {
+        return true;
+    }
+    let bit = |name: &str, bit: u32| {
+        registers.iter()
+            .find(|reg| reg.name.eq_ignore_ascii_case(name))
+            .and_then(|reg| parse_register_value(&reg.value, format))
+            .map(|value| value & (1 << bit) != 0)
+    };
+    bit("cpsr", 5).or_else(|| bit("xpsr", 24)).unwrap_or(false)
+}
+
+/// Split registers into display groups using the layout for `arch`.
+///
+/// Core and special registers follow the layout's order; everything else keeps
+/// GDB's numbering. Registers outside the core set go in the system group,
+/// then floating-point, and vector registers are grouped last. Empty groups
+/// are omitted, and unknown architectures get a single "All" group in GDB order.
+pub fn group_registers<'a>(arch: Option<&str>, registers: &'a [Register]) -> Vec<(RegisterGroup, Vec<&'a Register>)> {
+    let Some(layout) = arch.and_then(layout_for) else {
+        return vec![(RegisterGroup::All, registers.iter().collect())];
+    };
+
+    let position = |names: &[&str], reg: &Register| {
+        names.iter().position(|name| name.eq_ignore_ascii_case(&reg.name))
+    };
+
+    let mut core: Vec<_> = registers.iter().filter(|reg| position(layout.core, reg).is_some()).collect();
+    core.sort_by_key(|reg| position(layout.core, reg));
+
+    let mut special: Vec<_> = registers.iter().filter(|reg| position(layout.special, reg).is_some()).collect();
+    special.sort_by_key(|reg| position(layout.special, reg));
+
+    let in_group = |group: RegisterGroup| -> Vec<&Register> {
+        registers.iter().filter(|reg| classify(layout, &reg.name) == group).collect()
+    };
+
+    [
+        (RegisterGroup::Core, core),
+        (RegisterGroup::General, in_group(RegisterGroup::General)),
+        (RegisterGroup::Special, special),
+        (RegisterGroup::System, in_group(RegisterGroup::System)),
+        (RegisterGroup::Float, in_group(RegisterGroup::Float)),
+        (RegisterGroup::Vector, in_group(RegisterGroup::Vector)),
+    ]
+        .into_iter()
+        .filter(|(_, regs)| !regs.is_empty())
+        .collect()
+}
+
+/// Registers worth reading at a stop while the `folded` groups are closed, or
+/// `None` when nothing is folded and the whole set is needed. Core and special
+/// registers are always read, because the status register decides whether the
+/// disassembly is Thumb code, and so are the `pinned` ones.
+pub fn visible_register_numbers(groups: &[(RegisterGroup, Vec<&Register>)], folded: &[RegisterGroup], pinned: &[u32]) -> Option<Vec<u32>> {
+    let hidden = |group: &RegisterGroup| folded.contains(group) && !matches!(group, RegisterGroup::Core | RegisterGroup::Special);
+    if !groups.iter().any(|(group, _)| hidden(group)) {
+        return None;
+    }
+    let mut numbers: Vec<u32> = groups.iter()
+        .filter(|(group, _)| !hidden(group))
+        .flat_map(|(_, registers)| registers.iter().map(|reg| reg.number))
+        .chain(pinned.iter().copied())
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    Some(numbers)
+}
+
+/// The registers called one of the `pinned` names, in the order they were pinned
+pub fn pinned_registers<'a>(registers: &'a [Register], pinned: &[String]) -> Vec<&'a Register> {
+    pinned.iter()
+        .filter_map(|name| registers.iter().find(|reg| reg.name.eq_ignore_ascii_case(name)))
+        .collect()
+}
+
+/// Take the values of registers read as a subset, keeping the others as they were
+pub fn merge_register_values(registers: &mut [Register], values: Vec<Register>) {
+    for value in values {
+        if let Some(register) = registers.iter_mut().find(|reg| reg.number == value.number) {
+            register.value = value.value;
+        }
+    }
+}
+
+/// All registers as `name = value` lines, in display order
+pub fn format_registers(arch: Option<&str>, registers: &[Register]) -> String {
+    group_registers(arch, registers)
+        .into_iter()
+        .flat_map(|(_, group)| group)
+        .map(|reg| format!("{} = {}", reg.name, reg.value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regs(names: &[&str]) -> Vec<Register> {
+        names.iter().enumerate()
+            .map(|(i, name)| Register { number: i as u32, name: name.to_string(), value: "0x0".to_string() })
+            .collect()
+    }
+
+    fn names<'a>(group: &[&'a Register]) -> Vec<&'a str> {
+        group.iter().map(|reg| reg.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_arm_layout_puts_pc_sp_lr_first() {
+        let registers = regs(&["r0", "r1", "sp", "lr", "pc", "cpsr", "r12"]);
+        let groups = group_registers(Some("armv7e-m"), &registers);
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].0, RegisterGroup::Core);
+        assert_eq!(names(&groups[0].1), ["pc", "sp", "lr"]);
+        assert_eq!(groups[1].0, RegisterGroup::General);
+        assert_eq!(names(&groups[1].1), ["r0", "r1", "r12"]);
+        assert_eq!(groups[2].0, RegisterGroup::Special);
+        assert_eq!(names(&groups[2].1), ["cpsr"]);
+    }
+
+    #[test]
+    fn test_folded_groups_are_left_out_of_refreshes() {
+        let registers = regs(&["r0", "sp", "pc", "cpsr", "s0", "s1"]);
+        let groups = group_registers(Some("armv7-a"), &registers);
+
+        assert_eq!(visible_register_numbers(&groups, &[], &[]), None);
+        assert_eq!(visible_register_numbers(&groups, &[RegisterGroup::Float], &[]), Some(vec![0, 1, 2, 3]));
+        // The status register stays, it decides the Thumb state
+        assert_eq!(visible_register_numbers(&groups, &[RegisterGroup::Special], &[]), None);
+        // Pinned registers are read even when their group is folded
+        assert_eq!(visible_register_numbers(&groups, &[RegisterGroup::Float], &[5, 2]), Some(vec![0, 1, 2, 3, 5]));
+
+        let mut registers = registers;
+        let read = vec![Register { number: 2, name: "r2".to_string(), value: "0x8000".to_string() }];
+        merge_register_values(&mut registers, read);
+        assert_eq!((registers[2].name.as_str(), registers[2].value.as_str()), ("pc", "0x8000"));
+        assert_eq!(registers.len(), 6);
+    }
+
+    #[test]
+    fn test_pinned_registers_keep_pin_order() {
+        let registers = regs(&["r0", "sp", "pc", "CPSR"]);
+
+        assert_eq!(names(&pinned_registers(&registers, &["pc".to_string(), "r0".to_string()])), ["pc", "r0"]);
+        // Names from another target that this one lacks are skipped
+        assert_eq!(names(&pinned_registers(&registers, &["cpsr".to_string(), "xpsr".to_string()])), ["CPSR"]);
+    }
+
+    #[test]
+    fn test_x86_64_is_not_treated_as_i386() {
+        let registers = regs(&["rax", "rbx", "rsp", "rip", "eflags", "cs"]);
+        let groups = group_registers(Some("i386:x86-64"), &registers);
+
+        assert_eq!(names(&groups[0].1), ["rip", "rsp"]);
+        assert_eq!(names(&groups[1].1), ["rax", "rbx"]);
+        assert_eq!(names(&groups[2].1), ["eflags", "cs"]);
+    }
+
+    #[test]
+    fn test_vector_registers_grouped_last() {
+        let registers = regs(&["rax", "xmm0", "xmm15", "xmmx", "rip", "ymm1", "mxcsr"]);
+        let groups = group_registers(Some("i386:x86-64"), &registers);
+
+        assert_eq!(groups.last().unwrap().0, RegisterGroup::Vector);
+        assert_eq!(names(&groups.last().unwrap().1), ["xmm0", "xmm15", "ymm1"]);
+        assert_eq!(names(&groups[1].1), ["rax"]);
+        assert_eq!(groups[2].0, RegisterGroup::System);
+        assert_eq!(names(&groups[2].1), ["xmmx", "mxcsr"]);
+
+        assert!(has_vector_registers(Some("aarch64")));
+        assert!(!has_vector_registers(Some("riscv:rv32")));
+        assert!(!has_vector_registers(None));
+    }
+
+    #[test]
+    fn test_core_register_numbers_skip_system_registers() {
+        let register_names: Vec<(usize, String)> = ["r0", "r12", "sp", "lr", "pc", "", "xpsr", "dhcsr", "d0", "msp"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (i, name.to_string()))
+            .collect();
+
+        assert_eq!(core_register_numbers(Some("armv7e-m"), &register_names), Some(vec![0, 1, 2, 3, 4, 6, 9]));
+        assert_eq!(core_register_numbers(Some("m68k"), &register_names), None);
+
+        let registers = regs(&["r0", "pc", "dhcsr"]);
+        let groups = group_registers(Some("armv7e-m"), &registers);
+        assert_eq!(groups.last().unwrap().0, RegisterGroup::System);
+        assert_eq!(names(&groups.last().unwrap().1), ["dhcsr"]);
+    }
+
+    #[test]
+    fn test_float_registers_grouped_outside_core_set() {
+        let registers = regs(&["rax", "st0", "st7", "fctrl", "fs", "xmm0", "rip"]);
+        let groups = group_registers(Some("i386:x86-64"), &registers);
+        let float = groups.iter().find(|(group, _)| *group == RegisterGroup::Float).unwrap();
+
+        assert_eq!(names(&float.1), ["st0", "st7", "fctrl"]);
+        assert_eq!(groups.last().unwrap().0, RegisterGroup::Vector);
+
+        let register_names: Vec<(usize, String)> = ["a0", "fa0", "fs1", "fcsr", "s1"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (i, name.to_string()))
+            .collect();
+        assert_eq!(core_register_numbers(Some("riscv:rv32"), &register_names), Some(vec![0, 4]));
+    }
+
+    #[test]
+    fn test_format_registers_in_display_order() {
+        let registers = regs(&["r0", "pc"]);
+
+        assert_eq!(format_registers(Some("armv7"), &registers), "pc = 0x0\nr0 = 0x0");
+    }
+
+    #[test]
+    fn test_thumb_state_from_arch_and_status_register() {
+        let mut registers = regs(&["pc", "cpsr"]);
+
+        assert!(is_thumb_state(Some("armv7e-m"), &registers, RegisterFormat::Hex));
+        assert!(!is_thumb_state(Some("armv7"), &registers, RegisterFormat::Hex));
+        registers[1].value = "0x600001f3".to_string();
+        assert!(is_thumb_state(Some("armv7"), &registers, RegisterFormat::Hex));
+        assert!(!is_thumb_state(Some("aarch64"), &registers, RegisterFormat::Hex));
+        assert!(!is_thumb_state(None, &registers, RegisterFormat::Hex));
+
+        let registers = vec![Register { number: 16, name: "xpsr".to_string(), value: "16777216".to_string() }];
+        assert!(is_thumb_state(Some("arm"), &registers, RegisterFormat::Natural));
+    }
+
+    #[test]
+    fn test_unknown_architecture_keeps_gdb_order() {
+        let registers = regs(&["b", "a", "pc"]);
+
+        for arch in [None, Some("m68k")] {
+            let groups = group_registers(arch, &registers);
+            assert_eq!(groups.len(), 1);
+            assert_eq!(groups[0].0, RegisterGroup::All);
+            assert_eq!(names(&groups[0].1), ["b", "a", "pc"]);
+        }
+    }
+}
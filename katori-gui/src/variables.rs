@@ -0,0 +1,268 @@
+//! Expandable watch values
+//!
+//! A watch expression can be opened into a tree of GDB variable objects: its
+//! root is created the first time it is expanded, children are listed a page
+//! at a time as nodes open, and `-var-update` changes are applied in place
+//! after every stop so open nodes stay open.
+
+use std::collections::{HashMap, HashSet};
+
+use gdbadapter::{VarObj, VarObjChange, VarObjChildren};
+
+/// Children listed per request; large arrays and containers page through the rest
+pub const VAROBJ_PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Default)]
+pub struct VarObjTree {
+    /// Root object of each expanded watch expression
+    roots: HashMap<String, VarObj>,
+    /// Children listed so far, by parent name
+    children: HashMap<String, Vec<VarObj>>,
+    /// Dynamic (pretty-printed) parents that reported more children than listed
+    more: HashSet<String>,
+    expanded: HashSet<String>,
+    /// Objects whose value changed in the last update
+    changed: HashSet<String>,
+    /// Objects GDB can't evaluate where the program is now
+    out_of_scope: HashSet<String>,
+}
+
+impl VarObjTree {
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    pub fn root(&self, expression: &str) -> Option<&VarObj> {
+        self.roots.get(expression)
+    }
+
+    /// Record the object created for a watch expression, shown open
+    pub fn set_root(&mut self, expression: &str, varobj: VarObj) {
+        self.expanded.insert(varobj.name.clone());
+        self.roots.insert(expression.to_string(), varobj);
+    }
+
+    /// Forget a watch expression's tree; returns the object to delete in GDB
+    pub fn remove_root(&mut self, expression: &str) -> Option<String> {
+        let root = self.roots.remove(expression)?;
+        self.forget_below(&root.name);
+        self.expanded.remove(&root.name);
+        self.changed.remove(&root.name);
+        self.out_of_scope.remove(&root.name);
+        Some(root.name)
+    }
+
+    /// Drop everything, e.g. when GDB exits and takes its objects along
+    pub fn clear(&mut self) {
+        *self = VarObjTree::default();
+    }
+
+    /// Children listed so far
+    pub fn children(&self, name: &str) -> &[VarObj] {
+        self.children.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn is_listed(&self, name: &str) -> bool {
+        self.children.contains_key(name)
+    }
+
+    /// Store children listed from index `from`, replacing any listed past it
+    pub fn add_children(&mut self, parent: &str, from: u32, listed: VarObjChildren) {
+        let children = self.children.entry(parent.to_string()).or_default();
+        children.truncate(from as usize);
+        children.extend(listed.children);
+        if listed.has_more {
+            self.more.insert(parent.to_string());
+        } else {
+            self.more.remove(parent);
+        }
+    }
+
+    /// Whether `parent` has children beyond the ones listed
+    pub fn has_more(&self, parent: &VarObj) -> bool {
+        let listed = self.children(&parent.name).len();
+        if parent.dynamic {
+            self.more.contains(&parent.name)
+        } else {
+            self.is_listed(&parent.name) && listed < parent.num_children as usize
+        }
+    }
+
+    /// Range of the next page of `parent`'s children
+    pub fn next_page(&self, parent: &str) -> (u32, u32) {
+        let from = self.children(parent).len() as u32;
+        (from, from + VAROBJ_PAGE_SIZE)
+    }
+
+    pub fn is_expanded(&self, name: &str) -> bool {
+        self.expanded.contains(name)
+    }
+
+    pub fn set_expanded(&mut self, name: &str, expanded: bool) {
+        if expanded {
+            self.expanded.insert(name.to_string());
+        } else {
+            self.expanded.remove(name);
+        }
+    }
+
+    pub fn is_changed(&self, name: &str) -> bool {
+        self.changed.contains(name)
+    }
+
+    pub fn is_out_of_scope(&self, name: &str) -> bool {
+        self.out_of_scope.contains(name)
+    }
+
+    /// Apply a `-var-update` changelist. Returns the open objects whose children
+    /// have to be listed again because their type changed.
+    pub fn apply_changes(&mut self, changes: &[VarObjChange]) -> Vec<String> {
+        self.changed.clear();
+        let mut relist = Vec::new();
+        for change in changes {
+            if change.is_in_scope() {
+                self.out_of_scope.remove(&change.name);
+            } else {
+                self.out_of_scope.insert(change.name.clone());
+            }
+            let Some(varobj) = find_mut(&mut self.roots, &mut self.children, &change.name) else {
+                continue;
+            };
+            if change.value.is_some() && change.value != varobj.value {
+                varobj.value = change.value.clone();
+                self.changed.insert(change.name.clone());
+            }
+            if let Some(num_children) = change.new_num_children {
+                varobj.num_children = num_children;
+            }
+            if change.type_changed {
+                varobj.type_name = change.new_type.clone().or(varobj.type_name.take());
+                varobj.has_more = change.has_more;
+                // The old children belong to the old type
+                self.forget_below(&change.name);
+                if self.is_expanded(&change.name) {
+                    relist.push(change.name.clone());
+                }
+                continue;
+            }
+            if let Some(children) = self.children.get_mut(&change.name) {
+                children.extend(change.new_children.iter().cloned());
+                if let Some(num_children) = change.new_num_children {
+                    children.truncate(num_children as usize);
+                }
+            }
+            if change.has_more {
+                self.more.insert(change.name.clone());
+            } else {
+                self.more.remove(&change.name);
+            }
+        }
+        relist
+    }
+
+    /// Forget the children listed under `name`, at any depth; child names extend their parent's
+    fn forget_below(&mut self, name: &str) {
+        let prefix = format!("{name}.");
+        let below = |key: &String| key == name || key.starts_with(&prefix);
+        self.children.retain(|key, _| !below(key));
+        self.more.retain(|key| !below(key));
+        self.expanded.retain(|key| key == name || !below(key));
+        self.changed.retain(|key| key == name || !below(key));
+        self.out_of_scope.retain(|key| key == name || !below(key));
+    }
+}
+
+/// The root or listed child called `name`
+fn find_mut<'a>(roots: &'a mut HashMap<String, VarObj>, children: &'a mut HashMap<String, Vec<VarObj>>, name: &str) -> Option<&'a mut VarObj> {
+    roots.values_mut()
+        .chain(children.values_mut().flatten())
+        .find(|varobj| varobj.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varobj(name: &str, expression: &str, num_children: u32, value: &str) -> VarObj {
+        VarObj {
+            name: name.to_string(),
+            expression: expression.to_string(),
+            num_children,
+            value: Some(value.to_string()),
+            type_name: Some("int".to_string()),
+            thread_id: None,
+            dynamic: false,
+            has_more: false,
+            display_hint: None,
+        }
+    }
+
+    fn change(name: &str, value: Option<&str>) -> VarObjChange {
+        VarObjChange {
+            name: name.to_string(),
+            value: value.map(str::to_string),
+            in_scope: "true".to_string(),
+            type_changed: false,
+            new_type: None,
+            new_num_children: None,
+            has_more: false,
+            new_children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_children_page_until_all_listed() {
+        let mut tree = VarObjTree::default();
+        tree.set_root("samples", varobj("var1", "samples", 150, "{...}"));
+        let root = tree.root("samples").unwrap().clone();
+        assert!(tree.is_expanded("var1"));
+        assert!(!tree.has_more(&root));
+        assert_eq!(tree.next_page("var1"), (0, VAROBJ_PAGE_SIZE));
+
+        let page = |from: u32, count: u32| VarObjChildren {
+            children: (from..from + count).map(|i| varobj(&format!("var1.{i}"), &i.to_string(), 0, "0")).collect(),
+            has_more: false,
+        };
+        tree.add_children("var1", 0, page(0, 100));
+        assert!(tree.has_more(&root));
+        assert_eq!(tree.next_page("var1"), (100, 200));
+        tree.add_children("var1", 100, page(100, 50));
+        assert!(!tree.has_more(&root));
+        assert_eq!(tree.children("var1").len(), 150);
+    }
+
+    #[test]
+    fn test_updates_mark_changes_and_relist_retyped_objects() {
+        let mut tree = VarObjTree::default();
+        tree.set_root("p", varobj("var1", "p", 2, "{...}"));
+        tree.add_children("var1", 0, VarObjChildren {
+            children: vec![varobj("var1.x", "x", 0, "1"), varobj("var1.next", "next", 1, "0x0")],
+            has_more: false,
+        });
+        tree.set_expanded("var1.next", true);
+        tree.add_children("var1.next", 0, VarObjChildren { children: vec![varobj("var1.next.*next", "*next", 2, "{...}")], has_more: false });
+
+        let relist = tree.apply_changes(&[change("var1.x", Some("5")), change("var1.next", Some("0x4052a0"))]);
+        assert!(relist.is_empty());
+        assert!(tree.is_changed("var1.x") && tree.is_changed("var1.next"));
+        assert_eq!(tree.children("var1")[0].value.as_deref(), Some("5"));
+
+        // A floating object evaluated in another frame can change type entirely
+        let mut retyped = change("var1", Some("{...}"));
+        retyped.type_changed = true;
+        retyped.new_type = Some("struct node".to_string());
+        retyped.new_num_children = Some(3);
+        let mut gone = change("var2", None);
+        gone.in_scope = "false".to_string();
+        let relist = tree.apply_changes(&[retyped, gone]);
+        assert_eq!(relist, vec!["var1".to_string()]);
+        assert!(!tree.is_changed("var1.x"));
+        assert!(!tree.is_listed("var1") && !tree.is_listed("var1.next"));
+        assert!(!tree.is_expanded("var1.next") && tree.is_expanded("var1"));
+        assert_eq!(tree.root("p").unwrap().type_name.as_deref(), Some("struct node"));
+        assert!(tree.is_out_of_scope("var2"));
+
+        assert_eq!(tree.remove_root("p"), Some("var1".to_string()));
+        assert!(tree.is_empty());
+    }
+}
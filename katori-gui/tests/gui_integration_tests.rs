@@ -0,0 +1,115 @@
+//! Integration tests for the GUI state
+//!
+//! These drive a headless `KatoriApp` through its public API, without a window
+
+use katori_gui::{AttachMode, KatoriApp, Panel};
+
+#[tokio::test]
+async fn test_initial_state() {
+    let app = KatoriApp::headless();
+
+    assert!(!app.is_debugging());
+    assert_eq!(app.get_host_port(), "localhost:1337");
+    assert_eq!(app.get_attach_mode(), &AttachMode::GdbServer);
+    assert!(app.get_console_output().contains("Welcome to Katori"));
+    assert!(app.get_breakpoints().is_empty());
+}
+
+#[tokio::test]
+async fn test_console_messages_are_appended_as_lines() {
+    let mut app = KatoriApp::headless();
+
+    app.add_console_message("first");
+    app.add_console_message("second\n");
+
+    assert!(app.get_console_output().ends_with("first\nsecond\n"));
+}
+
+#[tokio::test]
+async fn test_attach_settings() {
+    let mut app = KatoriApp::headless();
+
+    app.set_attach_mode(AttachMode::Process);
+    app.set_host_port("192.168.1.10:3333");
+
+    assert_eq!(app.get_attach_mode(), &AttachMode::Process);
+    assert_eq!(app.get_host_port(), "192.168.1.10:3333");
+}
+
+#[tokio::test]
+async fn test_core_dump_needs_both_files() {
+    let mut app = KatoriApp::headless();
+
+    app.set_attach_mode(AttachMode::CoreDump);
+    app.attach_to_target();
+
+    assert!(app.get_console_output().contains("needs both the executable and the core file"));
+    assert!(!app.is_debugging());
+}
+
+#[tokio::test]
+async fn test_panel_visibility() {
+    let mut app = KatoriApp::headless();
+
+    assert!(app.is_panel_visible(Panel::Registers));
+    assert!(!app.is_panel_visible(Panel::Memory));
+    // Shows itself once the program prints something
+    assert!(!app.is_panel_visible(Panel::ProgramOutput));
+
+    app.set_panel_visible(Panel::Registers, false);
+    app.set_panel_visible(Panel::Memory, true);
+
+    assert!(!app.is_panel_visible(Panel::Registers));
+    assert!(app.is_panel_visible(Panel::Memory));
+}
+
+#[tokio::test]
+async fn test_breakpoint_waits_for_gdb_confirmation() {
+    let mut app = KatoriApp::headless();
+
+    app.add_breakpoint("main");
+
+    // The input is consumed, but the list only fills once GDB reports the breakpoint
+    assert_eq!(app.get_breakpoint_input(), "");
+    assert!(app.get_breakpoints().is_empty());
+    assert!(app.get_console_output().contains("Setting breakpoint at: main"));
+}
+
+#[tokio::test]
+async fn test_invalid_breakpoint_locations_are_not_sent() {
+    let mut app = KatoriApp::headless();
+
+    app.add_breakpoint("main.c:0");
+    app.add_breakpoint("*");
+
+    assert!(app.get_console_output().contains("Invalid breakpoint location: Line numbers start at 1"));
+    assert!(app.get_console_output().contains("Invalid breakpoint location: No address given"));
+    assert!(!app.get_console_output().contains("Setting breakpoint at"));
+
+    app.add_breakpoint("main.c:12");
+    assert!(app.get_console_output().contains("Setting breakpoint at: main.c:12"));
+}
+
+#[tokio::test]
+async fn test_stop_session_resets_state() {
+    let mut app = KatoriApp::headless();
+
+    app.stop_gdb_session();
+
+    assert!(!app.is_debugging());
+    assert!(app.get_console_output().contains("Stopping GDB session..."));
+}
+
+#[tokio::test]
+async fn test_pinned_registers_outlive_the_session() {
+    let mut app = KatoriApp::headless();
+
+    app.toggle_register_pin("pc");
+    app.toggle_register_pin("sp");
+    app.toggle_register_pin("r7");
+    app.toggle_register_pin("SP");
+    assert_eq!(app.get_pinned_registers(), ["pc", "r7"]);
+
+    app.stop_gdb_session();
+    assert_eq!(app.get_pinned_registers(), ["pc", "r7"]);
+}
@@ -1,7 +1,7 @@
-use gdbadapter::GdbAdapter;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     // Initialize the logger first
     simple_logger::SimpleLogger::new()
         .with_level(log::LevelFilter::Debug)
@@ -15,12 +15,13 @@ async fn main() {
     
     log::info!("Katori - GDB Frontend starting...");
     
-    // Initialize the GDB adapter (will be used later)
-    let _gdb = GdbAdapter::new();
-    
     // Start the GUI application
     let exit_code = katori_gui::run_gui();
     
     log::info!("Application exited with code: {exit_code}");
-    std::process::exit(exit_code);
+    
+    // Return instead of calling process::exit so the runtime shuts down and
+    // drops the GDB adapter, killing any GDB process that is still alive.
+    // Codes outside 0..=255 would wrap, possibly to 0, so report them as a failure
+    u8::try_from(exit_code).map_or(ExitCode::FAILURE, ExitCode::from)
 }